@@ -1,6 +1,6 @@
 use anyhow::{Result, anyhow};
 
-use crate::hl_api::{PerpMeta, SpotMeta};
+use crate::hl_api::{PerpMeta, SpotMeta, SpotUniverse};
 
 /// Resolve Hyperliquid asset index for a given `coin`.
 ///
@@ -39,6 +39,192 @@ pub fn resolve_asset_index(
     Err(anyhow!("Unknown coin: {coin}"))
 }
 
+/// Reverse of [`resolve_asset_index`]: the coin name for an asset index.
+pub fn coin_for_asset(
+    all_perp_metas: &[PerpMeta],
+    spot_meta: &SpotMeta,
+    asset: u32,
+) -> Option<String> {
+    let (dex_index, market_index) = match asset {
+        0..10_000 => (0, asset),
+        10_000..100_000 => {
+            return spot_meta
+                .universe
+                .get((asset - 10_000) as usize)
+                .map(|pair| pair.name.clone());
+        }
+        _ => ((asset - 100_000) / 10_000, (asset - 100_000) % 10_000),
+    };
+    all_perp_metas
+        .get(dex_index as usize)?
+        .universe
+        .get(market_index as usize)
+        .map(|a| a.name.clone())
+}
+
+/// Builder dex of a perp coin (`xyz` for `xyz:TSLA`); `None` for main-dex
+/// perps and spot pairs.
+pub fn coin_dex(coin: &str) -> Option<&str> {
+    coin.split_once(':').map(|(dex, _)| dex)
+}
+
+/// Name of the perp dex at `dex_index` in `allPerpMetas`: empty for the main
+/// dex, otherwise the prefix of its market names. `None` for a builder dex
+/// with no markets.
+pub fn perp_dex_name(dex_index: usize, meta: &PerpMeta) -> Option<String> {
+    if dex_index == 0 {
+        return Some(String::new());
+    }
+    meta.universe
+        .first()
+        .and_then(|m| coin_dex(&m.name))
+        .map(str::to_string)
+}
+
+/// Friendly `BASE/QUOTE` name of a spot pair, from its token indices.
+pub fn spot_pair_symbol(spot_meta: &SpotMeta, pair: &SpotUniverse) -> Option<String> {
+    let token = |idx: &u32| spot_meta.tokens.iter().find(|t| t.index == *idx);
+    match pair.tokens.as_slice() {
+        [base, quote] => Some(format!("{}/{}", token(base)?.name, token(quote)?.name)),
+        _ => None,
+    }
+}
+
+/// Resolve a coin as typed by the user to the name the exchange uses.
+///
+/// Accepts perp names, builder-dex perps qualified (`xyz:TSLA`) or bare
+/// (`TSLA`, when only one dex lists it and the main dex doesn't), spot pair
+/// names as listed (`@107`, `PURR/USDC`) and spot pairs by token names
+/// (`HYPE/USDC`), all case-insensitively. Exact matches win. Unknown coins
+/// error with the closest known names.
+pub fn resolve_coin(
+    all_perp_metas: &[PerpMeta],
+    spot_meta: &SpotMeta,
+    input: &str,
+) -> Result<String> {
+    let input = input.trim();
+    let perp_names = || {
+        all_perp_metas
+            .iter()
+            .flat_map(|dex| dex.universe.iter())
+            .map(|a| a.name.as_str())
+    };
+    if perp_names()
+        .chain(spot_meta.universe.iter().map(|p| p.name.as_str()))
+        .any(|n| n == input)
+    {
+        return Ok(input.to_string());
+    }
+
+    let mut matches: Vec<&str> = perp_names()
+        .filter(|n| n.eq_ignore_ascii_case(input))
+        .collect();
+    if matches.is_empty() && !input.contains(':') {
+        matches = perp_names()
+            .filter(|n| {
+                n.split_once(':')
+                    .is_some_and(|(_, name)| name.eq_ignore_ascii_case(input))
+            })
+            .collect();
+    }
+    if matches.is_empty() {
+        matches = spot_meta
+            .universe
+            .iter()
+            .filter(|pair| {
+                pair.name.eq_ignore_ascii_case(input)
+                    || spot_pair_symbol(spot_meta, pair)
+                        .is_some_and(|symbol| symbol.eq_ignore_ascii_case(input))
+            })
+            .map(|pair| pair.name.as_str())
+            .collect();
+    }
+    match matches.as_slice() {
+        [name] => Ok(name.to_string()),
+        [] => {
+            let suggestions = suggest_coins(all_perp_metas, spot_meta, input);
+            if suggestions.is_empty() {
+                Err(anyhow!("Unknown coin: {input}"))
+            } else {
+                Err(anyhow!(
+                    "Unknown coin: {input}. Did you mean: {}?",
+                    suggestions.join(", ")
+                ))
+            }
+        }
+        _ => Err(anyhow!(
+            "Ambiguous coin: {input} matches {}",
+            matches.join(", ")
+        )),
+    }
+}
+
+/// Up to three known coins closest to `input` by edit distance.
+fn suggest_coins(all_perp_metas: &[PerpMeta], spot_meta: &SpotMeta, input: &str) -> Vec<String> {
+    let input = input.to_ascii_uppercase();
+    let max_distance = (input.len() / 3).max(1);
+    let mut candidates: Vec<(usize, String)> = all_perp_metas
+        .iter()
+        .flat_map(|dex| dex.universe.iter())
+        .filter(|a| !a.is_delisted)
+        .map(|a| a.name.clone())
+        .chain(
+            spot_meta
+                .universe
+                .iter()
+                .filter_map(|pair| spot_pair_symbol(spot_meta, pair)),
+        )
+        .filter_map(|name| {
+            let distance = edit_distance(&input, &name.to_ascii_uppercase());
+            (distance <= max_distance).then_some((distance, name))
+        })
+        .collect();
+    candidates.sort();
+    candidates.dedup_by(|a, b| a.1 == b.1);
+    candidates
+        .into_iter()
+        .take(3)
+        .map(|(_, name)| name)
+        .collect()
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            row.push((prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// Size decimals for `coin`: the perp asset's `szDecimals`, or the base
+/// token's `szDecimals` for a spot pair.
+pub fn size_decimals(all_perp_metas: &[PerpMeta], spot_meta: &SpotMeta, coin: &str) -> Result<u32> {
+    if let Some(asset) = all_perp_metas
+        .iter()
+        .flat_map(|dex| dex.universe.iter())
+        .find(|a| a.name == coin)
+    {
+        return Ok(asset.sz_decimals);
+    }
+
+    if let Some(pair) = spot_meta.universe.iter().find(|a| a.name == coin) {
+        let base = pair
+            .tokens
+            .first()
+            .and_then(|idx| spot_meta.tokens.iter().find(|t| t.index == *idx))
+            .ok_or_else(|| anyhow!("Missing base token for {coin}"))?;
+        return Ok(base.sz_decimals);
+    }
+
+    Err(anyhow!("Unknown coin: {coin}"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,6 +307,90 @@ mod tests {
         assert_eq!(idx, 10_001);
     }
 
+    #[test]
+    fn coin_for_asset_reverses_index() {
+        let perps = vec![
+            perp_meta(&["BTC", "ETH"]),
+            perp_meta(&["DEX:AAA", "DEX:BBB"]),
+        ];
+        let spot = spot_meta(&["PURR/USDC", "ABC/USDC"]);
+        for coin in ["ETH", "DEX:BBB", "ABC/USDC"] {
+            let idx = resolve_asset_index(&perps, &spot, coin).unwrap();
+            assert_eq!(coin_for_asset(&perps, &spot, idx).as_deref(), Some(coin));
+        }
+        assert_eq!(coin_for_asset(&perps, &spot, 7), None);
+    }
+
+    #[test]
+    fn size_decimals_for_perp_and_spot() {
+        let perps = vec![perp_meta(&["BTC"])];
+        let mut spot = spot_meta(&["PURR/USDC"]);
+        spot.tokens[1].sz_decimals = 0;
+        assert_eq!(size_decimals(&perps, &spot, "BTC").unwrap(), 2);
+        assert_eq!(size_decimals(&perps, &spot, "PURR/USDC").unwrap(), 0);
+        assert!(size_decimals(&perps, &spot, "NOPE").is_err());
+    }
+
+    #[test]
+    fn resolves_spot_pairs_by_token_names() {
+        let perps = vec![perp_meta(&["BTC", "kPEPE"])];
+        let spot = spot_meta(&["PURR/USDC"]);
+        assert_eq!(resolve_coin(&perps, &spot, "kpepe").unwrap(), "kPEPE");
+        assert_eq!(
+            resolve_coin(&perps, &spot, "purr/usdc").unwrap(),
+            "PURR/USDC"
+        );
+
+        let mut spot = spot_meta(&["@107"]);
+        spot.tokens[1].name = "HYPE".to_string();
+        assert_eq!(resolve_coin(&perps, &spot, "@107").unwrap(), "@107");
+        assert_eq!(resolve_coin(&perps, &spot, "hype/usdc").unwrap(), "@107");
+        assert_eq!(
+            spot_pair_symbol(&spot, &spot.universe[0]).as_deref(),
+            Some("HYPE/USDC")
+        );
+    }
+
+    #[test]
+    fn resolves_builder_dex_coins() {
+        let perps = vec![
+            perp_meta(&["BTC", "ETH"]),
+            perp_meta(&["xyz:TSLA", "xyz:ETH"]),
+            perp_meta(&["abc:TSLA", "abc:GOLD"]),
+        ];
+        let spot = spot_meta(&[]);
+        assert_eq!(resolve_coin(&perps, &spot, "XYZ:tsla").unwrap(), "xyz:TSLA");
+        assert_eq!(resolve_coin(&perps, &spot, "gold").unwrap(), "abc:GOLD");
+        assert_eq!(resolve_coin(&perps, &spot, "eth").unwrap(), "ETH");
+        let err = resolve_coin(&perps, &spot, "TSLA").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Ambiguous coin: TSLA matches xyz:TSLA, abc:TSLA"
+        );
+        assert!(resolve_coin(&perps, &spot, "abc:ETH").is_err());
+
+        assert_eq!(coin_dex("xyz:TSLA"), Some("xyz"));
+        assert_eq!(coin_dex("ETH"), None);
+        assert_eq!(perp_dex_name(0, &perps[0]).as_deref(), Some(""));
+        assert_eq!(perp_dex_name(2, &perps[2]).as_deref(), Some("abc"));
+        assert_eq!(perp_dex_name(1, &perp_meta(&[])), None);
+    }
+
+    #[test]
+    fn unknown_coin_suggests_close_names() {
+        let perps = vec![perp_meta(&["BTC", "ETH"])];
+        let spot = spot_meta(&["PURR/USDC"]);
+        let err = resolve_coin(&perps, &spot, "ETC").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown coin: ETC. Did you mean: BTC, ETH?"
+        );
+        let err = resolve_coin(&perps, &spot, "PUR/USDC").unwrap_err();
+        assert!(err.to_string().ends_with("Did you mean: PURR/USDC?"));
+        let err = resolve_coin(&perps, &spot, "ZZZZZZ").unwrap_err();
+        assert_eq!(err.to_string(), "Unknown coin: ZZZZZZ");
+    }
+
     #[test]
     fn unknown_coin_errors() {
         let perps = vec![perp_meta(&["BTC"])];
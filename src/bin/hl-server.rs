@@ -3,13 +3,22 @@ use std::{
     fs,
     io::Write,
     path::PathBuf,
+    str::FromStr,
     sync::Arc,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use clap::Parser;
 use daemonize::Daemonize;
+use ethers::{signers::LocalWallet, types::Address};
+use hyperliquid::{
+    Exchange, Hyperliquid,
+    types::{
+        Chain,
+        exchange::response::{Response as ExchangeResponse, Status, StatusType},
+    },
+};
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
     net::{UnixListener, UnixStream},
@@ -17,9 +26,17 @@ use tokio::{
 };
 
 use hyperliquid_cli::{
+    asset_index, config,
     hl_api::{AllDexsAssetCtxsEvent, HlApi, PerpMeta, SpotAssetCtx, SpotMeta},
+    l1_action::L1Action,
     paths,
-    server::types::{CacheStatus, ServerStatus},
+    rounding::AssetPrecision,
+    server::types::{
+        AddTrailParams, ArmDeadManParams, CacheStatus, DeadManStatus, ServerStatus, TrailState,
+        TrailStatus,
+    },
+    trail::TrailingStop,
+    validation,
 };
 
 #[derive(Parser, Debug)]
@@ -177,6 +194,206 @@ impl Default for ServerCache {
     }
 }
 
+/// Dead-man's switch the server re-arms while its heartbeat stays fresh.
+/// Once heartbeats stop, the server stops re-arming and the exchange cancels
+/// all open orders at the last scheduled time.
+struct DeadManState {
+    wallet: Arc<LocalWallet>,
+    vault: Option<Address>,
+    timeout_ms: i64,
+    heartbeat_file: Option<PathBuf>,
+    last_heartbeat: i64,
+    last_armed: i64,
+    cancel_at: i64,
+    last_error: Option<String>,
+}
+
+type DeadMan = Arc<RwLock<Option<DeadManState>>>;
+
+impl DeadManState {
+    /// Alive if a heartbeat request arrived, or the heartbeat file was
+    /// touched, within the timeout.
+    fn is_alive(&self, now: i64) -> bool {
+        if now - self.last_heartbeat < self.timeout_ms {
+            return true;
+        }
+        self.heartbeat_file
+            .as_ref()
+            .and_then(|path| fs::metadata(path).ok())
+            .and_then(|meta| meta.modified().ok())
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+            .is_some_and(|modified| now - (modified.as_millis() as i64) < self.timeout_ms)
+    }
+
+    /// Re-arm a third of the way into the window, leaving time for retries.
+    fn needs_rearm(&self, now: i64) -> bool {
+        now - self.last_armed >= self.timeout_ms / 3
+    }
+
+    fn status(&self) -> DeadManStatus {
+        DeadManStatus {
+            timeout_ms: self.timeout_ms,
+            heartbeat_file: self
+                .heartbeat_file
+                .as_ref()
+                .map(|p| p.display().to_string()),
+            last_heartbeat: self.last_heartbeat,
+            cancel_at: self.cancel_at,
+            last_error: self.last_error.clone(),
+            vault: self.vault.map(|v| format!("{v:#x}")),
+        }
+    }
+}
+
+/// Signer for the default account, which must match the account that armed
+/// the switch.
+fn load_signer(testnet: bool, user: &str) -> Result<Arc<LocalWallet>> {
+    let user = validation::validate_address(user)?;
+    let cfg = config::load_config(testnet)?;
+    if cfg.wallet_address != Some(user) {
+        return Err(anyhow!(
+            "rhl-server signs for a different account than {user:#x}; restart it with that account as default"
+        ));
+    }
+    let pk = cfg
+        .private_key
+        .ok_or_else(|| anyhow!("No API wallet configured for {user:#x}"))?;
+    let pk = validation::validate_private_key(&pk)?;
+    Ok(Arc::new(
+        LocalWallet::from_str(&pk).context("Invalid private key")?,
+    ))
+}
+
+fn parse_vault(vault: Option<&str>) -> Result<Option<Address>> {
+    vault.map(validation::validate_address).transpose()
+}
+
+/// A trailing stop being followed, with what the server needs to close the
+/// position when it fires.
+struct TrailEntry {
+    wallet: Arc<LocalWallet>,
+    vault: Option<Address>,
+    asset: u32,
+    precision: AssetPrecision,
+    slippage: f64,
+    status: TrailStatus,
+}
+
+/// Trailing stops registered with `rhl order trail`. Fired and failed trails
+/// stay listed until removed, so their outcome can be checked later.
+#[derive(Default)]
+struct TrailBook {
+    next_id: u64,
+    entries: Vec<TrailEntry>,
+}
+
+type Trails = Arc<RwLock<TrailBook>>;
+
+impl TrailBook {
+    fn add(
+        &mut self,
+        wallet: Arc<LocalWallet>,
+        vault: Option<Address>,
+        params: AddTrailParams,
+        now: i64,
+    ) -> TrailStatus {
+        self.next_id += 1;
+        let stop = TrailingStop::new(params.is_long, params.distance, params.start_px);
+        let status = TrailStatus {
+            id: self.next_id,
+            vault: vault.map(|v| format!("{v:#x}")),
+            coin: params.coin,
+            size: params.size,
+            stop,
+            stop_px: stop.stop_px(),
+            last_px: None,
+            created_at: now,
+            state: TrailState::Active,
+            result: None,
+        };
+        self.entries.push(TrailEntry {
+            wallet,
+            vault,
+            asset: params.asset,
+            precision: AssetPrecision {
+                sz_decimals: params.sz_decimals,
+                is_spot: false,
+            },
+            slippage: params.slippage,
+            status: status.clone(),
+        });
+        status
+    }
+
+    fn active(&self) -> impl Iterator<Item = &TrailEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.status.state == TrailState::Active)
+    }
+
+    /// Follow new mid prices. Trails whose stop was crossed are marked firing
+    /// and their ids returned.
+    fn update(&mut self, mids: &HashMap<String, String>) -> Vec<u64> {
+        let mut crossed = Vec::new();
+        for entry in &mut self.entries {
+            let status = &mut entry.status;
+            if status.state != TrailState::Active {
+                continue;
+            }
+            let Some(px) = mids.get(&status.coin).and_then(|p| p.parse::<f64>().ok()) else {
+                continue;
+            };
+            status.last_px = Some(px);
+            if status.stop.update(px) {
+                status.state = TrailState::Firing;
+                crossed.push(status.id);
+            }
+            status.stop_px = status.stop.stop_px();
+        }
+        crossed
+    }
+
+    /// Remove one trail, or all of them without an `id`.
+    fn remove(&mut self, id: Option<u64>) -> usize {
+        let before = self.entries.len();
+        self.entries
+            .retain(|e| id.is_some_and(|id| e.status.id != id));
+        before - self.entries.len()
+    }
+}
+
+/// Trail state and result message for the response to a closing order.
+fn trail_close_result(resp: Result<ExchangeResponse>) -> (TrailState, String) {
+    let status = match resp {
+        Ok(ExchangeResponse::Ok(data)) => match data.data {
+            Some(StatusType::Statuses(statuses)) => statuses.into_iter().next(),
+            Some(StatusType::Status(status)) => Some(status),
+            _ => None,
+        },
+        Ok(ExchangeResponse::Err(e)) => return (TrailState::Failed, e),
+        Err(e) => return (TrailState::Failed, e.to_string()),
+    };
+    match status {
+        Some(Status::Filled(f)) => (
+            TrailState::Fired,
+            format!("Filled {} @ {}", f.total_sz, f.avg_px),
+        ),
+        Some(Status::Error(e)) => (TrailState::Failed, e),
+        Some(Status::Resting(r)) => (TrailState::Fired, format!("Order {} resting", r.oid)),
+        _ => (TrailState::Fired, "Close order sent".to_string()),
+    }
+}
+
+fn schedule_cancel_result(resp: ExchangeResponse) -> Result<()> {
+    match resp {
+        ExchangeResponse::Err(e) => Err(anyhow!(e)),
+        ExchangeResponse::Ok(data) => match data.data {
+            Some(StatusType::Status(Status::Error(e))) => Err(anyhow!(e)),
+            _ => Ok(()),
+        },
+    }
+}
+
 #[derive(serde::Deserialize)]
 struct RpcRequest {
     id: String,
@@ -331,6 +548,8 @@ async fn server_main(testnet: bool, started_at: i64, logger: Logger) -> Result<(
     let api = HlApi::new(testnet)?;
 
     let cache = Arc::new(RwLock::new(ServerCache::default()));
+    let dead_man = DeadMan::default();
+    let trails = Trails::default();
     let (shutdown_tx, shutdown_rx) = watch::channel(false);
 
     spawn_pollers(
@@ -339,6 +558,26 @@ async fn server_main(testnet: bool, started_at: i64, logger: Logger) -> Result<(
         shutdown_rx.clone(),
         logger.clone(),
     );
+    spawn_dead_man(
+        testnet,
+        dead_man.clone(),
+        shutdown_rx.clone(),
+        logger.clone(),
+    );
+    spawn_trails(
+        api.clone(),
+        cache.clone(),
+        trails.clone(),
+        shutdown_rx.clone(),
+        logger.clone(),
+    );
+    let state = ServerState {
+        cache,
+        dead_man,
+        trails,
+        testnet,
+        started_at,
+    };
 
     let socket_path = paths::server_socket_path()?;
     let listener = UnixListener::bind(&socket_path)
@@ -376,11 +615,11 @@ async fn server_main(testnet: bool, started_at: i64, logger: Logger) -> Result<(
             }
             accept_res = listener.accept() => {
                 let (stream, _) = accept_res?;
-                let cache = cache.clone();
+                let state = state.clone();
                 let shutdown_tx = shutdown_tx.clone();
                 let logger = logger.clone();
                 tokio::spawn(async move {
-                    if let Err(e) = handle_connection(stream, cache, shutdown_tx, testnet, started_at, logger).await {
+                    if let Err(e) = handle_connection(stream, state, shutdown_tx, logger).await {
                         // Most errors here are client disconnects; keep log light.
                         let _ = e;
                     }
@@ -404,11 +643,11 @@ async fn server_main(testnet: bool, started_at: i64, logger: Logger) -> Result<(
             }
             accept_res = listener.accept() => {
                 let (stream, _) = accept_res?;
-                let cache = cache.clone();
+                let state = state.clone();
                 let shutdown_tx = shutdown_tx.clone();
                 let logger = logger.clone();
                 tokio::spawn(async move {
-                    if let Err(e) = handle_connection(stream, cache, shutdown_tx, testnet, started_at, logger).await {
+                    if let Err(e) = handle_connection(stream, state, shutdown_tx, logger).await {
                         let _ = e;
                     }
                 });
@@ -417,6 +656,16 @@ async fn server_main(testnet: bool, started_at: i64, logger: Logger) -> Result<(
     }
 
     logger.log("Server stopping...");
+    if let Some(dead_man) = state.dead_man.read().await.as_ref() {
+        logger.log(format!(
+            "Dead-man switch no longer re-armed; open orders cancel at {}",
+            dead_man.cancel_at
+        ));
+    }
+    let active_trails = state.trails.read().await.active().count();
+    if active_trails > 0 {
+        logger.log(format!("Dropping {active_trails} active trailing stops"));
+    }
     cleanup_files(&logger);
     Ok(())
 }
@@ -570,12 +819,164 @@ fn spawn_pollers(
     }
 }
 
-async fn handle_connection(
-    stream: UnixStream,
+fn spawn_dead_man(
+    testnet: bool,
+    dead_man: DeadMan,
+    mut shutdown: watch::Receiver<bool>,
+    logger: Logger,
+) {
+    tokio::spawn(async move {
+        let exchange = Exchange::new(if testnet {
+            Chain::ArbitrumTestnet
+        } else {
+            Chain::Arbitrum
+        });
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => {
+                    if *shutdown.borrow() { break; }
+                }
+                _ = interval.tick() => {
+                    let now = now_ms();
+                    let due = dead_man
+                        .read()
+                        .await
+                        .as_ref()
+                        .filter(|s| s.needs_rearm(now))
+                        .map(|s| (s.is_alive(now), s.wallet.clone(), s.vault, s.timeout_ms, s.cancel_at));
+                    let Some((alive, wallet, vault, timeout_ms, cancel_at)) = due else {
+                        continue;
+                    };
+                    if !alive {
+                        logger.log(format!(
+                            "Dead-man heartbeat stale; open orders cancel at {cancel_at}"
+                        ));
+                        *dead_man.write().await = None;
+                        continue;
+                    }
+
+                    let time = now + timeout_ms;
+                    let action = L1Action::ScheduleCancel { time: Some(time as u64) };
+                    let result = match action.send(&exchange, wallet, vault).await {
+                        Ok(resp) => schedule_cancel_result(resp),
+                        Err(e) => Err(e),
+                    };
+                    if let Some(state) = dead_man.write().await.as_mut() {
+                        match result {
+                            Ok(()) => {
+                                state.last_armed = now;
+                                state.cancel_at = time;
+                                state.last_error = None;
+                            }
+                            Err(e) => {
+                                logger.log(format!("Error re-arming dead-man switch: {e}"));
+                                state.last_error = Some(e.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    });
+}
+
+fn spawn_trails(
+    api: HlApi,
     cache: Arc<RwLock<ServerCache>>,
-    shutdown_tx: watch::Sender<bool>,
+    trails: Trails,
+    mut shutdown: watch::Receiver<bool>,
+    logger: Logger,
+) {
+    tokio::spawn(async move {
+        let exchange = Exchange::new(if api.testnet {
+            Chain::ArbitrumTestnet
+        } else {
+            Chain::Arbitrum
+        });
+        let mut interval = tokio::time::interval(Duration::from_millis(500));
+        loop {
+            tokio::select! {
+                _ = shutdown.changed() => {
+                    if *shutdown.borrow() { break; }
+                }
+                _ = interval.tick() => {
+                    let mut dexes: Vec<String> = {
+                        let r = trails.read().await;
+                        if r.active().next().is_none() {
+                            continue;
+                        }
+                        r.active()
+                            .filter_map(|e| asset_index::coin_dex(&e.status.coin).map(str::to_string))
+                            .collect()
+                    };
+                    dexes.sort();
+                    dexes.dedup();
+
+                    // Never follow a stale price: a stop could fire on data
+                    // from before a disconnect.
+                    let mut mids = {
+                        let r = cache.read().await;
+                        match (&r.mids, r.is_connected()) {
+                            (Some(entry), true) => entry.data.clone(),
+                            _ => HashMap::new(),
+                        }
+                    };
+                    // The cache only holds main-dex mids.
+                    for dex in dexes {
+                        match api.all_mids_for_dex(Some(&dex)).await {
+                            Ok(dex_mids) => mids.extend(dex_mids),
+                            Err(e) => logger.log(format!("Error fetching allMids (dex: {dex}): {e}")),
+                        }
+                    }
+
+                    let crossed = trails.write().await.update(&mids);
+                    for trail_id in crossed {
+                        let close = trails
+                            .read()
+                            .await
+                            .entries
+                            .iter()
+                            .find(|e| e.status.id == trail_id)
+                            .and_then(|e| {
+                                let px = e.status.last_px?;
+                                let order = e.status.stop.close_order(e.asset, e.status.size, px, e.slippage, &e.precision);
+                                Some((e.wallet.clone(), e.vault, order))
+                            });
+                        let Some((wallet, vault, order)) = close else {
+                            continue;
+                        };
+                        let (state, result) = trail_close_result(exchange.place_order(wallet, vec![order], vault).await.map_err(Into::into));
+                        if let Some(entry) = trails.write().await.entries.iter_mut().find(|e| e.status.id == trail_id) {
+                            logger.log(format!(
+                                "Trailing stop {trail_id} ({}) fired at {:?}: {result}",
+                                entry.status.coin, entry.status.last_px
+                            ));
+                            entry.status.state = state;
+                            entry.status.result = Some(result);
+                        }
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// State shared by every IPC connection: the market data cache, the jobs the
+/// server runs for clients, and what `getStatus` reports.
+#[derive(Clone)]
+struct ServerState {
+    cache: Arc<RwLock<ServerCache>>,
+    dead_man: DeadMan,
+    trails: Trails,
     testnet: bool,
     started_at: i64,
+}
+
+async fn handle_connection(
+    stream: UnixStream,
+    state: ServerState,
+    shutdown_tx: watch::Sender<bool>,
     logger: Logger,
 ) -> Result<()> {
     let (read, mut write) = stream.into_split();
@@ -605,7 +1006,7 @@ async fn handle_connection(
             }
         };
 
-        let (resp, should_shutdown) = handle_request(req, cache.clone(), testnet, started_at).await;
+        let (resp, should_shutdown) = handle_request(req, &state).await;
         let s = serde_json::to_string(&resp)?;
         write.write_all(s.as_bytes()).await?;
         write.write_all(b"\n").await?;
@@ -621,12 +1022,14 @@ async fn handle_connection(
     Ok(())
 }
 
-async fn handle_request(
-    req: RpcRequest,
-    cache: Arc<RwLock<ServerCache>>,
-    testnet: bool,
-    started_at: i64,
-) -> (RpcResponse, bool) {
+async fn handle_request(req: RpcRequest, state: &ServerState) -> (RpcResponse, bool) {
+    let &ServerState {
+        ref cache,
+        ref dead_man,
+        ref trails,
+        testnet,
+        started_at,
+    } = state;
     let id = req.id.clone();
     match req.method.as_str() {
         "getPrices" => {
@@ -696,10 +1099,106 @@ async fn handle_request(
                 started_at,
                 uptime: now_ms() - started_at,
                 cache: cache_status,
+                dead_man: dead_man.read().await.as_ref().map(DeadManState::status),
+                active_trails: trails.read().await.active().count(),
+            };
+            let result = serde_json::to_value(status).unwrap_or(serde_json::Value::Null);
+            (response_ok(id, result, None), false)
+        }
+        "armDeadMan" => {
+            let params: ArmDeadManParams = match req.params.map(serde_json::from_value) {
+                Some(Ok(p)) => p,
+                _ => return (response_err(id, "Invalid armDeadMan params"), false),
+            };
+            let (wallet, vault) = match load_signer(testnet, &params.user)
+                .and_then(|w| Ok((w, parse_vault(params.vault.as_deref())?)))
+            {
+                Ok(signer) => signer,
+                Err(e) => return (response_err(id, e.to_string()), false),
+            };
+            let now = now_ms();
+            let state = DeadManState {
+                wallet,
+                vault,
+                timeout_ms: params.timeout_ms,
+                heartbeat_file: params.heartbeat_file.map(PathBuf::from),
+                last_heartbeat: now,
+                last_armed: now,
+                cancel_at: params.cancel_at,
+                last_error: None,
             };
+            let result = serde_json::to_value(state.status()).unwrap_or(serde_json::Value::Null);
+            *dead_man.write().await = Some(state);
+            (response_ok(id, result, None), false)
+        }
+        "disarmDeadMan" => {
+            let was_armed = dead_man.write().await.take().is_some();
+            (
+                response_ok(id, serde_json::json!({ "wasArmed": was_armed }), None),
+                false,
+            )
+        }
+        "heartbeat" => {
+            let mut w = dead_man.write().await;
+            if let Some(state) = w.as_mut() {
+                state.last_heartbeat = now_ms();
+            }
+            (
+                response_ok(id, serde_json::json!({ "armed": w.is_some() }), None),
+                false,
+            )
+        }
+        "addTrail" => {
+            let params: AddTrailParams = match req.params.map(serde_json::from_value) {
+                Some(Ok(p)) => p,
+                _ => return (response_err(id, "Invalid addTrail params"), false),
+            };
+            let (wallet, vault) = match load_signer(testnet, &params.user)
+                .and_then(|w| Ok((w, parse_vault(params.vault.as_deref())?)))
+            {
+                Ok(signer) => signer,
+                Err(e) => return (response_err(id, e.to_string()), false),
+            };
+            let status = trails.write().await.add(wallet, vault, params, now_ms());
             let result = serde_json::to_value(status).unwrap_or(serde_json::Value::Null);
             (response_ok(id, result, None), false)
         }
+        "listTrails" => {
+            let r = trails.read().await;
+            let statuses: Vec<&TrailStatus> = r.entries.iter().map(|e| &e.status).collect();
+            let result = serde_json::to_value(statuses).unwrap_or(serde_json::Value::Null);
+            (response_ok(id, result, None), false)
+        }
+        "removeTrail" => {
+            let params = req.params.as_ref();
+            let all = params.and_then(|p| p.get("all")).and_then(|v| v.as_bool());
+            let trail_id = match (params.and_then(|p| p.get("id")), all) {
+                (Some(v), None) => match v.as_u64() {
+                    Some(trail_id) => Some(trail_id),
+                    None => return (response_err(id, format!("Invalid trail id: {v}")), false),
+                },
+                (None, Some(true)) => None,
+                _ => {
+                    return (
+                        response_err(id, "removeTrail needs an id or \"all\": true"),
+                        false,
+                    );
+                }
+            };
+            let removed = trails.write().await.remove(trail_id);
+            if let Some(trail_id) = trail_id
+                && removed == 0
+            {
+                return (
+                    response_err(id, format!("Trailing stop {trail_id} not found")),
+                    false,
+                );
+            }
+            (
+                response_ok(id, serde_json::json!({ "removed": removed }), None),
+                false,
+            )
+        }
         "shutdown" => (
             response_ok(id, serde_json::json!({ "ok": true }), None),
             true,
@@ -712,6 +1211,9 @@ async fn handle_request(
 mod tests {
     use std::sync::atomic::{AtomicI64, Ordering};
 
+    use ethers::signers::Signer;
+    use hyperliquid_cli::trail::TrailDistance;
+
     use super::*;
 
     fn test_clock(now: Arc<AtomicI64>) -> Clock {
@@ -720,6 +1222,16 @@ mod tests {
         }
     }
 
+    fn test_state(cache: Arc<RwLock<ServerCache>>, trails: Trails) -> ServerState {
+        ServerState {
+            cache,
+            dead_man: DeadMan::default(),
+            trails,
+            testnet: false,
+            started_at: 0,
+        }
+    }
+
     #[test]
     fn server_cache_matches_js_cache_tests() {
         let now = Arc::new(AtomicI64::new(0));
@@ -803,12 +1315,13 @@ mod tests {
 
         // getPrices empty
         let cache = Arc::new(RwLock::new(cache));
+        let state = test_state(cache.clone(), Trails::default());
         let req = RpcRequest {
             id: "1".to_string(),
             method: "getPrices".to_string(),
             params: None,
         };
-        let (resp, shutdown) = handle_request(req, cache.clone(), false, 0).await;
+        let (resp, shutdown) = handle_request(req, &state).await;
         assert_eq!(resp.id, "1");
         assert_eq!(resp.error.as_deref(), Some("No data available"));
         assert!(!shutdown);
@@ -829,7 +1342,7 @@ mod tests {
             method: "getPrices".to_string(),
             params: None,
         };
-        let (resp, shutdown) = handle_request(req, cache.clone(), false, 0).await;
+        let (resp, shutdown) = handle_request(req, &state).await;
         assert!(!shutdown);
         let data: HashMap<String, String> = serde_json::from_value(resp.result.unwrap()).unwrap();
         assert_eq!(data.get("BTC").unwrap(), "50000");
@@ -842,7 +1355,7 @@ mod tests {
             method: "getPrices".to_string(),
             params: Some(serde_json::json!({ "coin": "btc" })),
         };
-        let (resp, shutdown) = handle_request(req, cache.clone(), false, 0).await;
+        let (resp, shutdown) = handle_request(req, &state).await;
         assert!(!shutdown);
         let data: HashMap<String, String> = serde_json::from_value(resp.result.unwrap()).unwrap();
         assert_eq!(
@@ -856,7 +1369,7 @@ mod tests {
             method: "getPrices".to_string(),
             params: Some(serde_json::json!({ "coin": "UNKNOWN" })),
         };
-        let (resp, shutdown) = handle_request(req, cache.clone(), false, 0).await;
+        let (resp, shutdown) = handle_request(req, &state).await;
         assert!(!shutdown);
         assert_eq!(resp.error.as_deref(), Some("Coin not found: UNKNOWN"));
 
@@ -866,7 +1379,7 @@ mod tests {
             method: "getAssetCtxs".to_string(),
             params: None,
         };
-        let (resp, _) = handle_request(req, cache.clone(), false, 0).await;
+        let (resp, _) = handle_request(req, &state).await;
         assert_eq!(resp.error.as_deref(), Some("No data available"));
 
         // getPerpMeta empty -> error
@@ -875,7 +1388,7 @@ mod tests {
             method: "getPerpMeta".to_string(),
             params: None,
         };
-        let (resp, _) = handle_request(req, cache.clone(), false, 0).await;
+        let (resp, _) = handle_request(req, &state).await;
         assert_eq!(resp.error.as_deref(), Some("No data available"));
 
         // getStatus reflects testnet + connected
@@ -890,7 +1403,15 @@ mod tests {
             method: "getStatus".to_string(),
             params: None,
         };
-        let (resp, _) = handle_request(req, cache.clone(), true, 1_000).await;
+        let (resp, _) = handle_request(
+            req,
+            &ServerState {
+                testnet: true,
+                started_at: 1_000,
+                ..state.clone()
+            },
+        )
+        .await;
         assert!(resp.error.is_none());
         let status: ServerStatus = serde_json::from_value(resp.result.unwrap()).unwrap();
         assert!(status.running);
@@ -904,7 +1425,7 @@ mod tests {
             method: "shutdown".to_string(),
             params: None,
         };
-        let (resp, shutdown) = handle_request(req, cache.clone(), false, 0).await;
+        let (resp, shutdown) = handle_request(req, &state).await;
         assert!(shutdown);
         assert!(resp.error.is_none());
 
@@ -914,9 +1435,161 @@ mod tests {
             method: "unknownMethod".to_string(),
             params: None,
         };
-        let (resp, shutdown) = handle_request(req, cache.clone(), false, 0).await;
+        let (resp, shutdown) = handle_request(req, &state).await;
         assert!(!shutdown);
         assert_eq!(resp.error.as_deref(), Some("Unknown method: unknownMethod"));
         assert_eq!(resp.id, "9");
     }
+
+    #[tokio::test]
+    async fn dead_man_heartbeats_keep_switch_alive() {
+        let wallet = LocalWallet::from_str(
+            "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef",
+        )
+        .unwrap();
+        let state = DeadManState {
+            wallet: Arc::new(wallet),
+            vault: None,
+            timeout_ms: 60_000,
+            heartbeat_file: None,
+            last_heartbeat: 0,
+            last_armed: 0,
+            cancel_at: 60_000,
+            last_error: None,
+        };
+        assert!(state.is_alive(59_999));
+        assert!(!state.is_alive(60_000));
+        assert!(!state.needs_rearm(19_999));
+        assert!(state.needs_rearm(20_000));
+
+        // A freshly touched heartbeat file keeps a stale switch alive
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let stale_with_file = DeadManState {
+            heartbeat_file: Some(file.path().to_path_buf()),
+            ..state
+        };
+        assert!(stale_with_file.is_alive(now_ms()));
+
+        let state = test_state(Arc::default(), Trails::default());
+        let dead_man = state.dead_man.clone();
+        let heartbeat = || RpcRequest {
+            id: "1".to_string(),
+            method: "heartbeat".to_string(),
+            params: None,
+        };
+        let (resp, _) = handle_request(heartbeat(), &state).await;
+        assert_eq!(resp.result.unwrap()["armed"], false);
+
+        *dead_man.write().await = Some(stale_with_file);
+        let (resp, _) = handle_request(heartbeat(), &state).await;
+        assert_eq!(resp.result.unwrap()["armed"], true);
+        assert!(dead_man.read().await.as_ref().unwrap().last_heartbeat > 0);
+
+        let req = RpcRequest {
+            id: "2".to_string(),
+            method: "getStatus".to_string(),
+            params: None,
+        };
+        let (resp, _) = handle_request(req, &state).await;
+        let status: ServerStatus = serde_json::from_value(resp.result.unwrap()).unwrap();
+        assert_eq!(status.dead_man.unwrap().timeout_ms, 60_000);
+
+        let req = RpcRequest {
+            id: "3".to_string(),
+            method: "disarmDeadMan".to_string(),
+            params: None,
+        };
+        let (resp, _) = handle_request(req, &state).await;
+        assert_eq!(resp.result.unwrap()["wasArmed"], true);
+        assert!(dead_man.read().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn trails_ratchet_fire_and_are_removed() {
+        let wallet = LocalWallet::from_str(
+            "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef",
+        )
+        .unwrap();
+        let params = |coin: &str, is_long| AddTrailParams {
+            user: format!("{:#x}", wallet.address()),
+            vault: None,
+            coin: coin.to_string(),
+            asset: 0,
+            sz_decimals: 2,
+            is_long,
+            size: 1.0,
+            distance: TrailDistance::Percent(10.0),
+            slippage: 0.01,
+            start_px: 100.0,
+        };
+        let trails = Trails::default();
+        {
+            let mut book = trails.write().await;
+            let wallet = Arc::new(wallet.clone());
+            assert_eq!(book.add(wallet.clone(), None, params("BTC", true), 0).id, 1);
+            assert_eq!(book.add(wallet, None, params("ETH", false), 0).id, 2);
+
+            let mids = |btc: &str, eth: &str| {
+                HashMap::from([
+                    ("BTC".to_string(), btc.to_string()),
+                    ("ETH".to_string(), eth.to_string()),
+                ])
+            };
+            assert!(book.update(&mids("120", "100")).is_empty());
+            assert_eq!(book.entries[0].status.stop_px, 108.0);
+            assert_eq!(book.update(&mids("107", "100")), vec![1]);
+            assert_eq!(book.entries[0].status.state, TrailState::Firing);
+            // A firing trail is no longer followed
+            assert!(book.update(&mids("90", "100")).is_empty());
+            assert_eq!(book.entries[0].status.last_px, Some(107.0));
+            assert_eq!(book.active().count(), 1);
+        }
+
+        let state = test_state(Arc::default(), trails.clone());
+        let request = |method: &str, params| RpcRequest {
+            id: "1".to_string(),
+            method: method.to_string(),
+            params,
+        };
+        let (resp, _) = handle_request(request("getStatus", None), &state).await;
+        let status: ServerStatus = serde_json::from_value(resp.result.unwrap()).unwrap();
+        assert_eq!(status.active_trails, 1);
+
+        let (resp, _) = handle_request(request("listTrails", None), &state).await;
+        let listed: Vec<TrailStatus> = serde_json::from_value(resp.result.unwrap()).unwrap();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[1].stop.distance, TrailDistance::Percent(10.0));
+
+        let (resp, _) = handle_request(
+            request("removeTrail", Some(serde_json::json!({ "id": 7 }))),
+            &state,
+        )
+        .await;
+        assert_eq!(resp.error.as_deref(), Some("Trailing stop 7 not found"));
+
+        let (resp, _) = handle_request(
+            request("removeTrail", Some(serde_json::json!({ "id": 1 }))),
+            &state,
+        )
+        .await;
+        assert_eq!(resp.result.unwrap()["removed"], 1);
+
+        let (resp, _) = handle_request(
+            request("removeTrail", Some(serde_json::json!({ "id": "2" }))),
+            &state,
+        )
+        .await;
+        assert_eq!(resp.error.as_deref(), Some("Invalid trail id: \"2\""));
+        let (resp, _) = handle_request(request("removeTrail", None), &state).await;
+        assert!(resp.error.is_some());
+        assert_eq!(trails.read().await.entries.len(), 1);
+
+        let (resp, _) = handle_request(
+            request("removeTrail", Some(serde_json::json!({ "all": true }))),
+            &state,
+        )
+        .await;
+        assert_eq!(resp.result.unwrap()["removed"], 1);
+        assert!(trails.read().await.entries.is_empty());
+    }
 }
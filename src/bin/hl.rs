@@ -11,20 +11,35 @@ use hyperliquid::{
     types::{
         Chain,
         exchange::{
-            request::{CancelRequest, Limit, OrderRequest, OrderType},
+            request::{
+                Action, CancelByCloidRequest, CancelRequest, Grouping, Limit, ModifyRequest,
+                OrderRequest, OrderType, Tif, TpSl, Trigger, TwapRequest,
+            },
             response::{Response as ExchangeResponse, Status, StatusType},
         },
     },
 };
 
+use uuid::Uuid;
+
 use hyperliquid_cli::{
-    asset_index, config,
+    asset_index, book, config,
     db::{self, CreateAccountInput},
-    hl_api::{HlApi, UserRoleResponse},
-    order_config,
+    hl_api::{
+        self, Fill, FrontendOpenOrder, FundingRate, HistoricalOrder, HlApi, OrderRef, UserFunding,
+        UserRoleResponse,
+    },
+    l1_action::L1Action,
+    order_batch::{self, BatchEntry, BatchFormat},
+    order_config::{self, Exposure, MarginMode, OrderIntent},
     output::{self, OutputOptions},
     paths, prompt,
-    server::client::ServerClient,
+    rounding::AssetPrecision,
+    server::{
+        client::ServerClient,
+        types::{AddTrailParams, ArmDeadManParams, TrailState},
+    },
+    trail::{TrailDistance, TrailingStop},
     validation, watch, ws,
 };
 
@@ -39,6 +54,14 @@ struct Cli {
     #[arg(long, global = true, default_value_t = false)]
     testnet: bool,
 
+    /// Stop trading commands before signing and print what would be sent
+    #[arg(long, global = true, default_value_t = false)]
+    dry_run: bool,
+
+    /// Trade and query a vault or sub-account (address or account alias)
+    #[arg(long, global = true)]
+    vault: Option<String>,
+
     #[command(subcommand)]
     command: Command,
 }
@@ -59,7 +82,7 @@ enum Command {
     },
     Order {
         #[command(subcommand)]
-        command: OrderCommand,
+        command: Box<OrderCommand>,
     },
     #[command(hide = true)]
     Referral {
@@ -81,6 +104,10 @@ enum AccountCommand {
     Remove(AccountRemoveArgs),
     Positions(AccountUserWatchArgs),
     Orders(AccountUserWatchArgs),
+    /// Trade executions, newest first
+    Fills(AccountFillsArgs),
+    /// Funding paid and received per position, with per-coin totals
+    Funding(AccountFundingArgs),
     Balances(AccountUserWatchArgs),
     Portfolio(AccountUserWatchArgs),
 }
@@ -112,9 +139,57 @@ struct AccountUserWatchArgs {
     watch: bool,
 }
 
+#[derive(Args, Debug, Clone)]
+struct AccountFillsArgs {
+    /// User address (defaults to configured wallet)
+    #[arg(long)]
+    user: Option<String>,
+
+    /// Watch mode - stream new fills as they happen
+    #[arg(short, long, default_value_t = false)]
+    watch: bool,
+
+    /// Only fills in this coin
+    #[arg(long)]
+    coin: Option<String>,
+
+    /// Start of the window: a duration ago (7d, 12h), a date or an RFC 3339 time
+    #[arg(long)]
+    since: Option<String>,
+
+    /// End of the window, in the same forms as --since
+    #[arg(long, conflicts_with = "watch")]
+    until: Option<String>,
+
+    /// Maximum number of fills to show
+    #[arg(long, default_value = "50")]
+    limit: String,
+}
+
+#[derive(Args, Debug, Clone)]
+struct AccountFundingArgs {
+    /// User address (defaults to configured wallet)
+    #[arg(long)]
+    user: Option<String>,
+
+    /// Only payments for this coin
+    #[arg(long)]
+    coin: Option<String>,
+
+    /// Start of the window: a duration ago (default 7d), a date or an RFC 3339 time
+    #[arg(long)]
+    since: Option<String>,
+
+    /// End of the window, in the same forms as --since
+    #[arg(long)]
+    until: Option<String>,
+}
+
 #[derive(Subcommand, Debug)]
 enum MarketsCommand {
     Ls(MarketsLsArgs),
+    /// List perp dexes, including builder-deployed (HIP-3) dexes
+    Dexs,
     #[command(hide = true)]
     Prices(MarketsPricesArgs),
 }
@@ -129,6 +204,10 @@ struct MarketsLsArgs {
     #[arg(long, default_value_t = false)]
     perp_only: bool,
 
+    /// List only the perps of one builder dex (see `markets dexs`)
+    #[arg(long, conflicts_with = "spot_only")]
+    dex: Option<String>,
+
     /// Watch mode - stream real-time updates
     #[arg(short, long, default_value_t = false)]
     watch: bool,
@@ -146,6 +225,8 @@ enum AssetCommand {
     Price(AssetPriceArgs),
     Book(AssetBookArgs),
     Leverage(AssetLeverageArgs),
+    /// Historical funding rates, with annualized figures
+    Funding(AssetFundingArgs),
 }
 
 #[derive(Args, Debug, Clone)]
@@ -182,1479 +263,1710 @@ struct AssetLeverageArgs {
     watch: bool,
 }
 
+#[derive(Args, Debug, Clone)]
+struct AssetFundingArgs {
+    /// Coin symbol (e.g., BTC, ETH)
+    coin: String,
+
+    /// Start of the window: a duration ago (default 7d), a date or an RFC 3339 time
+    #[arg(long)]
+    since: Option<String>,
+
+    /// End of the window, in the same forms as --since
+    #[arg(long)]
+    until: Option<String>,
+}
+
 #[derive(Subcommand, Debug)]
 enum OrderCommand {
     Ls(AccountUserWatchArgs),
+    /// Recent orders in any state, with why they were cancelled or rejected
+    History(OrderHistoryArgs),
+    /// Look up one order by order ID or client order ID
+    Status(OrderStatusArgs),
     Limit(OrderLimitArgs),
     Market(OrderMarketArgs),
+    Stop(OrderTriggerArgs),
+    Tp(OrderTriggerArgs),
+    Scale(OrderScaleArgs),
+    Twap(OrderTwapArgs),
+    TwapCancel(OrderTwapCancelArgs),
+    TwapWatch(OrderTwapWatchArgs),
+    /// Post-only order that follows the best bid or ask until filled
+    Chase(OrderChaseArgs),
+    Modify(OrderModifyArgs),
+    ModifyBatch(OrderModifyBatchArgs),
     Cancel(OrderCancelArgs),
     CancelAll(OrderCancelAllArgs),
+    Batch(OrderBatchArgs),
+    DeadMan(OrderDeadManArgs),
+    /// Trailing stop followed by rhl-server, closing the position when hit
+    Trail(OrderTrailArgs),
+    Close(OrderCloseArgs),
+    Flatten(OrderFlattenArgs),
     SetLeverage(OrderSetLeverageArgs),
+    Margin {
+        #[command(subcommand)]
+        command: OrderMarginCommand,
+    },
     Configure(OrderConfigureArgs),
 }
 
 #[derive(Args, Debug, Clone)]
+#[command(
+    override_usage = "rhl order limit [OPTIONS] <SIDE> <SIZE> <COIN> <PRICE>\n       \
+    rhl order limit [OPTIONS] <--usd <USD>|--equity-pct <PCT>|--risk <PCT>|--risk-usd <USD>> <SIDE> <COIN> <PRICE>"
+)]
 struct OrderLimitArgs {
     /// Order side: buy, sell, long, or short
     side: String,
-    /// Order size
-    size: String,
+    /// Order size in coin units; omitted when sizing by --usd, --equity-pct, --risk or --risk-usd
+    #[arg(value_name = "SIZE")]
+    first: Option<String>,
     /// Coin symbol (e.g., BTC, ETH)
-    coin: String,
+    #[arg(value_name = "COIN")]
+    second: Option<String>,
     /// Limit price
-    price: String,
+    #[arg(value_name = "PRICE")]
+    third: Option<String>,
 
-    /// Time-in-force: Gtc, Ioc, Alo
-    #[arg(long, default_value = "Gtc")]
-    tif: String,
+    #[command(flatten)]
+    sizing: SizingArgs,
 
-    /// Reduce-only order
+    #[command(flatten)]
+    leverage: LeverageArgs,
+
+    /// Time-in-force: Gtc, Ioc, Alo (default: configured, else Gtc)
+    #[arg(long)]
+    tif: Option<String>,
+
+    #[command(flatten)]
+    reduce_only: ReduceOnlyArgs,
+
+    /// Error instead of rounding price and size to exchange precision
     #[arg(long, default_value_t = false)]
-    reduce_only: bool,
+    strict: bool,
+
+    /// Attach a take-profit trigger at this price
+    #[arg(long)]
+    tp: Option<String>,
+
+    /// Attach a stop-loss trigger at this price
+    #[arg(long)]
+    sl: Option<String>,
+
+    /// Client order ID (0x + 32 hex chars); generated if omitted
+    #[arg(long)]
+    cloid: Option<String>,
 }
 
 #[derive(Args, Debug, Clone)]
+#[command(
+    override_usage = "rhl order market [OPTIONS] <SIDE> <SIZE> <COIN>\n       \
+    rhl order market [OPTIONS] <--usd <USD>|--equity-pct <PCT>|--risk <PCT>|--risk-usd <USD>> <SIDE> <COIN>"
+)]
 struct OrderMarketArgs {
     /// Order side: buy, sell, long, or short
     side: String,
-    /// Order size
-    size: String,
+    /// Order size in coin units; omitted when sizing by --usd, --equity-pct, --risk or --risk-usd
+    #[arg(value_name = "SIZE")]
+    first: Option<String>,
     /// Coin symbol (e.g., BTC, ETH)
-    coin: String,
+    #[arg(value_name = "COIN")]
+    second: Option<String>,
 
-    /// Reduce-only order
+    #[command(flatten)]
+    sizing: SizingArgs,
+
+    #[command(flatten)]
+    leverage: LeverageArgs,
+
+    #[command(flatten)]
+    reduce_only: ReduceOnlyArgs,
+
+    /// Error instead of rounding price and size to exchange precision
     #[arg(long, default_value_t = false)]
-    reduce_only: bool,
+    strict: bool,
 
     /// Slippage percentage (overrides config)
     #[arg(long)]
     slippage: Option<String>,
-}
 
-#[derive(Args, Debug, Clone)]
-struct OrderCancelArgs {
-    /// Order ID to cancel (interactive if omitted)
-    oid: Option<String>,
-}
+    /// Price from order book depth instead of mid +/- slippage
+    #[arg(long, default_value_t = false)]
+    book: bool,
 
-#[derive(Args, Debug, Clone)]
-struct OrderCancelAllArgs {
-    /// Skip confirmation prompt
-    #[arg(short = 'y', long, default_value_t = false)]
-    yes: bool,
+    /// Attach a take-profit trigger at this price
+    #[arg(long)]
+    tp: Option<String>,
 
-    /// Only cancel orders for a specific coin
+    /// Attach a stop-loss trigger at this price
     #[arg(long)]
-    coin: Option<String>,
-}
+    sl: Option<String>,
 
-#[derive(Args, Debug, Clone)]
-struct OrderSetLeverageArgs {
-    /// Coin symbol
-    coin: String,
-    /// Leverage value
-    leverage: String,
+    /// Client order ID (0x + 32 hex chars); generated if omitted
+    #[arg(long)]
+    cloid: Option<String>,
+}
 
-    /// Use cross margin
-    #[arg(long, default_value_t = false)]
-    cross: bool,
+// Leverage to switch a perp to before an entry order. Falls back to the
+// coin's configured defaults. (Plain comments: clap would use a doc comment on
+// a flattened struct as the subcommand's about text.)
+#[derive(Args, Debug, Clone, Default)]
+struct LeverageArgs {
+    /// Set the coin's leverage before placing the order
+    #[arg(long)]
+    leverage: Option<String>,
 
     /// Use isolated margin
-    #[arg(long, default_value_t = false)]
+    #[arg(long, default_value_t = false, conflicts_with = "cross")]
     isolated: bool,
-}
 
-#[derive(Args, Debug, Clone)]
-struct OrderConfigureArgs {
-    /// Set default slippage percentage for market orders
-    #[arg(long)]
-    slippage: Option<String>,
+    /// Use cross margin
+    #[arg(long, default_value_t = false)]
+    cross: bool,
 }
 
-#[derive(Subcommand, Debug)]
-enum ReferralCommand {
-    Set(ReferralSetArgs),
-    Status,
+impl LeverageArgs {
+    fn resolve(
+        &self,
+        defaults: &order_config::OrderDefaults,
+    ) -> Result<(Option<u32>, Option<MarginMode>)> {
+        let leverage = match &self.leverage {
+            Some(l) => Some(validation::validate_positive_u64(l, "leverage")? as u32),
+            None => defaults.leverage,
+        };
+        let margin_mode = if self.isolated {
+            Some(MarginMode::Isolated)
+        } else if self.cross {
+            Some(MarginMode::Cross)
+        } else {
+            defaults.margin_mode
+        };
+        Ok((leverage, margin_mode))
+    }
 }
 
-#[derive(Args, Debug, Clone)]
-struct ReferralSetArgs {
-    /// Referral code
-    code: String,
-}
+// Reduce-only flag for entry orders. Either form overrides the coin's
+// configured default; the last one given wins.
+#[derive(Args, Debug, Clone, Default)]
+struct ReduceOnlyArgs {
+    /// Reduce-only order
+    #[arg(long, default_value_t = false, overrides_with = "no_reduce_only")]
+    reduce_only: bool,
 
-#[derive(Subcommand, Debug)]
-enum ServerCommand {
-    Start,
-    Stop,
-    Status,
+    /// Not reduce-only, even if configured as the coin's default
+    #[arg(long, default_value_t = false, overrides_with = "reduce_only")]
+    no_reduce_only: bool,
 }
 
-#[tokio::main]
-async fn main() {
-    install_broken_pipe_panic_hook();
-
-    let cli = Cli::parse();
-    let start = Instant::now();
-
-    let output_opts = OutputOptions { json: cli.json };
-    let result = run(cli, output_opts).await;
-
-    match result {
-        Ok(()) => {
-            if !output_opts.json {
-                let duration = start.elapsed().as_secs_f64();
-                println!("\nCompleted in {:.2}s", duration);
-            }
-        }
-        Err(err) => {
-            output::print_error(err.to_string());
-            std::process::exit(1);
+impl ReduceOnlyArgs {
+    fn resolve(&self, defaults: &order_config::OrderDefaults) -> bool {
+        if self.reduce_only {
+            true
+        } else if self.no_reduce_only {
+            false
+        } else {
+            defaults.reduce_only.unwrap_or(false)
         }
     }
 }
 
-fn install_broken_pipe_panic_hook() {
-    let default_hook = std::panic::take_hook();
-    std::panic::set_hook(Box::new(move |info| {
-        let message = info
-            .payload()
-            .downcast_ref::<String>()
-            .map(String::as_str)
-            .or_else(|| info.payload().downcast_ref::<&str>().copied());
-
-        if let Some(msg) = message
-            && msg.contains("Broken pipe")
-        {
-            std::process::exit(0);
-        }
-
-        default_hook(info);
-    }));
-}
+// Alternatives to giving the order size in coin units. At most one of the
+// `size_by` group may be given; with one, the size positional is left out.
+#[derive(Args, Debug, Clone, Default)]
+struct SizingArgs {
+    /// Size the order by USD notional instead of coin units
+    #[arg(long, value_name = "USD", group = "size_by")]
+    usd: Option<String>,
 
-async fn run(cli: Cli, output_opts: OutputOptions) -> Result<()> {
-    let cfg = config::load_config(cli.testnet)?;
-    let api = HlApi::new(cli.testnet)?;
+    /// Size the order as a percentage of account equity
+    #[arg(long, value_name = "PCT", group = "size_by")]
+    equity_pct: Option<String>,
 
-    match cli.command {
-        Command::Account { command } => run_account(command, &cfg, &api, output_opts).await,
-        Command::Markets { command } => run_markets(command, &api, output_opts).await,
-        Command::Asset { command } => run_asset(command, &cfg, &api, output_opts).await,
-        Command::Order { command } => run_order(command, &cfg, &api, output_opts).await,
-        Command::Referral { command } => run_referral(command, &cfg, &api, output_opts).await,
-        Command::Server { command } => run_server(command, cli.testnet, output_opts).await,
-        Command::Upgrade => run_upgrade(output_opts).await,
-    }
-}
+    /// Size so that hitting --stop loses this share of equity (e.g. 1%)
+    #[arg(long, value_name = "PCT", group = "size_by", requires = "stop")]
+    risk: Option<String>,
 
-fn require_wallet_address(cfg: &config::LoadedConfig) -> Result<Address> {
-    cfg.wallet_address.ok_or_else(|| {
-        anyhow!("No account configured. Run 'rhl account add' to set up your account.")
-    })
-}
+    /// Size so that hitting --stop loses this many USD
+    #[arg(long, value_name = "USD", group = "size_by", requires = "stop")]
+    risk_usd: Option<String>,
 
-fn resolve_user_address(cfg: &config::LoadedConfig, user: &Option<String>) -> Result<Address> {
-    if let Some(u) = user {
-        return validation::validate_address(u);
-    }
-    require_wallet_address(cfg)
+    /// Stop price used for risk-based sizing
+    #[arg(long)]
+    stop: Option<String>,
+
+    /// Also place --stop as a linked stop-loss trigger
+    #[arg(
+        long,
+        default_value_t = false,
+        requires = "stop",
+        conflicts_with = "sl"
+    )]
+    attach_stop: bool,
 }
 
-fn exchange_chain(testnet: bool) -> Chain {
-    if testnet {
-        Chain::ArbitrumTestnet
-    } else {
-        Chain::Arbitrum
+impl SizingArgs {
+    fn is_set(&self) -> bool {
+        self.usd.is_some()
+            || self.equity_pct.is_some()
+            || self.risk.is_some()
+            || self.risk_usd.is_some()
     }
 }
 
-fn require_wallet_signer(cfg: &config::LoadedConfig) -> Result<Arc<LocalWallet>> {
-    let Some(pk) = &cfg.private_key else {
-        if let Some(acc) = &cfg.account
-            && acc.account_type == config::AccountType::Readonly
-        {
-            return Err(anyhow!(
-                "Account \"{}\" is read-only and cannot perform trading operations.\nRun 'rhl account add' to set up an API wallet for trading.",
-                acc.alias
-            ));
+const SIZE_BY_FLAGS: &str = "--usd, --equity-pct, --risk or --risk-usd";
+
+impl OrderLimitArgs {
+    /// `(size, coin, price)`. Clap fills the positionals in order, so with a
+    /// `size_by` flag the coin and price arrive in the first two slots.
+    fn positionals(&self) -> Result<(Option<&str>, &str, &str)> {
+        let values = [&self.first, &self.second, &self.third].map(|v| v.as_deref());
+        match (self.sizing.is_set(), values) {
+            (false, [Some(size), Some(coin), Some(price)]) => Ok((Some(size), coin, price)),
+            (true, [Some(coin), Some(price), None]) => Ok((None, coin, price)),
+            (false, _) => Err(anyhow!(
+                "Expected <SIDE> <SIZE> <COIN> <PRICE>, or <SIDE> <COIN> <PRICE> with one of {SIZE_BY_FLAGS}"
+            )),
+            (true, _) => Err(anyhow!(
+                "Expected <SIDE> <COIN> <PRICE> when sizing with {SIZE_BY_FLAGS}; leave out <SIZE>"
+            )),
         }
-        return Err(anyhow!(
-            "No account configured. Run 'rhl account add' to set up your account."
-        ));
-    };
-    let pk = validation::validate_private_key(pk)?;
-    let wallet = LocalWallet::from_str(&pk).context("Invalid private key")?;
-    Ok(Arc::new(wallet))
+    }
 }
 
-fn print_exchange_response(resp: &ExchangeResponse) {
-    match resp {
-        ExchangeResponse::Ok(data) => {
-            if let Some(status_type) = &data.data {
-                match status_type {
-                    StatusType::Statuses(statuses) => {
-                        for s in statuses {
-                            print_exchange_status(s);
-                        }
-                    }
-                    StatusType::Status(status) => print_exchange_status(status),
-                    StatusType::Address(addr) => {
-                        output::print_success(format!("{addr:#x}"));
-                    }
-                }
-            } else {
-                output::print_success(format!("Ok ({})", data.type_));
-            }
+impl OrderMarketArgs {
+    /// `(size, coin)`. With a `size_by` flag the coin arrives in the first slot.
+    fn positionals(&self) -> Result<(Option<&str>, &str)> {
+        match (
+            self.sizing.is_set(),
+            self.first.as_deref(),
+            self.second.as_deref(),
+        ) {
+            (false, Some(size), Some(coin)) => Ok((Some(size), coin)),
+            (true, Some(coin), None) => Ok((None, coin)),
+            (false, _, _) => Err(anyhow!(
+                "Expected <SIDE> <SIZE> <COIN>, or <SIDE> <COIN> with one of {SIZE_BY_FLAGS}"
+            )),
+            (true, _, _) => Err(anyhow!(
+                "Expected <SIDE> <COIN> when sizing with {SIZE_BY_FLAGS}; leave out <SIZE>"
+            )),
         }
-        ExchangeResponse::Err(err) => output::print_error(err),
     }
 }
 
-fn print_exchange_status(status: &Status) {
-    match status {
-        Status::Resting(r) => output::print_success(format!("Order placed: ID {}", r.oid)),
-        Status::Filled(f) => {
-            output::print_success(format!("Order filled: {} @ {}", f.total_sz, f.avg_px))
-        }
-        Status::Error(e) => output::print_error(format!("Order error: {e}")),
-        Status::Success => output::print_success("Order status: success"),
-        Status::WaitingForFill => output::print_success("Order status: waitingForFill"),
-        Status::WaitingForTrigger => output::print_success("Order status: waitingForTrigger"),
-        Status::Running(t) => {
-            output::print_success(format!("Order status: running (twapId: {})", t.twap_id))
-        }
-    }
+#[derive(Args, Debug, Clone)]
+struct OrderTriggerArgs {
+    /// Order side: buy, sell, long, or short
+    side: String,
+    /// Order size
+    size: String,
+    /// Coin symbol (e.g., BTC, ETH)
+    coin: String,
+    /// Trigger price
+    trigger_price: String,
+
+    /// Limit price once triggered (executes as market if omitted)
+    #[arg(long)]
+    limit: Option<String>,
+
+    /// Reduce-only order
+    #[arg(long, default_value_t = false)]
+    reduce_only: bool,
+
+    /// Error instead of rounding price and size to exchange precision
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+
+    /// Slippage percentage for market execution (overrides config)
+    #[arg(long)]
+    slippage: Option<String>,
 }
 
-async fn run_account(
-    cmd: AccountCommand,
-    cfg: &config::LoadedConfig,
-    api: &HlApi,
-    output_opts: OutputOptions,
-) -> Result<()> {
-    match cmd {
-        AccountCommand::Add => account_add(cfg, api, output_opts).await,
-        AccountCommand::Ls => account_ls(output_opts),
-        AccountCommand::SetDefault(args) => account_set_default(args, output_opts).await,
-        AccountCommand::Remove(args) => account_remove(args, output_opts).await,
-        AccountCommand::Positions(args) => account_positions(cfg, api, args, output_opts).await,
-        AccountCommand::Orders(args) => account_orders(cfg, api, args, output_opts).await,
-        AccountCommand::Balances(args) => account_balances(cfg, api, args, output_opts).await,
-        AccountCommand::Portfolio(args) => account_portfolio(cfg, api, args, output_opts).await,
-    }
-}
+#[derive(Args, Debug, Clone)]
+struct OrderScaleArgs {
+    /// Order side: buy, sell, long, or short
+    side: String,
+    /// Total order size
+    size: String,
+    /// Coin symbol (e.g., BTC, ETH)
+    coin: String,
 
-async fn account_add(
-    cfg: &config::LoadedConfig,
-    api: &HlApi,
-    output_opts: OutputOptions,
-) -> Result<()> {
-    println!("\n=== Add New Account ===\n");
+    /// Price of the first order
+    #[arg(long)]
+    from: String,
 
-    let setup_method = prompt::select(
-        "How would you like to add your account?",
-        vec![
-            prompt::SelectOption {
-                value: "existing",
-                label: "Use existing wallet".to_string(),
-            },
-            prompt::SelectOption {
-                value: "new",
-                label: "Create new wallet".to_string(),
-            },
-            prompt::SelectOption {
-                value: "readonly",
-                label: "Add read-only account".to_string(),
-            },
-        ],
-    )?;
+    /// Price of the last order
+    #[arg(long)]
+    to: String,
 
-    match setup_method {
-        "new" => {
-            println!("\nCreating new wallets with encrypted keystores is coming soon!");
-            println!("For now, please use an existing wallet or add a read-only account.\n");
-            Ok(())
-        }
-        "existing" => account_add_existing_wallet(cfg, api, output_opts).await,
-        "readonly" => account_add_readonly(output_opts).await,
-        _ => Err(anyhow!("Unknown setup method")),
-    }
+    /// Number of orders (2 - 50)
+    #[arg(long)]
+    count: String,
+
+    /// Size ratio of the last order to the first (1 = evenly split)
+    #[arg(long, default_value = "1")]
+    skew: String,
+
+    /// Time-in-force: Gtc, Ioc, Alo
+    #[arg(long, default_value = "Gtc")]
+    tif: String,
+
+    /// Reduce-only orders
+    #[arg(long, default_value_t = false)]
+    reduce_only: bool,
+
+    /// Error instead of rounding price and size to exchange precision
+    #[arg(long, default_value_t = false)]
+    strict: bool,
+
+    /// Skip confirmation prompt
+    #[arg(short = 'y', long, default_value_t = false)]
+    yes: bool,
 }
 
-async fn account_add_existing_wallet(
-    cfg: &config::LoadedConfig,
-    api: &HlApi,
-    output_opts: OutputOptions,
-) -> Result<()> {
-    const REFERRAL_LINK: &str = "https://app.hyperliquid.xyz/join/CHRISLING";
+#[derive(Args, Debug, Clone)]
+struct OrderTwapArgs {
+    /// Order side: buy, sell, long, or short
+    side: String,
+    /// Total order size
+    size: String,
+    /// Coin symbol (e.g., BTC, ETH)
+    coin: String,
 
-    let api_url = if cfg.testnet {
-        "https://app.hyperliquid-testnet.xyz/API"
-    } else {
-        "https://app.hyperliquid.xyz/API"
-    };
-    println!("\nVisit {api_url} and click \"Generate\" to create one.\n");
+    /// Running time in minutes (5 - 1440)
+    #[arg(long)]
+    minutes: String,
 
-    let api_private_key = loop {
-        let input = prompt::prompt("Enter your API wallet private key: ")?;
-        match validation::validate_private_key(&input) {
-            Ok(pk) => break pk,
-            Err(e) => {
-                output::print_error(e.to_string());
-            }
-        }
-    };
+    /// Randomize the size of each slice
+    #[arg(long, default_value_t = false)]
+    randomize: bool,
 
-    // Validate API key by checking userRole(address) is agent
-    println!("\nValidating API key...");
-    let wallet = LocalWallet::from_str(&api_private_key).context("Invalid private key")?;
-    let api_wallet_address = wallet.address();
+    /// Reduce-only order
+    #[arg(long, default_value_t = false)]
+    reduce_only: bool,
 
-    let role = api.user_role(api_wallet_address).await?;
-    let master_address = match role {
-        UserRoleResponse::Agent { data } => data.user,
-        UserRoleResponse::Missing => {
-            return Err(anyhow!(
-                "This key is not registered as an API wallet on Hyperliquid"
-            ));
-        }
-        other => {
-            return Err(anyhow!(
-                "Invalid role: {:?}. Expected an agent wallet.",
-                other
-            ));
-        }
-    };
+    /// Error instead of rounding price and size to exchange precision
+    #[arg(long, default_value_t = false)]
+    strict: bool,
 
-    println!(
-        "Valid API wallet for {}",
-        output::format_short_address(&master_address)
-    );
+    /// Watch mode - stream slice fills until the TWAP completes
+    #[arg(short, long, default_value_t = false)]
+    watch: bool,
+}
 
-    println!(
-        "\n{}{}{}",
-        output::style_warning("🎁 Get "),
-        output::style_profit_bold("4% off"),
-        output::style_warning(" trading fees with our referral link:")
-    );
-    println!("   {}", output::style_link(REFERRAL_LINK));
-    println!();
+#[derive(Args, Debug, Clone)]
+struct OrderTwapCancelArgs {
+    /// Coin symbol (e.g., BTC, ETH)
+    coin: String,
+    /// TWAP ID to cancel
+    twap_id: String,
+}
 
-    let open_referral =
-        prompt::press_enter_or_esc("Press Enter to open in browser, or Esc to skip")?;
-    if open_referral {
-        let _ = if cfg!(target_os = "macos") {
-            std::process::Command::new("open")
-                .arg(REFERRAL_LINK)
-                .status()
-        } else if cfg!(target_os = "windows") {
-            std::process::Command::new("cmd")
-                .args(["/C", "start", REFERRAL_LINK])
-                .status()
-        } else {
-            std::process::Command::new("xdg-open")
-                .arg(REFERRAL_LINK)
-                .status()
-        };
-    }
+#[derive(Args, Debug, Clone)]
+struct OrderTwapWatchArgs {
+    /// TWAP ID to follow
+    twap_id: String,
 
-    let alias = prompt_for_alias()?;
+    /// Target size, to show progress against
+    #[arg(long)]
+    size: Option<String>,
 
-    let mut set_as_default = false;
-    let existing_count = db::get_account_count()?;
-    if existing_count > 0 {
-        set_as_default = prompt::confirm("\nSet this as your default account?", true)?;
-    }
+    /// User address (defaults to configured wallet)
+    #[arg(long)]
+    user: Option<String>,
+}
 
-    let new_account = db::create_account(CreateAccountInput {
-        alias: alias.clone(),
-        user_address: master_address.clone(),
-        account_type: "api_wallet".to_string(),
-        source: Some("cli_import".to_string()),
-        api_wallet_private_key: Some(api_private_key.clone()),
-        api_wallet_public_key: Some(format!("{api_wallet_address:#x}")),
-        set_as_default,
-    })?;
+#[derive(Args, Debug, Clone)]
+struct OrderChaseArgs {
+    /// Order side: buy, sell, long, or short
+    side: String,
+    /// Order size in coin units
+    size: String,
+    /// Coin symbol (e.g., BTC, ETH)
+    coin: String,
 
-    if output_opts.json {
-        let mut redacted = new_account.clone();
-        redacted.api_wallet_private_key = Some("[REDACTED]".to_string());
-        output::print_json_pretty(&redacted)?;
-        return Ok(());
-    }
+    /// Stop once the book moves past this price (a ceiling for buys, a floor for sells)
+    #[arg(long)]
+    max_price: Option<String>,
 
-    println!();
-    output::print_success(format!("Account \"{alias}\" added successfully!"));
-    println!("\nAccount details:");
-    println!("  Alias: {}", new_account.alias);
-    println!("  Address: {}", new_account.user_address);
-    println!("  Type: {}", new_account.account_type);
-    if let Some(api_wallet) = &new_account.api_wallet_public_key {
-        println!("  API Wallet: {}", api_wallet);
-    }
-    println!(
-        "  Default: {}",
-        if new_account.is_default { "Yes" } else { "No" }
-    );
-    println!();
+    /// Stop after this long (e.g. 30s, 2m); runs until filled if omitted
+    #[arg(long)]
+    timeout: Option<String>,
 
-    Ok(())
+    /// Reduce-only order
+    #[arg(long, default_value_t = false)]
+    reduce_only: bool,
+
+    /// Error instead of rounding price and size to exchange precision
+    #[arg(long, default_value_t = false)]
+    strict: bool,
 }
 
-async fn account_add_readonly(output_opts: OutputOptions) -> Result<()> {
-    println!();
-    let user_address_input = prompt::prompt("Enter the wallet address to watch: ")?;
-    let user_address = validation::validate_address(&user_address_input)?;
-    let alias = prompt_for_alias()?;
+#[derive(Args, Debug, Clone)]
+struct OrderModifyArgs {
+    /// Order ID to modify (interactive if omitted)
+    oid: Option<String>,
 
-    let mut set_as_default = false;
-    let existing_count = db::get_account_count()?;
-    if existing_count > 0 {
-        set_as_default = prompt::confirm("\nSet this as your default account?", true)?;
-    }
+    /// New limit price
+    #[arg(long)]
+    price: Option<String>,
 
-    let new_account = db::create_account(CreateAccountInput {
-        alias: alias.clone(),
-        user_address: format!("{user_address:#x}"),
-        account_type: "readonly".to_string(),
-        source: Some("cli_import".to_string()),
-        api_wallet_private_key: None,
-        api_wallet_public_key: None,
-        set_as_default,
-    })?;
+    /// New order size
+    #[arg(long)]
+    size: Option<String>,
 
-    if output_opts.json {
-        output::print_json_pretty(&new_account)?;
-        return Ok(());
-    }
+    /// New time-in-force: Gtc, Ioc, Alo
+    #[arg(long)]
+    tif: Option<String>,
 
-    println!();
-    output::print_success(format!("Account \"{alias}\" added successfully!"));
-    println!("\nAccount details:");
-    println!("  Alias: {}", new_account.alias);
-    println!("  Address: {}", new_account.user_address);
-    println!("  Type: {}", new_account.account_type);
-    println!(
-        "  Default: {}",
-        if new_account.is_default { "Yes" } else { "No" }
-    );
-    println!();
-    Ok(())
+    /// New trigger price (trigger orders only)
+    #[arg(long)]
+    trigger_price: Option<String>,
+
+    /// Error instead of rounding price and size to exchange precision
+    #[arg(long, default_value_t = false)]
+    strict: bool,
 }
 
-fn prompt_for_alias() -> Result<String> {
-    loop {
-        let alias = prompt::prompt("Enter an alias for this account (e.g., 'main', 'trading'): ")?;
-        if alias.trim().is_empty() {
-            println!("Alias cannot be empty.");
-            continue;
-        }
-        if db::is_alias_taken(&alias)? {
-            println!("Alias \"{alias}\" is already taken. Please choose another.");
-            continue;
-        }
-        return Ok(alias);
-    }
+#[derive(Args, Debug, Clone)]
+struct OrderModifyBatchArgs {
+    /// Modifications as OID:PRICE or OID:PRICE:SIZE
+    #[arg(required = true)]
+    modifies: Vec<String>,
+
+    /// Time-in-force for every modified limit order: Gtc, Ioc, Alo
+    #[arg(long)]
+    tif: Option<String>,
+
+    /// Error instead of rounding price and size to exchange precision
+    #[arg(long, default_value_t = false)]
+    strict: bool,
 }
 
-fn account_ls(output_opts: OutputOptions) -> Result<()> {
-    let accounts = db::get_all_accounts()?;
+#[derive(Args, Debug, Clone)]
+struct OrderHistoryArgs {
+    /// User address (defaults to configured wallet)
+    #[arg(long)]
+    user: Option<String>,
 
-    if output_opts.json {
-        let json_accounts: Vec<_> = accounts
-            .iter()
-            .map(|acc| {
-                let mut a = acc.clone();
-                a.api_wallet_private_key = None;
-                a
-            })
-            .collect();
-        output::print_json_pretty(&json_accounts)?;
-        return Ok(());
-    }
+    /// Only orders in this coin
+    #[arg(long)]
+    coin: Option<String>,
 
-    if accounts.is_empty() {
-        println!("{}", output::style_muted("No accounts found."));
-        println!(
-            "{}",
-            output::style_muted("Run 'rhl account add' to add your first account.")
-        );
-        return Ok(());
-    }
+    /// Only orders that are open, filled, canceled, triggered or rejected
+    #[arg(long)]
+    status: Option<String>,
 
-    let rows: Vec<Vec<String>> = accounts
-        .iter()
-        .map(|acc| {
-            vec![
-                if acc.is_default {
-                    "*".to_string()
-                } else {
-                    "".to_string()
-                },
-                acc.alias.clone(),
-                output::format_short_address(&acc.user_address),
-                acc.account_type.clone(),
-                acc.api_wallet_public_key
-                    .as_ref()
-                    .map(|s| output::format_short_address(s))
-                    .unwrap_or_else(|| "-".to_string()),
-            ]
-        })
-        .collect();
+    /// Maximum number of orders to show
+    #[arg(long, default_value = "50")]
+    limit: String,
+}
 
-    let columns = [
-        output::TableColumn::left_with_width("", 2),
-        output::TableColumn::left("Alias"),
-        output::TableColumn::left("Address"),
-        output::TableColumn::left("Type"),
-        output::TableColumn::left("API Wallet"),
-    ];
-    output::print_table_with_columns(&columns, rows);
-    println!();
-    println!("{}", output::style_muted("* = default account"));
-    Ok(())
+#[derive(Args, Debug, Clone)]
+struct OrderStatusArgs {
+    /// Order ID, or client order ID (0x + 32 hex characters)
+    id: String,
+
+    /// User address (defaults to configured wallet)
+    #[arg(long)]
+    user: Option<String>,
 }
 
-async fn account_set_default(
-    args: AccountSetDefaultArgs,
-    output_opts: OutputOptions,
-) -> Result<()> {
-    let alias = args.alias;
+#[derive(Args, Debug, Clone)]
+struct OrderCancelArgs {
+    /// Order ID to cancel (interactive if omitted)
+    oid: Option<String>,
 
-    let existing = db::get_account_by_alias(&alias)?;
-    let Some(existing) = existing else {
-        return Err(anyhow!(
-            "Account with alias \"{alias}\" not found. Run 'rhl account ls' to see available accounts."
-        ));
-    };
+    /// Cancel by client order ID instead of order ID
+    #[arg(long, conflicts_with = "oid")]
+    cloid: Option<String>,
+}
 
-    if existing.is_default {
-        if output_opts.json {
-            output::print_json_pretty(&serde_json::json!({
-                "message": format!("Account \"{alias}\" is already the default")
-            }))?;
-        } else {
-            println!("Account \"{alias}\" is already the default.");
-        }
-        return Ok(());
-    }
+#[derive(Args, Debug, Clone)]
+struct OrderCancelAllArgs {
+    /// Skip confirmation prompt
+    #[arg(short = 'y', long, default_value_t = false)]
+    yes: bool,
 
-    let updated = db::set_default_account(&alias)?;
+    /// Only cancel orders for a specific coin
+    #[arg(long)]
+    coin: Option<String>,
+}
 
-    if output_opts.json {
-        output::print_json_pretty(&serde_json::json!({
-            "id": updated.id,
-            "alias": updated.alias,
-            "userAddress": updated.user_address,
-            "type": updated.account_type,
-            "isDefault": updated.is_default,
-        }))?;
-        return Ok(());
-    }
+#[derive(Args, Debug, Clone)]
+struct OrderBatchArgs {
+    /// JSON, NDJSON or CSV file of orders and cancels, or - for stdin
+    input: String,
 
-    output::print_success(format!("Account \"{alias}\" is now the default."));
-    Ok(())
+    /// Input format: json, ndjson, csv (detected from the file if omitted)
+    #[arg(long)]
+    format: Option<String>,
+
+    /// Error instead of rounding price and size to exchange precision
+    #[arg(long, default_value_t = false)]
+    strict: bool,
 }
 
-async fn account_remove(args: AccountRemoveArgs, output_opts: OutputOptions) -> Result<()> {
-    let existing = db::get_account_by_alias(&args.alias)?;
-    let Some(existing) = existing else {
-        return Err(anyhow!(
-            "Account with alias \"{}\" not found. Run 'rhl account ls' to see available accounts.",
-            args.alias
-        ));
-    };
+#[derive(Args, Debug, Clone)]
+struct OrderDeadManArgs {
+    /// Cancel all open orders after this long (e.g. 30s, 5m)
+    #[arg(
+        long = "in",
+        value_name = "DURATION",
+        conflicts_with_all = ["clear", "heartbeat"],
+        required_unless_present_any = ["clear", "heartbeat"]
+    )]
+    after: Option<String>,
+
+    /// Remove the scheduled cancel
+    #[arg(long, default_value_t = false, conflicts_with = "heartbeat")]
+    clear: bool,
+
+    /// Send one heartbeat to rhl-server for a kept-alive switch
+    #[arg(long, default_value_t = false)]
+    heartbeat: bool,
 
-    if !args.force {
-        let confirmed = prompt::confirm(
-            &format!(
-                "Are you sure you want to remove account \"{}\" ({})?",
-                args.alias, existing.user_address
-            ),
-            false,
-        )?;
-        if !confirmed {
-            println!("Cancelled.");
-            return Ok(());
-        }
-    }
+    /// Have rhl-server keep re-arming the switch while heartbeats arrive
+    #[arg(long, default_value_t = false, requires = "after")]
+    keepalive: bool,
 
-    let deleted = db::delete_account(&args.alias)?;
-    if !deleted {
-        return Err(anyhow!("Failed to remove account \"{}\"", args.alias));
-    }
+    /// File whose modification time counts as a heartbeat (with --keepalive)
+    #[arg(long, requires = "keepalive")]
+    heartbeat_file: Option<String>,
+}
 
-    if output_opts.json {
-        output::print_json_pretty(&serde_json::json!({
-            "message": format!("Account \"{}\" removed", args.alias),
-            "deleted": true
-        }))?;
-    } else {
-        output::print_success(format!("Account \"{}\" removed.", args.alias));
-    }
-    Ok(())
+#[derive(Args, Debug, Clone)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+struct OrderTrailArgs {
+    #[command(subcommand)]
+    command: Option<OrderTrailCommand>,
+
+    /// Coin of the position to protect (e.g., BTC, ETH)
+    #[arg(required = true)]
+    coin: Option<String>,
+
+    /// How far the stop follows: a percentage (1.5%) or a price amount (50)
+    #[arg(long, required = true)]
+    distance: Option<String>,
+
+    /// Size to close when the stop fires (default: the whole position)
+    #[arg(long)]
+    size: Option<String>,
+
+    /// Slippage for the closing market order in percent (default: configured)
+    #[arg(long)]
+    slippage: Option<String>,
 }
 
-#[derive(serde::Serialize)]
-struct PositionRow {
-    coin: String,
-    size: String,
-    #[serde(rename = "entryPx")]
-    entry_px: Option<String>,
-    #[serde(rename = "positionValue")]
-    position_value: String,
-    #[serde(rename = "unrealizedPnl")]
-    unrealized_pnl: String,
-    leverage: String,
-    #[serde(rename = "liquidationPx")]
-    liquidation_px: String,
+#[derive(Subcommand, Debug, Clone)]
+enum OrderTrailCommand {
+    /// List trailing stops registered with rhl-server
+    Ls,
+    /// Remove a trailing stop
+    Rm(OrderTrailRmArgs),
 }
 
-#[derive(serde::Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct AllDexsClearinghouseStateEvent {
-    clearinghouse_states: Vec<(String, hyperliquid_cli::hl_api::ClearinghouseState)>,
+#[derive(Args, Debug, Clone)]
+struct OrderTrailRmArgs {
+    /// Trailing stop ID (see `order trail ls`)
+    #[arg(required_unless_present = "all")]
+    id: Option<String>,
+
+    /// Remove every trailing stop
+    #[arg(long, default_value_t = false, conflicts_with = "id")]
+    all: bool,
 }
 
-fn format_pnl_cell(value: &str) -> String {
-    let Ok(num_value) = value.parse::<f64>() else {
-        return output::style_muted("-");
-    };
-    if !num_value.is_finite() {
-        return output::style_muted("-");
-    }
+#[derive(Args, Debug, Clone)]
+struct OrderCloseArgs {
+    /// Coin symbol (e.g., BTC, ETH)
+    coin: String,
 
-    let sign = if num_value > 0.0 { "+" } else { "" };
-    let formatted = format!("{sign}{num_value:.2}");
-    if num_value > 0.0 {
-        output::style_profit(formatted)
-    } else if num_value < 0.0 {
-        output::style_loss(formatted)
-    } else {
-        formatted
-    }
+    /// Percentage of the position to close
+    #[arg(long, default_value = "100")]
+    pct: String,
+
+    /// Slippage percentage (overrides config)
+    #[arg(long)]
+    slippage: Option<String>,
 }
 
-async fn account_positions(
-    cfg: &config::LoadedConfig,
-    api: &HlApi,
-    args: AccountUserWatchArgs,
-    output_opts: OutputOptions,
-) -> Result<()> {
-    let user = resolve_user_address(cfg, &args.user)?;
-    let table_columns = [
-        output::TableColumn::left("Coin"),
-        output::TableColumn::right("Size"),
-        output::TableColumn::right("Entry"),
-        output::TableColumn::right("Value"),
-        output::TableColumn::right("PnL"),
-        output::TableColumn::right("Leverage"),
-        output::TableColumn::right("Liq. Price"),
-    ];
+#[derive(Args, Debug, Clone)]
+struct OrderFlattenArgs {
+    /// Cancel all open orders before closing positions
+    #[arg(long, default_value_t = false)]
+    cancel_orders: bool,
 
-    if args.watch {
-        if !output_opts.json {
-            watch::hide_cursor();
-        }
+    /// Slippage percentage (overrides config)
+    #[arg(long)]
+    slippage: Option<String>,
 
-        let ctrl_c = tokio::signal::ctrl_c();
-        tokio::pin!(ctrl_c);
+    /// Skip confirmation prompt
+    #[arg(short = 'y', long, default_value_t = false)]
+    yes: bool,
+}
 
-        let mut last_updated = watch::format_timestamp();
-        let mut positions: Vec<PositionRow> = Vec::new();
-        let mut account_value = "0".to_string();
-        let mut total_margin_used = "0".to_string();
+#[derive(Args, Debug, Clone)]
+struct OrderSetLeverageArgs {
+    /// Coin symbol
+    coin: String,
+    /// Leverage value
+    leverage: String,
 
-        let render = |positions: &[PositionRow],
-                      account_value: &str,
-                      total_margin_used: &str,
-                      last_updated: &str| {
-            watch::clear_screen();
-            println!("{}", output::format_watch_header("Positions", last_updated));
-            println!();
+    /// Use cross margin
+    #[arg(long, default_value_t = false)]
+    cross: bool,
 
-            if positions.is_empty() {
-                println!("{}", output::style_muted("No open positions"));
-            } else {
-                let rows: Vec<Vec<String>> = positions
-                    .iter()
-                    .map(|p| {
-                        vec![
-                            p.coin.clone(),
-                            p.size.clone(),
-                            p.entry_px.clone().unwrap_or_else(|| "-".to_string()),
-                            p.position_value.clone(),
-                            format_pnl_cell(&p.unrealized_pnl),
-                            p.leverage.clone(),
-                            p.liquidation_px.clone(),
-                        ]
-                    })
-                    .collect();
-                output::print_table_with_columns(&table_columns, rows);
-            }
+    /// Use isolated margin
+    #[arg(long, default_value_t = false)]
+    isolated: bool,
+}
 
-            println!();
-            println!("Account Value: {}", output::style_bold(account_value));
-            println!(
-                "Total Margin Used: {}",
-                output::style_bold(total_margin_used)
-            );
-            println!();
-            println!("{}", output::style_muted("Press Ctrl+C to exit"));
-        };
+#[derive(Subcommand, Debug, Clone)]
+enum OrderMarginCommand {
+    /// Add USD margin to an isolated position
+    Add(OrderMarginArgs),
+    /// Remove USD margin from an isolated position
+    Remove(OrderMarginArgs),
+}
 
-        if !output_opts.json {
-            render(
-                &positions,
-                &account_value,
-                &total_margin_used,
-                &last_updated,
-            );
-        }
+#[derive(Args, Debug, Clone)]
+struct OrderMarginArgs {
+    /// Coin symbol
+    coin: String,
+    /// Amount in USD
+    amount: String,
+}
 
-        'outer: loop {
-            let mut client = match ws::WsClient::connect(api.testnet).await {
-                Ok(c) => c,
-                Err(e) => {
-                    if !output_opts.json {
-                        watch::clear_screen();
-                        println!("{}", output::style_loss(format!("Error: {e}")));
-                        println!("{}", output::style_muted("Reconnecting..."));
-                    } else {
-                        output::print_error(e.to_string());
-                    }
-                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                    continue;
-                }
-            };
+#[derive(Args, Debug, Clone)]
+struct OrderConfigureArgs {
+    /// Show the configuration (for one coin with --coin)
+    #[arg(long, default_value_t = false)]
+    show: bool,
 
-            if let Err(e) = client
-                .subscribe(ws::sub_all_dexs_clearinghouse_state(user))
-                .await
-            {
-                if !output_opts.json {
-                    watch::clear_screen();
-                    println!("{}", output::style_loss(format!("Error: {e}")));
-                    println!("{}", output::style_muted("Reconnecting..."));
-                } else {
-                    output::print_error(e.to_string());
-                }
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                continue;
-            }
+    /// Set order defaults for this coin instead of all coins
+    #[arg(long)]
+    coin: Option<String>,
 
-            loop {
-                tokio::select! {
-                    msg = client.next_json() => {
-                        let msg = match msg {
-                            Ok(Some(m)) => m,
-                            Ok(None) => break,
-                            Err(e) => {
-                                if !output_opts.json {
-                                    watch::clear_screen();
-                                    println!("{}", output::style_loss(format!("Error: {e}")));
-                                    println!("{}", output::style_muted("Reconnecting..."));
-                                } else {
-                                    output::print_error(e.to_string());
-                                }
-                                break;
-                            }
-                        };
+    /// Remove settings so they fall back to the global or built-in default (comma-separated, e.g. tif,max-order-usd)
+    #[arg(long, value_name = "KEYS", value_delimiter = ',')]
+    unset: Vec<String>,
 
-                        let Ok(channel) = ws::WsClient::channel(&msg) else {
-                            continue;
-                        };
-                        if channel != "allDexsClearinghouseState" {
-                            continue;
-                        }
+    /// Set default slippage percentage for market orders
+    #[arg(long)]
+    slippage: Option<String>,
 
-                        let Ok(data) = ws::WsClient::data(&msg) else {
-                            continue;
-                        };
-                        let Ok(event) = serde_json::from_value::<AllDexsClearinghouseStateEvent>(data.clone()) else {
-                            continue;
-                        };
+    /// Default time-in-force for limit orders: Gtc, Ioc, Alo
+    #[arg(long)]
+    tif: Option<String>,
 
-                        let clearinghouse_states = event.clearinghouse_states;
-                        if let Some((_, main)) = clearinghouse_states.first() {
-                            account_value = main.margin_summary.account_value.clone();
-                            total_margin_used = main.margin_summary.total_margin_used.clone();
-                        } else {
-                            account_value = "0".to_string();
-                            total_margin_used = "0".to_string();
-                        }
+    /// Default reduce-only flag for limit and market orders
+    #[arg(long, value_name = "BOOL")]
+    reduce_only: Option<bool>,
 
-                        positions = clearinghouse_states
-                            .into_iter()
-                            .flat_map(|(_, st)| st.asset_positions.into_iter().map(|ap| ap.position))
-                            .filter(|p| p.szi.parse::<f64>().unwrap_or(0.0) != 0.0)
-                            .map(|p| PositionRow {
-                                coin: p.coin,
-                                size: p.szi,
-                                entry_px: p.entry_px,
-                                position_value: p.position_value,
-                                unrealized_pnl: p.unrealized_pnl,
-                                leverage: format!("{}x {}", p.leverage.value, p.leverage.leverage_type),
-                                liquidation_px: p.liquidation_px.unwrap_or_else(|| "-".to_string()),
-                            })
-                            .collect();
+    /// Leverage to set before limit and market orders, if different
+    #[arg(long)]
+    leverage: Option<String>,
 
-                        last_updated = watch::format_timestamp();
+    /// Margin mode to use with the default leverage: cross or isolated
+    #[arg(long)]
+    margin_mode: Option<String>,
 
-                        if output_opts.json {
-                            output::print_json_line(&serde_json::json!({
-                                "positions": positions,
-                                "accountValue": account_value,
-                                "totalMarginUsed": total_margin_used,
-                                "timestamp": chrono::Utc::now().to_rfc3339(),
-                            }))?;
-                        } else {
-                            render(&positions, &account_value, &total_margin_used, &last_updated);
-                        }
-                    }
-                    _ = &mut ctrl_c => break 'outer,
-                }
-            }
+    /// Reject orders above this notional (USD)
+    #[arg(long, value_name = "USD")]
+    max_order_usd: Option<String>,
 
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-        }
+    /// Reject orders that take a perp position above this notional (USD)
+    #[arg(long, value_name = "USD")]
+    max_position_usd: Option<String>,
 
-        if !output_opts.json {
-            watch::show_cursor();
-        }
-        return Ok(());
-    }
+    /// Reject orders that take account leverage above this, and higher leverage settings
+    #[arg(long)]
+    max_leverage: Option<String>,
 
-    let state = api.clearinghouse_state(user).await?;
-    let positions: Vec<PositionRow> = state
-        .asset_positions
-        .iter()
-        .map(|p| &p.position)
-        .filter(|p| p.szi.parse::<f64>().unwrap_or(0.0) != 0.0)
-        .map(|p| PositionRow {
-            coin: p.coin.clone(),
-            size: p.szi.clone(),
-            entry_px: p.entry_px.clone(),
-            position_value: p.position_value.clone(),
-            unrealized_pnl: p.unrealized_pnl.clone(),
-            leverage: format!("{}x {}", p.leverage.value, p.leverage.leverage_type),
-            liquidation_px: p.liquidation_px.clone().unwrap_or_else(|| "-".to_string()),
-        })
-        .collect();
+    /// Only allow trading these coins (comma-separated)
+    #[arg(long, value_name = "COINS", value_delimiter = ',')]
+    allow_coins: Option<Vec<String>>,
 
-    if output_opts.json {
-        output::print_json_pretty(&serde_json::json!({
-            "positions": positions,
-            "marginSummary": state.margin_summary,
-            "crossMarginSummary": state.cross_margin_summary,
-        }))?;
-        return Ok(());
+    /// Never allow trading these coins (comma-separated)
+    #[arg(long, value_name = "COINS", value_delimiter = ',')]
+    deny_coins: Option<Vec<String>>,
+
+    /// Ask for confirmation before orders above this notional (USD)
+    #[arg(long, value_name = "USD")]
+    confirm_above_usd: Option<String>,
+
+    /// Apply the limits to this account only (alias)
+    #[arg(long)]
+    account: Option<String>,
+}
+
+impl OrderConfigureArgs {
+    fn order_defaults(&self) -> Result<order_config::OrderDefaults> {
+        Ok(order_config::OrderDefaults {
+            slippage: self
+                .slippage
+                .as_deref()
+                .map(|s| validation::validate_non_negative_number(s, "slippage"))
+                .transpose()?,
+            tif: self
+                .tif
+                .as_deref()
+                .map(|t| validation::validate_tif(t).map(|tif| format!("{tif:?}")))
+                .transpose()?,
+            reduce_only: self.reduce_only,
+            leverage: self
+                .leverage
+                .as_deref()
+                .map(|v| validation::validate_positive_u64(v, "leverage").map(|l| l as u32))
+                .transpose()?,
+            margin_mode: self
+                .margin_mode
+                .as_deref()
+                .map(order_config::MarginMode::parse)
+                .transpose()?,
+        })
     }
 
-    if positions.is_empty() {
-        println!("{}", output::style_muted("No open positions"));
-    } else {
-        let rows: Vec<Vec<String>> = positions
-            .iter()
-            .map(|p| {
-                vec![
-                    p.coin.clone(),
-                    p.size.clone(),
-                    p.entry_px.clone().unwrap_or_else(|| "-".to_string()),
-                    p.position_value.clone(),
-                    format_pnl_cell(&p.unrealized_pnl),
-                    p.leverage.clone(),
-                    p.liquidation_px.clone(),
-                ]
-            })
-            .collect();
-        output::print_table_with_columns(&table_columns, rows);
+    fn risk_limits(&self) -> Result<order_config::RiskLimits> {
+        let usd = |value: &Option<String>, name: &str| {
+            value
+                .as_deref()
+                .map(|v| validation::validate_positive_number(v, name))
+                .transpose()
+        };
+        let coins = |value: &Option<Vec<String>>| {
+            value
+                .iter()
+                .flatten()
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty())
+                .collect()
+        };
+        Ok(order_config::RiskLimits {
+            max_order_usd: usd(&self.max_order_usd, "max-order-usd")?,
+            max_position_usd: usd(&self.max_position_usd, "max-position-usd")?,
+            max_leverage: self
+                .max_leverage
+                .as_deref()
+                .map(|v| validation::validate_positive_u64(v, "max-leverage").map(|l| l as u32))
+                .transpose()?,
+            allow_coins: coins(&self.allow_coins),
+            deny_coins: coins(&self.deny_coins),
+            confirm_above_usd: usd(&self.confirm_above_usd, "confirm-above-usd")?,
+        })
     }
-    println!();
-    println!(
-        "Account Value: {}",
-        output::style_bold(&state.margin_summary.account_value)
-    );
-    println!(
-        "Total Margin Used: {}",
-        output::style_bold(&state.margin_summary.total_margin_used)
-    );
-    Ok(())
 }
 
-#[derive(serde::Serialize)]
-struct OrderRow {
-    oid: u64,
-    coin: String,
-    side: String,
-    sz: String,
-    #[serde(rename = "limitPx")]
-    limit_px: String,
-    timestamp: String,
+#[derive(Subcommand, Debug)]
+enum ReferralCommand {
+    Set(ReferralSetArgs),
+    Status,
 }
 
-fn format_order_timestamp(ms: u64) -> String {
-    let Some(dt_utc) = chrono::DateTime::<chrono::Utc>::from_timestamp_millis(ms as i64) else {
-        return ms.to_string();
-    };
+#[derive(Args, Debug, Clone)]
+struct ReferralSetArgs {
+    /// Referral code
+    code: String,
+}
 
-    let dt = dt_utc.with_timezone(&chrono::Local);
+#[derive(Subcommand, Debug)]
+enum ServerCommand {
+    Start,
+    Stop,
+    Status,
+}
 
-    let locale_raw = std::env::var("LC_ALL")
-        .ok()
-        .filter(|s| !s.trim().is_empty())
-        .or_else(|| {
-            std::env::var("LC_TIME")
-                .ok()
-                .filter(|s| !s.trim().is_empty())
-        })
-        .or_else(|| std::env::var("LANG").ok().filter(|s| !s.trim().is_empty()));
+#[tokio::main]
+async fn main() {
+    install_broken_pipe_panic_hook();
 
-    let locale = locale_raw
-        .as_deref()
-        .and_then(|s| s.split(['.', '@']).next())
-        .map(|s| s.replace('_', "-"));
+    let cli = Cli::parse();
+    let start = Instant::now();
 
-    match locale.as_deref() {
-        Some(tag) if tag.eq_ignore_ascii_case("en-US") => {
-            dt.format("%m/%d/%Y, %-I:%M:%S %p").to_string()
+    let output_opts = OutputOptions { json: cli.json };
+    let result = run(cli, output_opts).await;
+
+    match result {
+        Ok(()) => {
+            if !output_opts.json {
+                let duration = start.elapsed().as_secs_f64();
+                println!("\nCompleted in {:.2}s", duration);
+            }
         }
-        Some(tag) if tag.eq_ignore_ascii_case("en-AU") => dt
-            .format("%d/%m/%Y, %-I:%M:%S %p")
-            .to_string()
-            .replace("AM", "am")
-            .replace("PM", "pm"),
-        Some(tag) if tag.eq_ignore_ascii_case("en-GB") => {
-            dt.format("%d/%m/%Y, %H:%M:%S").to_string()
+        Err(err) => {
+            output::print_error(err.to_string());
+            std::process::exit(1);
         }
-        _ => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
     }
 }
 
-async fn fetch_open_orders(api: &HlApi, user: Address) -> Result<Vec<OrderRow>> {
-    let orders = api.open_orders(user).await?;
-    let formatted: Vec<OrderRow> = orders
-        .into_iter()
-        .map(|o| OrderRow {
-            oid: o.oid,
-            coin: o.coin,
-            side: o.side,
-            sz: o.sz,
-            limit_px: o.limit_px,
-            timestamp: format_order_timestamp(o.timestamp),
-        })
-        .collect();
-    Ok(formatted)
+fn install_broken_pipe_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<String>()
+            .map(String::as_str)
+            .or_else(|| info.payload().downcast_ref::<&str>().copied());
+
+        if let Some(msg) = message
+            && msg.contains("Broken pipe")
+        {
+            std::process::exit(0);
+        }
+
+        default_hook(info);
+    }));
 }
 
-async fn account_orders(
-    cfg: &config::LoadedConfig,
-    api: &HlApi,
-    args: AccountUserWatchArgs,
-    output_opts: OutputOptions,
-) -> Result<()> {
-    let user = resolve_user_address(cfg, &args.user)?;
-    let table_columns = [
-        output::TableColumn::right("OID"),
-        output::TableColumn::left("Coin"),
-        output::TableColumn::left("Side"),
-        output::TableColumn::right("Size"),
-        output::TableColumn::right("Price"),
-        output::TableColumn::left("Time"),
-    ];
+async fn run(cli: Cli, output_opts: OutputOptions) -> Result<()> {
+    let mut cfg = config::load_config(cli.testnet)?;
+    cfg.dry_run = cli.dry_run;
+    if let Some(vault) = &cli.vault {
+        cfg.vault = Some(config::Vault::resolve(vault)?);
+    }
+    let api = HlApi::new(cli.testnet)?;
 
-    if args.watch {
-        if !output_opts.json {
-            watch::hide_cursor();
+    match cli.command {
+        Command::Account { command } => run_account(command, &cfg, &api, output_opts).await,
+        Command::Markets { command } => run_markets(command, &api, output_opts).await,
+        Command::Asset { command } => run_asset(command, &cfg, &api, output_opts).await,
+        Command::Order { command } => run_order(*command, &cfg, &api, output_opts).await,
+        Command::Referral { command } => run_referral(command, &cfg, &api, output_opts).await,
+        Command::Server { command } => run_server(command, cli.testnet, output_opts).await,
+        Command::Upgrade => run_upgrade(output_opts).await,
+    }
+}
+
+/// Account commands act on: the `--vault`, or else the configured account.
+fn account_address(cfg: &config::LoadedConfig) -> Option<Address> {
+    cfg.vault_address().or(cfg.wallet_address)
+}
+
+fn require_wallet_address(cfg: &config::LoadedConfig) -> Result<Address> {
+    account_address(cfg).ok_or_else(|| {
+        anyhow!("No account configured. Run 'rhl account add' to set up your account.")
+    })
+}
+
+/// The configured account itself, which signs even when trading a vault.
+fn require_signer_address(cfg: &config::LoadedConfig) -> Result<Address> {
+    cfg.wallet_address.ok_or_else(|| {
+        anyhow!("No account configured. Run 'rhl account add' to set up your account.")
+    })
+}
+
+/// Ask a yes/no question about a trade, naming the vault when trading one.
+fn confirm_trade(cfg: &config::LoadedConfig, question: &str) -> Result<bool> {
+    match &cfg.vault {
+        Some(vault) => prompt::confirm(&format!("[vault {vault}] {question}"), false),
+        None => prompt::confirm(question, false),
+    }
+}
+
+fn resolve_user_address(cfg: &config::LoadedConfig, user: &Option<String>) -> Result<Address> {
+    if let Some(u) = user {
+        return validation::validate_address(u);
+    }
+    require_wallet_address(cfg)
+}
+
+fn exchange_chain(testnet: bool) -> Chain {
+    if testnet {
+        Chain::ArbitrumTestnet
+    } else {
+        Chain::Arbitrum
+    }
+}
+
+fn require_wallet_signer(cfg: &config::LoadedConfig) -> Result<Arc<LocalWallet>> {
+    let Some(pk) = &cfg.private_key else {
+        if let Some(acc) = &cfg.account
+            && acc.account_type == config::AccountType::Readonly
+        {
+            return Err(anyhow!(
+                "Account \"{}\" is read-only and cannot perform trading operations.\nRun 'rhl account add' to set up an API wallet for trading.",
+                acc.alias
+            ));
         }
-        let ctrl_c = tokio::signal::ctrl_c();
-        tokio::pin!(ctrl_c);
+        return Err(anyhow!(
+            "No account configured. Run 'rhl account add' to set up your account."
+        ));
+    };
+    let pk = validation::validate_private_key(pk)?;
+    let wallet = LocalWallet::from_str(&pk).context("Invalid private key")?;
+    Ok(Arc::new(wallet))
+}
 
-        let mut last_updated = watch::format_timestamp();
-        let mut orders: Vec<OrderRow> = Vec::new();
+/// One order of a `--dry-run` preview, with the economics of the action.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DryRunOrder {
+    coin: String,
+    asset: u32,
+    side: &'static str,
+    #[serde(rename = "type")]
+    order_type: String,
+    price: Option<String>,
+    size: String,
+    reduce_only: bool,
+    notional: Option<f64>,
+    estimated_margin: Option<f64>,
+}
 
-        let render = |orders: &[OrderRow], last_updated: &str| {
-            watch::clear_screen();
-            println!(
-                "{}",
-                output::format_watch_header("Open Orders", last_updated)
-            );
-            println!();
-            if orders.is_empty() {
-                println!("{}", output::style_muted("No open orders"));
+fn dry_run_order(asset: u32, order: &OrderRequest) -> DryRunOrder {
+    let order_type = match &order.order_type {
+        OrderType::Limit(limit) => format!("Limit {:?}", limit.tif),
+        OrderType::Trigger(trigger) => format!(
+            "{} {} @ {}",
+            if matches!(trigger.tpsl, TpSl::Tp) {
+                "Take Profit"
             } else {
-                let rows: Vec<Vec<String>> = orders
-                    .iter()
-                    .map(|o| {
-                        let side = if o.side == "B" {
-                            output::style_profit("Buy")
-                        } else {
-                            output::style_loss("Sell")
-                        };
-                        vec![
-                            o.oid.to_string(),
-                            o.coin.clone(),
-                            side,
-                            o.sz.clone(),
-                            o.limit_px.clone(),
-                            o.timestamp.clone(),
-                        ]
-                    })
-                    .collect();
-                output::print_table_with_columns(&table_columns, rows);
-            }
-            println!();
-            println!("{}", output::style_muted("Press Ctrl+C to exit"));
-        };
+                "Stop"
+            },
+            if trigger.is_market { "Market" } else { "Limit" },
+            trigger.trigger_px
+        ),
+    };
+    DryRunOrder {
+        coin: String::new(),
+        asset,
+        side: if order.is_buy { "buy" } else { "sell" },
+        order_type,
+        price: Some(order.limit_px.clone()),
+        size: order.sz.clone(),
+        reduce_only: order.reduce_only,
+        notional: None,
+        estimated_margin: None,
+    }
+}
 
-        if !output_opts.json {
-            render(&orders, &last_updated);
+/// Orders carried by an exchange action, for the `--dry-run` preview.
+fn dry_run_orders(action: &Action) -> Vec<DryRunOrder> {
+    match action {
+        Action::Order { orders, .. } => orders.iter().map(|o| dry_run_order(o.asset, o)).collect(),
+        Action::Modify(m) => vec![dry_run_order(m.order.asset, &m.order)],
+        Action::BatchModify { modifies } => modifies
+            .iter()
+            .map(|m| dry_run_order(m.order.asset, &m.order))
+            .collect(),
+        Action::TwapOrder { twap } => vec![DryRunOrder {
+            coin: String::new(),
+            asset: twap.asset,
+            side: if twap.is_buy { "buy" } else { "sell" },
+            order_type: format!("TWAP {}m", twap.duration),
+            price: None,
+            size: twap.sz.clone(),
+            reduce_only: twap.reduce_only,
+            notional: None,
+            estimated_margin: None,
+        }],
+        _ => Vec::new(),
+    }
+}
+
+/// A `--dry-run` preview: every action a command would sign, in order, with
+/// the orders they carry and any warnings raised while building them.
+#[derive(Default)]
+struct DryRun {
+    actions: Vec<serde_json::Value>,
+    orders: Vec<DryRunOrder>,
+    warnings: Vec<String>,
+    /// Extra command-specific fields for the JSON document.
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl DryRun {
+    fn warning(mut self, msg: Option<String>) -> Self {
+        self.warnings.extend(msg);
+        self
+    }
+
+    /// Add an action built by the `hyperliquid` crate.
+    fn action(self, action: &Action) -> Result<Self> {
+        self.raw_action(action, dry_run_orders(action))
+    }
+
+    fn raw_action(
+        mut self,
+        action: &impl serde::Serialize,
+        orders: Vec<DryRunOrder>,
+    ) -> Result<Self> {
+        self.actions.push(serde_json::to_value(action)?);
+        self.orders.extend(orders);
+        Ok(self)
+    }
+
+    fn field(mut self, key: &str, value: &impl serde::Serialize) -> Result<Self> {
+        self.fields
+            .insert(key.to_string(), serde_json::to_value(value)?);
+        Ok(self)
+    }
+
+    /// Print the resolved orders and the exact actions that would be signed,
+    /// without signing or sending anything.
+    async fn print(
+        mut self,
+        cfg: &config::LoadedConfig,
+        api: &HlApi,
+        output_opts: OutputOptions,
+    ) -> Result<()> {
+        let orders = &mut self.orders;
+        if !orders.is_empty() {
+            let (all_perp_metas, spot_meta) =
+                tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+            let mut asset_data = std::collections::HashMap::new();
+            for order in orders.iter_mut() {
+                order.coin = asset_index::coin_for_asset(&all_perp_metas, &spot_meta, order.asset)
+                    .unwrap_or_else(|| order.asset.to_string());
+                let is_spot = (10_000..100_000).contains(&order.asset);
+                let data = match (account_address(cfg), is_spot) {
+                    (Some(user), false) => {
+                        if !asset_data.contains_key(&order.coin) {
+                            let data = api.active_asset_data(user, &order.coin).await.ok();
+                            asset_data.insert(order.coin.clone(), data);
+                        }
+                        asset_data.get(&order.coin).and_then(|d| d.as_ref())
+                    }
+                    _ => None,
+                };
+
+                let size: f64 = order.size.parse().unwrap_or(0.0);
+                let price = order
+                    .price
+                    .as_deref()
+                    .or(data.map(|d| d.mark_px.as_str()))
+                    .and_then(|p| p.parse::<f64>().ok());
+                order.notional = price.map(|px| px * size);
+                order.estimated_margin = match (order.notional, order.reduce_only) {
+                    (_, true) => Some(0.0),
+                    (Some(notional), false) if is_spot => Some(notional),
+                    (Some(notional), false) => data
+                        .filter(|d| d.leverage.value > 0)
+                        .map(|d| notional / d.leverage.value as f64),
+                    (None, false) => None,
+                };
+            }
         }
 
-        match fetch_open_orders(api, user).await {
-            Ok(initial) => {
-                orders = initial;
-                last_updated = watch::format_timestamp();
-                if output_opts.json {
-                    output::print_json_line(&serde_json::json!({
-                        "orders": orders,
-                        "timestamp": chrono::Utc::now().to_rfc3339(),
-                    }))?;
-                } else {
-                    render(&orders, &last_updated);
-                }
+        if output_opts.json {
+            let mut doc = serde_json::json!({
+                "dryRun": true,
+                "vaultAddress": cfg.vault_address(),
+            });
+            if !self.warnings.is_empty() {
+                doc["warnings"] = serde_json::json!(self.warnings);
             }
-            Err(e) => {
-                if !output_opts.json {
-                    watch::clear_screen();
-                    println!("{}", output::style_loss(format!("Error: {e}")));
-                    println!("{}", output::style_muted("Reconnecting..."));
-                } else {
-                    output::print_error(e.to_string());
-                }
+            doc["orders"] = serde_json::json!(self.orders);
+            if let [action] = self.actions.as_slice() {
+                doc["action"] = action.clone();
+            } else {
+                doc["actions"] = serde_json::json!(self.actions);
+            }
+            for (key, value) in self.fields {
+                doc[key] = value;
             }
+            return output::print_json_pretty(&doc);
         }
 
-        'outer: loop {
-            let mut client = match ws::WsClient::connect(api.testnet).await {
-                Ok(c) => c,
-                Err(e) => {
-                    if !output_opts.json {
-                        watch::clear_screen();
-                        println!("{}", output::style_loss(format!("Error: {e}")));
-                        println!("{}", output::style_muted("Reconnecting..."));
-                    } else {
-                        output::print_error(e.to_string());
-                    }
-                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                    continue;
-                }
+        println!(
+            "{}",
+            output::style_warning("Dry run: nothing was signed or sent")
+        );
+        for warning in &self.warnings {
+            println!("{}", output::style_warning(warning));
+        }
+        if let Some(vault) = &cfg.vault {
+            println!("Vault: {vault}");
+        }
+        println!();
+        if !self.orders.is_empty() {
+            let columns = [
+                output::TableColumn::left("Coin"),
+                output::TableColumn::right("Asset"),
+                output::TableColumn::left("Side"),
+                output::TableColumn::left("Type"),
+                output::TableColumn::right("Price"),
+                output::TableColumn::right("Size"),
+                output::TableColumn::right("Notional"),
+                output::TableColumn::right("Est. Margin"),
+            ];
+            let fmt_usd = |v: Option<f64>| {
+                v.map(|v| format!("{v:.2}"))
+                    .unwrap_or_else(|| "-".to_string())
             };
-
-            if let Err(e) = client.subscribe(ws::sub_order_updates(user)).await {
-                if !output_opts.json {
-                    watch::clear_screen();
-                    println!("{}", output::style_loss(format!("Error: {e}")));
-                    println!("{}", output::style_muted("Reconnecting..."));
-                } else {
-                    output::print_error(e.to_string());
-                }
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                continue;
+            let rows: Vec<Vec<String>> = self
+                .orders
+                .iter()
+                .map(|o| {
+                    vec![
+                        o.coin.clone(),
+                        o.asset.to_string(),
+                        o.side.to_string(),
+                        if o.reduce_only {
+                            format!("{} (RO)", o.order_type)
+                        } else {
+                            o.order_type.clone()
+                        },
+                        o.price.clone().unwrap_or_else(|| "-".to_string()),
+                        o.size.clone(),
+                        fmt_usd(o.notional),
+                        fmt_usd(o.estimated_margin),
+                    ]
+                })
+                .collect();
+            output::print_table_with_columns(&columns, rows);
+            println!();
+        }
+        let count = self.actions.len();
+        for (i, action) in self.actions.iter().enumerate() {
+            if i > 0 {
+                println!();
             }
+            let header = if count > 1 {
+                format!("Action {} of {count}", i + 1)
+            } else {
+                "Action".to_string()
+            };
+            println!("{}", output::style_header(header));
+            output::print_json_pretty(action)?;
+        }
+        Ok(())
+    }
+}
 
-            loop {
-                tokio::select! {
-                    msg = client.next_json() => {
-                        let msg = match msg {
-                            Ok(Some(m)) => m,
-                            Ok(None) => break,
-                            Err(e) => {
-                                if !output_opts.json {
-                                    watch::clear_screen();
-                                    println!("{}", output::style_loss(format!("Error: {e}")));
-                                    println!("{}", output::style_muted("Reconnecting..."));
-                                } else {
-                                    output::print_error(e.to_string());
-                                }
-                                break;
-                            }
-                        };
+/// `--dry-run` preview of a single action.
+async fn print_dry_run(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    action: &impl serde::Serialize,
+    orders: Vec<DryRunOrder>,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    DryRun::default()
+        .raw_action(action, orders)?
+        .print(cfg, api, output_opts)
+        .await
+}
 
-                        let Ok(channel) = ws::WsClient::channel(&msg) else {
-                            continue;
-                        };
-                        if channel != "orderUpdates" {
-                            continue;
-                        }
+/// `--dry-run` preview for an action built by the `hyperliquid` crate.
+async fn dry_run_action(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    action: &Action,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    DryRun::default()
+        .action(action)?
+        .print(cfg, api, output_opts)
+        .await
+}
 
-                        match fetch_open_orders(api, user).await {
-                            Ok(next) => {
-                                orders = next;
-                                last_updated = watch::format_timestamp();
-                                if output_opts.json {
-                                    output::print_json_line(&serde_json::json!({
-                                        "orders": orders,
-                                        "timestamp": chrono::Utc::now().to_rfc3339(),
-                                    }))?;
-                                } else {
-                                    render(&orders, &last_updated);
-                                }
-                            }
-                            Err(e) => {
-                                if !output_opts.json {
-                                    watch::clear_screen();
-                                    println!("{}", output::style_loss(format!("Error: {e}")));
-                                    println!("{}", output::style_muted("Reconnecting..."));
-                                } else {
-                                    output::print_error(e.to_string());
-                                }
-                            }
+/// Print an exchange response. `legs` labels each status in order (e.g. the
+/// entry and child orders of a bracket); statuses past the labels print bare.
+fn print_exchange_response(resp: &ExchangeResponse, legs: &[&str]) {
+    match resp {
+        ExchangeResponse::Ok(data) => {
+            if let Some(status_type) = &data.data {
+                match status_type {
+                    StatusType::Statuses(statuses) => {
+                        for (i, s) in statuses.iter().enumerate() {
+                            print_exchange_status(s, legs.get(i).copied());
                         }
                     }
-                    _ = &mut ctrl_c => break 'outer,
+                    StatusType::Status(status) => {
+                        print_exchange_status(status, legs.first().copied())
+                    }
+                    StatusType::Address(addr) => {
+                        output::print_success(format!("{addr:#x}"));
+                    }
                 }
+            } else {
+                output::print_success(format!("Ok ({})", data.type_));
             }
-
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-        }
-
-        if !output_opts.json {
-            watch::show_cursor();
         }
-        return Ok(());
+        ExchangeResponse::Err(err) => output::print_error(err),
     }
+}
 
-    let orders = fetch_open_orders(api, user).await?;
-    if output_opts.json {
-        output::print_json_pretty(&orders)?;
-        return Ok(());
+fn print_exchange_status(status: &Status, leg: Option<&str>) {
+    let prefix = leg.map(|l| format!("{l}: ")).unwrap_or_default();
+    match status {
+        Status::Resting(r) => output::print_success(format!("{prefix}Order placed: ID {}", r.oid)),
+        Status::Filled(f) => output::print_success(format!(
+            "{prefix}Order filled: {} @ {}",
+            f.total_sz, f.avg_px
+        )),
+        Status::Error(e) => output::print_error(format!("{prefix}Order error: {e}")),
+        Status::Success => output::print_success(format!("{prefix}Order status: success")),
+        Status::WaitingForFill => {
+            output::print_success(format!("{prefix}Order status: waitingForFill"))
+        }
+        Status::WaitingForTrigger => {
+            output::print_success(format!("{prefix}Order status: waitingForTrigger"))
+        }
+        Status::Running(t) => output::print_success(format!(
+            "{prefix}Order status: running (twapId: {})",
+            t.twap_id
+        )),
     }
+}
 
-    if orders.is_empty() {
-        println!("{}", output::style_muted("No open orders"));
-        return Ok(());
+async fn run_account(
+    cmd: AccountCommand,
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    match cmd {
+        AccountCommand::Add => account_add(cfg, api, output_opts).await,
+        AccountCommand::Ls => account_ls(output_opts),
+        AccountCommand::SetDefault(args) => account_set_default(args, output_opts).await,
+        AccountCommand::Remove(args) => account_remove(args, output_opts).await,
+        AccountCommand::Positions(args) => account_positions(cfg, api, args, output_opts).await,
+        AccountCommand::Orders(args) => account_orders(cfg, api, args, output_opts).await,
+        AccountCommand::Fills(args) => account_fills(cfg, api, args, output_opts).await,
+        AccountCommand::Funding(args) => account_funding(cfg, api, args, output_opts).await,
+        AccountCommand::Balances(args) => account_balances(cfg, api, args, output_opts).await,
+        AccountCommand::Portfolio(args) => account_portfolio(cfg, api, args, output_opts).await,
     }
-
-    let rows: Vec<Vec<String>> = orders
-        .iter()
-        .map(|o| {
-            let side = if o.side == "B" {
-                output::style_profit("Buy")
-            } else {
-                output::style_loss("Sell")
-            };
-            vec![
-                o.oid.to_string(),
-                o.coin.clone(),
-                side,
-                o.sz.clone(),
-                o.limit_px.clone(),
-                o.timestamp.clone(),
-            ]
-        })
-        .collect();
-    output::print_table_with_columns(&table_columns, rows);
-    Ok(())
 }
 
-#[derive(serde::Serialize)]
-struct SpotBalanceRow {
-    token: String,
-    total: String,
-    hold: String,
-    available: String,
-}
+async fn account_add(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    println!("\n=== Add New Account ===\n");
 
-async fn fetch_balances(api: &HlApi, user: Address) -> Result<(Vec<SpotBalanceRow>, String)> {
-    let (clearinghouse, spot_state) = tokio::try_join!(
-        api.clearinghouse_state(user),
-        api.spot_clearinghouse_state(user)
+    let setup_method = prompt::select(
+        "How would you like to add your account?",
+        vec![
+            prompt::SelectOption {
+                value: "existing",
+                label: "Use existing wallet".to_string(),
+            },
+            prompt::SelectOption {
+                value: "new",
+                label: "Create new wallet".to_string(),
+            },
+            prompt::SelectOption {
+                value: "readonly",
+                label: "Add read-only account".to_string(),
+            },
+        ],
     )?;
 
-    let spot_balances: Vec<SpotBalanceRow> = spot_state
-        .balances
-        .into_iter()
-        .filter(|b| b.total.parse::<f64>().unwrap_or(0.0) != 0.0)
-        .map(|b| {
-            let total = b.total.parse::<f64>().unwrap_or(0.0);
-            let hold = b.hold.parse::<f64>().unwrap_or(0.0);
-            let available = total - hold;
-            SpotBalanceRow {
-                token: b.coin,
-                total: b.total,
-                hold: b.hold,
-                available: available.to_string(),
-            }
-        })
-        .collect();
-
-    let perp_balance = clearinghouse.margin_summary.account_value.clone();
-    Ok((spot_balances, perp_balance))
+    match setup_method {
+        "new" => {
+            println!("\nCreating new wallets with encrypted keystores is coming soon!");
+            println!("For now, please use an existing wallet or add a read-only account.\n");
+            Ok(())
+        }
+        "existing" => account_add_existing_wallet(cfg, api, output_opts).await,
+        "readonly" => account_add_readonly(output_opts).await,
+        _ => Err(anyhow!("Unknown setup method")),
+    }
 }
 
-async fn account_balances(
+async fn account_add_existing_wallet(
     cfg: &config::LoadedConfig,
     api: &HlApi,
-    args: AccountUserWatchArgs,
     output_opts: OutputOptions,
 ) -> Result<()> {
-    let user = resolve_user_address(cfg, &args.user)?;
-    let table_columns = [
-        output::TableColumn::left("Token"),
-        output::TableColumn::right("Total"),
-        output::TableColumn::right("Hold"),
-        output::TableColumn::right("Available"),
-    ];
+    const REFERRAL_LINK: &str = "https://app.hyperliquid.xyz/join/CHRISLING";
 
-    if args.watch {
-        if !output_opts.json {
-            watch::hide_cursor();
+    let api_url = if cfg.testnet {
+        "https://app.hyperliquid-testnet.xyz/API"
+    } else {
+        "https://app.hyperliquid.xyz/API"
+    };
+    println!("\nVisit {api_url} and click \"Generate\" to create one.\n");
+
+    let api_private_key = loop {
+        let input = prompt::prompt("Enter your API wallet private key: ")?;
+        match validation::validate_private_key(&input) {
+            Ok(pk) => break pk,
+            Err(e) => {
+                output::print_error(e.to_string());
+            }
         }
-        let ctrl_c = tokio::signal::ctrl_c();
-        tokio::pin!(ctrl_c);
+    };
 
-        let mut last_updated = watch::format_timestamp();
-        let mut spot_balances: Vec<SpotBalanceRow> = Vec::new();
-        let mut perp_balance = "0".to_string();
+    // Validate API key by checking userRole(address) is agent
+    println!("\nValidating API key...");
+    let wallet = LocalWallet::from_str(&api_private_key).context("Invalid private key")?;
+    let api_wallet_address = wallet.address();
 
-        let render = |spot_balances: &[SpotBalanceRow], perp_balance: &str, last_updated: &str| {
-            watch::clear_screen();
-            println!("{}", output::format_watch_header("Balances", last_updated));
-            println!();
-            println!(
-                "{}{} USD",
-                output::style_bold("Perpetuals Balance: "),
-                perp_balance
-            );
-            println!();
-            println!("{}", output::style_header("Spot Balances:"));
-            if spot_balances.is_empty() {
-                println!("{}", output::style_muted("No spot balances"));
-            } else {
-                let rows: Vec<Vec<String>> = spot_balances
-                    .iter()
-                    .map(|b| {
-                        vec![
-                            b.token.clone(),
-                            b.total.clone(),
-                            b.hold.clone(),
-                            b.available.clone(),
-                        ]
-                    })
-                    .collect();
-                output::print_table_with_columns(&table_columns, rows);
-            }
-            println!();
-            println!("{}", output::style_muted("Press Ctrl+C to exit"));
-        };
-
-        if !output_opts.json {
-            render(&spot_balances, &perp_balance, &last_updated);
+    let role = api.user_role(api_wallet_address).await?;
+    let master_address = match role {
+        UserRoleResponse::Agent { data } => data.user,
+        UserRoleResponse::Missing => {
+            return Err(anyhow!(
+                "This key is not registered as an API wallet on Hyperliquid"
+            ));
+        }
+        other => {
+            return Err(anyhow!(
+                "Invalid role: {:?}. Expected an agent wallet.",
+                other
+            ));
         }
+    };
 
-        'outer: loop {
-            let mut client = match ws::WsClient::connect(api.testnet).await {
-                Ok(c) => c,
-                Err(e) => {
-                    if !output_opts.json {
-                        watch::clear_screen();
-                        println!("{}", output::style_loss(format!("Error: {e}")));
-                        println!("{}", output::style_muted("Reconnecting..."));
-                    } else {
-                        output::print_error(e.to_string());
-                    }
-                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                    continue;
-                }
-            };
+    println!(
+        "Valid API wallet for {}",
+        output::format_short_address(&master_address)
+    );
 
-            if let Err(e) = client
-                .subscribe(ws::sub_all_dexs_clearinghouse_state(user))
-                .await
-            {
-                if !output_opts.json {
-                    watch::clear_screen();
-                    println!("{}", output::style_loss(format!("Error: {e}")));
-                    println!("{}", output::style_muted("Reconnecting..."));
-                } else {
-                    output::print_error(e.to_string());
-                }
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                continue;
-            }
+    println!(
+        "\n{}{}{}",
+        output::style_warning("🎁 Get "),
+        output::style_profit_bold("4% off"),
+        output::style_warning(" trading fees with our referral link:")
+    );
+    println!("   {}", output::style_link(REFERRAL_LINK));
+    println!();
 
-            loop {
-                tokio::select! {
-                    msg = client.next_json() => {
-                        let msg = match msg {
-                            Ok(Some(m)) => m,
-                            Ok(None) => break,
-                            Err(e) => {
-                                if !output_opts.json {
-                                    watch::clear_screen();
-                                    println!("{}", output::style_loss(format!("Error: {e}")));
-                                    println!("{}", output::style_muted("Reconnecting..."));
-                                } else {
-                                    output::print_error(e.to_string());
-                                }
-                                break;
-                            }
-                        };
+    let open_referral =
+        prompt::press_enter_or_esc("Press Enter to open in browser, or Esc to skip")?;
+    if open_referral {
+        let _ = if cfg!(target_os = "macos") {
+            std::process::Command::new("open")
+                .arg(REFERRAL_LINK)
+                .status()
+        } else if cfg!(target_os = "windows") {
+            std::process::Command::new("cmd")
+                .args(["/C", "start", REFERRAL_LINK])
+                .status()
+        } else {
+            std::process::Command::new("xdg-open")
+                .arg(REFERRAL_LINK)
+                .status()
+        };
+    }
 
-                        let Ok(channel) = ws::WsClient::channel(&msg) else {
-                            continue;
-                        };
-                        if channel != "allDexsClearinghouseState" {
-                            continue;
-                        }
+    let alias = prompt_for_alias()?;
 
-                        let Ok(data) = ws::WsClient::data(&msg) else {
-                            continue;
-                        };
-                        let Ok(event) = serde_json::from_value::<AllDexsClearinghouseStateEvent>(data.clone()) else {
-                            continue;
-                        };
+    let mut set_as_default = false;
+    let existing_count = db::get_account_count()?;
+    if existing_count > 0 {
+        set_as_default = prompt::confirm("\nSet this as your default account?", true)?;
+    }
 
-                        if let Some((_, main)) = event.clearinghouse_states.first() {
-                            perp_balance = main.margin_summary.account_value.clone();
-                        } else {
-                            perp_balance = "0".to_string();
-                        }
+    let new_account = db::create_account(CreateAccountInput {
+        alias: alias.clone(),
+        user_address: master_address.clone(),
+        account_type: "api_wallet".to_string(),
+        source: Some("cli_import".to_string()),
+        api_wallet_private_key: Some(api_private_key.clone()),
+        api_wallet_public_key: Some(format!("{api_wallet_address:#x}")),
+        set_as_default,
+    })?;
 
-                        if let Ok(spot_state) = api.spot_clearinghouse_state(user).await {
-                            spot_balances = spot_state
-                                .balances
-                                .into_iter()
-                                .filter(|b| b.total.parse::<f64>().unwrap_or(0.0) != 0.0)
-                                .map(|b| {
-                                    let total = b.total.parse::<f64>().unwrap_or(0.0);
-                                    let hold = b.hold.parse::<f64>().unwrap_or(0.0);
-                                    let available = total - hold;
-                                    SpotBalanceRow {
-                                        token: b.coin,
-                                        total: b.total,
-                                        hold: b.hold,
-                                        available: available.to_string(),
-                                    }
-                                })
-                                .collect();
-                        }
+    if output_opts.json {
+        let mut redacted = new_account.clone();
+        redacted.api_wallet_private_key = Some("[REDACTED]".to_string());
+        output::print_json_pretty(&redacted)?;
+        return Ok(());
+    }
 
-                        last_updated = watch::format_timestamp();
+    println!();
+    output::print_success(format!("Account \"{alias}\" added successfully!"));
+    println!("\nAccount details:");
+    println!("  Alias: {}", new_account.alias);
+    println!("  Address: {}", new_account.user_address);
+    println!("  Type: {}", new_account.account_type);
+    if let Some(api_wallet) = &new_account.api_wallet_public_key {
+        println!("  API Wallet: {}", api_wallet);
+    }
+    println!(
+        "  Default: {}",
+        if new_account.is_default { "Yes" } else { "No" }
+    );
+    println!();
 
-                        if output_opts.json {
-                            output::print_json_line(&serde_json::json!({
-                                "spotBalances": spot_balances,
-                                "perpBalance": perp_balance,
-                                "timestamp": chrono::Utc::now().to_rfc3339(),
-                            }))?;
-                        } else {
-                            render(&spot_balances, &perp_balance, &last_updated);
-                        }
-                    }
-                    _ = &mut ctrl_c => break 'outer,
-                }
-            }
+    Ok(())
+}
 
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-        }
+async fn account_add_readonly(output_opts: OutputOptions) -> Result<()> {
+    println!();
+    let user_address_input = prompt::prompt("Enter the wallet address to watch: ")?;
+    let user_address = validation::validate_address(&user_address_input)?;
+    let alias = prompt_for_alias()?;
 
-        if !output_opts.json {
-            watch::show_cursor();
-        }
-        return Ok(());
+    let mut set_as_default = false;
+    let existing_count = db::get_account_count()?;
+    if existing_count > 0 {
+        set_as_default = prompt::confirm("\nSet this as your default account?", true)?;
     }
 
-    let (spot_balances, perp_balance) = fetch_balances(api, user).await?;
+    let new_account = db::create_account(CreateAccountInput {
+        alias: alias.clone(),
+        user_address: format!("{user_address:#x}"),
+        account_type: "readonly".to_string(),
+        source: Some("cli_import".to_string()),
+        api_wallet_private_key: None,
+        api_wallet_public_key: None,
+        set_as_default,
+    })?;
+
     if output_opts.json {
-        output::print_json_pretty(&serde_json::json!({
-            "spotBalances": spot_balances,
-            "perpBalance": perp_balance,
-        }))?;
+        output::print_json_pretty(&new_account)?;
         return Ok(());
     }
 
+    println!();
+    output::print_success(format!("Account \"{alias}\" added successfully!"));
+    println!("\nAccount details:");
+    println!("  Alias: {}", new_account.alias);
+    println!("  Address: {}", new_account.user_address);
+    println!("  Type: {}", new_account.account_type);
     println!(
-        "{}{} USD",
-        output::style_bold("Perpetuals Balance: "),
-        perp_balance
+        "  Default: {}",
+        if new_account.is_default { "Yes" } else { "No" }
     );
     println!();
-    println!("{}", output::style_header("Spot Balances:"));
-    if spot_balances.is_empty() {
-        println!("{}", output::style_muted("No spot balances"));
-        return Ok(());
-    }
-    let rows: Vec<Vec<String>> = spot_balances
-        .iter()
-        .map(|b| {
-            vec![
-                b.token.clone(),
-                b.total.clone(),
-                b.hold.clone(),
-                b.available.clone(),
-            ]
-        })
-        .collect();
-    output::print_table_with_columns(&table_columns, rows);
     Ok(())
 }
 
-async fn account_portfolio(
-    cfg: &config::LoadedConfig,
-    api: &HlApi,
+fn prompt_for_alias() -> Result<String> {
+    loop {
+        let alias = prompt::prompt("Enter an alias for this account (e.g., 'main', 'trading'): ")?;
+        if alias.trim().is_empty() {
+            println!("Alias cannot be empty.");
+            continue;
+        }
+        if db::is_alias_taken(&alias)? {
+            println!("Alias \"{alias}\" is already taken. Please choose another.");
+            continue;
+        }
+        return Ok(alias);
+    }
+}
+
+fn account_ls(output_opts: OutputOptions) -> Result<()> {
+    let accounts = db::get_all_accounts()?;
+
+    if output_opts.json {
+        let json_accounts: Vec<_> = accounts
+            .iter()
+            .map(|acc| {
+                let mut a = acc.clone();
+                a.api_wallet_private_key = None;
+                a
+            })
+            .collect();
+        output::print_json_pretty(&json_accounts)?;
+        return Ok(());
+    }
+
+    if accounts.is_empty() {
+        println!("{}", output::style_muted("No accounts found."));
+        println!(
+            "{}",
+            output::style_muted("Run 'rhl account add' to add your first account.")
+        );
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = accounts
+        .iter()
+        .map(|acc| {
+            vec![
+                if acc.is_default {
+                    "*".to_string()
+                } else {
+                    "".to_string()
+                },
+                acc.alias.clone(),
+                output::format_short_address(&acc.user_address),
+                acc.account_type.clone(),
+                acc.api_wallet_public_key
+                    .as_ref()
+                    .map(|s| output::format_short_address(s))
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    let columns = [
+        output::TableColumn::left_with_width("", 2),
+        output::TableColumn::left("Alias"),
+        output::TableColumn::left("Address"),
+        output::TableColumn::left("Type"),
+        output::TableColumn::left("API Wallet"),
+    ];
+    output::print_table_with_columns(&columns, rows);
+    println!();
+    println!("{}", output::style_muted("* = default account"));
+    Ok(())
+}
+
+async fn account_set_default(
+    args: AccountSetDefaultArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let alias = args.alias;
+
+    let existing = db::get_account_by_alias(&alias)?;
+    let Some(existing) = existing else {
+        return Err(anyhow!(
+            "Account with alias \"{alias}\" not found. Run 'rhl account ls' to see available accounts."
+        ));
+    };
+
+    if existing.is_default {
+        if output_opts.json {
+            output::print_json_pretty(&serde_json::json!({
+                "message": format!("Account \"{alias}\" is already the default")
+            }))?;
+        } else {
+            println!("Account \"{alias}\" is already the default.");
+        }
+        return Ok(());
+    }
+
+    let updated = db::set_default_account(&alias)?;
+
+    if output_opts.json {
+        output::print_json_pretty(&serde_json::json!({
+            "id": updated.id,
+            "alias": updated.alias,
+            "userAddress": updated.user_address,
+            "type": updated.account_type,
+            "isDefault": updated.is_default,
+        }))?;
+        return Ok(());
+    }
+
+    output::print_success(format!("Account \"{alias}\" is now the default."));
+    Ok(())
+}
+
+async fn account_remove(args: AccountRemoveArgs, output_opts: OutputOptions) -> Result<()> {
+    let existing = db::get_account_by_alias(&args.alias)?;
+    let Some(existing) = existing else {
+        return Err(anyhow!(
+            "Account with alias \"{}\" not found. Run 'rhl account ls' to see available accounts.",
+            args.alias
+        ));
+    };
+
+    if !args.force {
+        let confirmed = prompt::confirm(
+            &format!(
+                "Are you sure you want to remove account \"{}\" ({})?",
+                args.alias, existing.user_address
+            ),
+            false,
+        )?;
+        if !confirmed {
+            println!("Cancelled.");
+            return Ok(());
+        }
+    }
+
+    let deleted = db::delete_account(&args.alias)?;
+    if !deleted {
+        return Err(anyhow!("Failed to remove account \"{}\"", args.alias));
+    }
+
+    if output_opts.json {
+        output::print_json_pretty(&serde_json::json!({
+            "message": format!("Account \"{}\" removed", args.alias),
+            "deleted": true
+        }))?;
+    } else {
+        output::print_success(format!("Account \"{}\" removed.", args.alias));
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct PositionRow {
+    coin: String,
+    size: String,
+    #[serde(rename = "entryPx")]
+    entry_px: Option<String>,
+    #[serde(rename = "positionValue")]
+    position_value: String,
+    #[serde(rename = "unrealizedPnl")]
+    unrealized_pnl: String,
+    leverage: String,
+    #[serde(rename = "liquidationPx")]
+    liquidation_px: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AllDexsClearinghouseStateEvent {
+    clearinghouse_states: Vec<(String, hyperliquid_cli::hl_api::ClearinghouseState)>,
+}
+
+fn format_pnl_cell(value: &str) -> String {
+    let Ok(num_value) = value.parse::<f64>() else {
+        return output::style_muted("-");
+    };
+    if !num_value.is_finite() {
+        return output::style_muted("-");
+    }
+
+    let sign = if num_value > 0.0 { "+" } else { "" };
+    let formatted = format!("{sign}{num_value:.2}");
+    if num_value > 0.0 {
+        output::style_profit(formatted)
+    } else if num_value < 0.0 {
+        output::style_loss(formatted)
+    } else {
+        formatted
+    }
+}
+
+async fn account_positions(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
     args: AccountUserWatchArgs,
     output_opts: OutputOptions,
 ) -> Result<()> {
     let user = resolve_user_address(cfg, &args.user)?;
-    let position_table_columns = [
+    let table_columns = [
         output::TableColumn::left("Coin"),
         output::TableColumn::right("Size"),
         output::TableColumn::right("Entry"),
         output::TableColumn::right("Value"),
         output::TableColumn::right("PnL"),
         output::TableColumn::right("Leverage"),
-    ];
-    let balance_table_columns = [
-        output::TableColumn::left("Token"),
-        output::TableColumn::right("Total"),
-        output::TableColumn::right("Hold"),
+        output::TableColumn::right("Liq. Price"),
     ];
 
     if args.watch {
         if !output_opts.json {
             watch::hide_cursor();
         }
+
         let ctrl_c = tokio::signal::ctrl_c();
         tokio::pin!(ctrl_c);
 
         let mut last_updated = watch::format_timestamp();
-        let mut data = PortfolioData {
-            positions: Vec::new(),
-            spot_balances: Vec::new(),
-            account_value: "0".to_string(),
-            total_margin_used: "0".to_string(),
-        };
+        let mut positions: Vec<PositionRow> = Vec::new();
+        let mut account_value = "0".to_string();
+        let mut total_margin_used = "0".to_string();
 
-        let render = |data: &PortfolioData, last_updated: &str| {
+        let render = |positions: &[PositionRow],
+                      account_value: &str,
+                      total_margin_used: &str,
+                      last_updated: &str| {
             watch::clear_screen();
-            println!("{}", output::format_watch_header("Portfolio", last_updated));
-            println!();
-            println!("{}", output::style_header("Account Summary"));
-            println!("Account Value: {}", output::style_bold(&data.account_value));
-            println!(
-                "Total Margin Used: {}",
-                output::style_bold(&data.total_margin_used)
-            );
+            println!("{}", output::format_watch_header("Positions", last_updated));
             println!();
-            println!("{}", output::style_header("Perpetual Positions:"));
-            if data.positions.is_empty() {
+
+            if positions.is_empty() {
                 println!("{}", output::style_muted("No open positions"));
             } else {
-                let rows: Vec<Vec<String>> = data
-                    .positions
+                let rows: Vec<Vec<String>> = positions
                     .iter()
                     .map(|p| {
                         vec![
@@ -1664,29 +1976,30 @@ async fn account_portfolio(
                             p.position_value.clone(),
                             format_pnl_cell(&p.unrealized_pnl),
                             p.leverage.clone(),
+                            p.liquidation_px.clone(),
                         ]
                     })
                     .collect();
-                output::print_table_with_columns(&position_table_columns, rows);
+                output::print_table_with_columns(&table_columns, rows);
             }
+
             println!();
-            println!("{}", output::style_header("Spot Balances:"));
-            if data.spot_balances.is_empty() {
-                println!("{}", output::style_muted("No spot balances"));
-            } else {
-                let rows: Vec<Vec<String>> = data
-                    .spot_balances
-                    .iter()
-                    .map(|b| vec![b.token.clone(), b.total.clone(), b.hold.clone()])
-                    .collect();
-                output::print_table_with_columns(&balance_table_columns, rows);
-            }
+            println!("Account Value: {}", output::style_bold(account_value));
+            println!(
+                "Total Margin Used: {}",
+                output::style_bold(total_margin_used)
+            );
             println!();
             println!("{}", output::style_muted("Press Ctrl+C to exit"));
         };
 
         if !output_opts.json {
-            render(&data, &last_updated);
+            render(
+                &positions,
+                &account_value,
+                &total_margin_used,
+                &last_updated,
+            );
         }
 
         'outer: loop {
@@ -1745,62 +2058,48 @@ async fn account_portfolio(
                             continue;
                         }
 
-                        let Ok(data_msg) = ws::WsClient::data(&msg) else {
+                        let Ok(data) = ws::WsClient::data(&msg) else {
                             continue;
                         };
-                        let Ok(event) = serde_json::from_value::<AllDexsClearinghouseStateEvent>(data_msg.clone()) else {
+                        let Ok(event) = serde_json::from_value::<AllDexsClearinghouseStateEvent>(data.clone()) else {
                             continue;
                         };
 
-                        if let Some((_, main)) = event.clearinghouse_states.first() {
-                            data.account_value = main.margin_summary.account_value.clone();
-                            data.total_margin_used = main.margin_summary.total_margin_used.clone();
+                        let clearinghouse_states = event.clearinghouse_states;
+                        if let Some((_, main)) = clearinghouse_states.first() {
+                            account_value = main.margin_summary.account_value.clone();
+                            total_margin_used = main.margin_summary.total_margin_used.clone();
                         } else {
-                            data.account_value = "0".to_string();
-                            data.total_margin_used = "0".to_string();
+                            account_value = "0".to_string();
+                            total_margin_used = "0".to_string();
                         }
 
-                        data.positions = event
-                            .clearinghouse_states
+                        positions = clearinghouse_states
                             .into_iter()
                             .flat_map(|(_, st)| st.asset_positions.into_iter().map(|ap| ap.position))
                             .filter(|p| p.szi.parse::<f64>().unwrap_or(0.0) != 0.0)
-                            .map(|p| PortfolioPositionRow {
+                            .map(|p| PositionRow {
                                 coin: p.coin,
                                 size: p.szi,
                                 entry_px: p.entry_px,
                                 position_value: p.position_value,
                                 unrealized_pnl: p.unrealized_pnl,
                                 leverage: format!("{}x {}", p.leverage.value, p.leverage.leverage_type),
+                                liquidation_px: p.liquidation_px.unwrap_or_else(|| "-".to_string()),
                             })
                             .collect();
 
-                        if let Ok(spot_state) = api.spot_clearinghouse_state(user).await {
-                            data.spot_balances = spot_state
-                                .balances
-                                .into_iter()
-                                .filter(|b| b.total.parse::<f64>().unwrap_or(0.0) != 0.0)
-                                .map(|b| PortfolioSpotBalanceRow {
-                                    token: b.coin,
-                                    total: b.total,
-                                    hold: b.hold,
-                                })
-                                .collect();
-                        }
-
                         last_updated = watch::format_timestamp();
 
                         if output_opts.json {
-                            let mut v = serde_json::to_value(&data)?;
-                            if let Some(obj) = v.as_object_mut() {
-                                obj.insert(
-                                    "timestamp".to_string(),
-                                    serde_json::Value::String(chrono::Utc::now().to_rfc3339()),
-                                );
-                            }
-                            output::print_json_line(&v)?;
+                            output::print_json_line(&serde_json::json!({
+                                "positions": positions,
+                                "accountValue": account_value,
+                                "totalMarginUsed": total_margin_used,
+                                "timestamp": chrono::Utc::now().to_rfc3339(),
+                            }))?;
                         } else {
-                            render(&data, &last_updated);
+                            render(&positions, &account_value, &total_margin_used, &last_updated);
                         }
                     }
                     _ = &mut ctrl_c => break 'outer,
@@ -1816,25 +2115,36 @@ async fn account_portfolio(
         return Ok(());
     }
 
-    let data = fetch_portfolio(api, user).await?;
+    let state = api.clearinghouse_state(user).await?;
+    let positions: Vec<PositionRow> = state
+        .asset_positions
+        .iter()
+        .map(|p| &p.position)
+        .filter(|p| p.szi.parse::<f64>().unwrap_or(0.0) != 0.0)
+        .map(|p| PositionRow {
+            coin: p.coin.clone(),
+            size: p.szi.clone(),
+            entry_px: p.entry_px.clone(),
+            position_value: p.position_value.clone(),
+            unrealized_pnl: p.unrealized_pnl.clone(),
+            leverage: format!("{}x {}", p.leverage.value, p.leverage.leverage_type),
+            liquidation_px: p.liquidation_px.clone().unwrap_or_else(|| "-".to_string()),
+        })
+        .collect();
+
     if output_opts.json {
-        output::print_json_pretty(&data)?;
+        output::print_json_pretty(&serde_json::json!({
+            "positions": positions,
+            "marginSummary": state.margin_summary,
+            "crossMarginSummary": state.cross_margin_summary,
+        }))?;
         return Ok(());
     }
 
-    println!("{}", output::style_header("Account Summary"));
-    println!("Account Value: {}", output::style_bold(&data.account_value));
-    println!(
-        "Total Margin Used: {}",
-        output::style_bold(&data.total_margin_used)
-    );
-    println!();
-    println!("{}", output::style_header("Perpetual Positions:"));
-    if data.positions.is_empty() {
+    if positions.is_empty() {
         println!("{}", output::style_muted("No open positions"));
     } else {
-        let rows: Vec<Vec<String>> = data
-            .positions
+        let rows: Vec<Vec<String>> = positions
             .iter()
             .map(|p| {
                 vec![
@@ -1844,319 +2154,551 @@ async fn account_portfolio(
                     p.position_value.clone(),
                     format_pnl_cell(&p.unrealized_pnl),
                     p.leverage.clone(),
+                    p.liquidation_px.clone(),
                 ]
             })
             .collect();
-        output::print_table_with_columns(&position_table_columns, rows);
+        output::print_table_with_columns(&table_columns, rows);
     }
-
     println!();
-    println!("{}", output::style_header("Spot Balances:"));
-    if data.spot_balances.is_empty() {
-        println!("{}", output::style_muted("No spot balances"));
-    } else {
-        let rows: Vec<Vec<String>> = data
-            .spot_balances
-            .iter()
-            .map(|b| vec![b.token.clone(), b.total.clone(), b.hold.clone()])
-            .collect();
-        output::print_table_with_columns(&balance_table_columns, rows);
-    }
+    println!(
+        "Account Value: {}",
+        output::style_bold(&state.margin_summary.account_value)
+    );
+    println!(
+        "Total Margin Used: {}",
+        output::style_bold(&state.margin_summary.total_margin_used)
+    );
     Ok(())
 }
 
 #[derive(serde::Serialize)]
-struct PortfolioPositionRow {
+struct OrderRow {
+    oid: u64,
     coin: String,
-    size: String,
-    #[serde(rename = "entryPx")]
-    entry_px: Option<String>,
-    #[serde(rename = "positionValue")]
-    position_value: String,
-    #[serde(rename = "unrealizedPnl")]
-    unrealized_pnl: String,
-    leverage: String,
-}
-
-#[derive(serde::Serialize)]
-struct PortfolioSpotBalanceRow {
-    token: String,
-    total: String,
-    hold: String,
+    side: String,
+    sz: String,
+    #[serde(rename = "limitPx")]
+    limit_px: String,
+    #[serde(rename = "orderType")]
+    order_type: String,
+    #[serde(rename = "reduceOnly")]
+    reduce_only: bool,
+    #[serde(rename = "triggerCondition")]
+    trigger_condition: Option<String>,
+    timestamp: String,
 }
 
-#[derive(serde::Serialize)]
-struct PortfolioData {
-    positions: Vec<PortfolioPositionRow>,
-    #[serde(rename = "spotBalances")]
-    spot_balances: Vec<PortfolioSpotBalanceRow>,
-    #[serde(rename = "accountValue")]
-    account_value: String,
-    #[serde(rename = "totalMarginUsed")]
-    total_margin_used: String,
+fn format_order_row(o: &OrderRow) -> Vec<String> {
+    let side = if o.side == "B" {
+        output::style_profit("Buy")
+    } else {
+        output::style_loss("Sell")
+    };
+    let order_type = if o.reduce_only {
+        format!("{} (RO)", o.order_type)
+    } else {
+        o.order_type.clone()
+    };
+    vec![
+        o.oid.to_string(),
+        o.coin.clone(),
+        side,
+        o.sz.clone(),
+        o.limit_px.clone(),
+        order_type,
+        o.trigger_condition
+            .clone()
+            .unwrap_or_else(|| output::style_muted("-")),
+        o.timestamp.clone(),
+    ]
 }
 
-async fn fetch_portfolio(api: &HlApi, user: Address) -> Result<PortfolioData> {
-    let (clearinghouse, spot_state) = tokio::try_join!(
-        api.clearinghouse_state(user),
-        api.spot_clearinghouse_state(user)
-    )?;
+fn format_order_timestamp(ms: u64) -> String {
+    let Some(dt_utc) = chrono::DateTime::<chrono::Utc>::from_timestamp_millis(ms as i64) else {
+        return ms.to_string();
+    };
 
-    let positions: Vec<PortfolioPositionRow> = clearinghouse
-        .asset_positions
-        .into_iter()
-        .map(|p| p.position)
-        .filter(|p| p.szi.parse::<f64>().unwrap_or(0.0) != 0.0)
-        .map(|p| PortfolioPositionRow {
-            coin: p.coin,
-            size: p.szi,
-            entry_px: p.entry_px,
-            position_value: p.position_value,
-            unrealized_pnl: p.unrealized_pnl,
-            leverage: format!("{}x {}", p.leverage.value, p.leverage.leverage_type),
-        })
-        .collect();
+    let dt = dt_utc.with_timezone(&chrono::Local);
 
-    let spot_balances: Vec<PortfolioSpotBalanceRow> = spot_state
-        .balances
-        .into_iter()
-        .filter(|b| b.total.parse::<f64>().unwrap_or(0.0) != 0.0)
-        .map(|b| PortfolioSpotBalanceRow {
-            token: b.coin,
-            total: b.total,
-            hold: b.hold,
+    let locale_raw = std::env::var("LC_ALL")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .or_else(|| {
+            std::env::var("LC_TIME")
+                .ok()
+                .filter(|s| !s.trim().is_empty())
         })
-        .collect();
+        .or_else(|| std::env::var("LANG").ok().filter(|s| !s.trim().is_empty()));
 
-    Ok(PortfolioData {
-        positions,
-        spot_balances,
-        account_value: clearinghouse.margin_summary.account_value,
-        total_margin_used: clearinghouse.margin_summary.total_margin_used,
-    })
-}
+    let locale = locale_raw
+        .as_deref()
+        .and_then(|s| s.split(['.', '@']).next())
+        .map(|s| s.replace('_', "-"));
 
-async fn run_markets(cmd: MarketsCommand, api: &HlApi, output_opts: OutputOptions) -> Result<()> {
-    match cmd {
-        MarketsCommand::Ls(args) => markets_ls(api, args, output_opts).await,
-        MarketsCommand::Prices(args) => markets_prices(api, args, output_opts).await,
+    match locale.as_deref() {
+        Some(tag) if tag.eq_ignore_ascii_case("en-US") => {
+            dt.format("%m/%d/%Y, %-I:%M:%S %p").to_string()
+        }
+        Some(tag) if tag.eq_ignore_ascii_case("en-AU") => dt
+            .format("%d/%m/%Y, %-I:%M:%S %p")
+            .to_string()
+            .replace("AM", "am")
+            .replace("PM", "pm"),
+        Some(tag) if tag.eq_ignore_ascii_case("en-GB") => {
+            dt.format("%d/%m/%Y, %H:%M:%S").to_string()
+        }
+        _ => dt.format("%Y-%m-%d %H:%M:%S").to_string(),
     }
 }
 
-#[derive(serde::Serialize)]
-struct MarketRow {
-    coin: String,
-    #[serde(rename = "pairName")]
-    pair_name: String,
-    price: String,
-    #[serde(rename = "priceChange")]
-    price_change: Option<f64>,
-    #[serde(rename = "volumeUsd")]
-    volume_usd: String,
-    funding: Option<String>,
-    #[serde(rename = "openInterest")]
-    open_interest: Option<String>,
+async fn fetch_open_orders(api: &HlApi, user: Address) -> Result<Vec<OrderRow>> {
+    let orders = api.frontend_open_orders(user).await?;
+    let formatted: Vec<OrderRow> = orders
+        .into_iter()
+        .map(|o| OrderRow {
+            oid: o.oid,
+            coin: o.coin,
+            side: o.side,
+            sz: o.sz,
+            limit_px: o.limit_px,
+            order_type: if o.order_type.is_empty() {
+                "Limit".to_string()
+            } else {
+                o.order_type
+            },
+            reduce_only: o.reduce_only,
+            trigger_condition: o.is_trigger.then_some(o.trigger_condition),
+            timestamp: format_order_timestamp(o.timestamp),
+        })
+        .collect();
+    Ok(formatted)
 }
 
-fn calculate_price_change(current: &str, prev: &str) -> Option<f64> {
-    let c = current.parse::<f64>().ok()?;
-    let p = prev.parse::<f64>().ok()?;
-    if p == 0.0 {
-        return None;
-    }
-    Some(((c - p) / p) * 100.0)
-}
-
-fn format_volume(volume: Option<&str>) -> String {
-    let Some(volume) = volume else {
-        return "N/A".to_string();
-    };
-    let volume = volume.trim();
-    if volume.is_empty() {
-        return "N/A".to_string();
-    }
-    let num = match volume.parse::<f64>() {
-        Ok(v) if v.is_finite() => v,
-        _ => return "N/A".to_string(),
-    };
-
-    // Match `toLocaleString(undefined, { maximumFractionDigits: 2 })` behavior.
-    let mut s = format!("{num:.2}");
-    if let Some(dot) = s.find('.') {
-        while s.ends_with('0') {
-            s.pop();
-        }
-        if s.len() == dot + 1 {
-            s.pop();
-        }
-    }
-
-    let (sign, rest) = s
-        .strip_prefix('-')
-        .map(|r| ("-", r))
-        .unwrap_or(("", s.as_str()));
-    let (int_part, frac_part) = rest.split_once('.').unwrap_or((rest, ""));
-
-    let mut grouped = String::with_capacity(rest.len() + rest.len() / 3);
-    for (idx, ch) in int_part.chars().rev().enumerate() {
-        if idx > 0 && idx % 3 == 0 {
-            grouped.push(',');
-        }
-        grouped.push(ch);
-    }
-    let int_grouped: String = grouped.chars().rev().collect();
-
-    if frac_part.is_empty() {
-        format!("{sign}{int_grouped}")
-    } else {
-        format!("{sign}{int_grouped}.{frac_part}")
-    }
-}
-
-async fn markets_ls(_api: &HlApi, args: MarketsLsArgs, output_opts: OutputOptions) -> Result<()> {
-    let is_spot_only = args.spot_only;
-    let is_perp_only = args.perp_only;
+async fn account_orders(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: AccountUserWatchArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let user = resolve_user_address(cfg, &args.user)?;
     let table_columns = [
+        output::TableColumn::right("OID"),
         output::TableColumn::left("Coin"),
-        output::TableColumn::left("Pair"),
+        output::TableColumn::left("Side"),
+        output::TableColumn::right("Size"),
         output::TableColumn::right("Price"),
-        output::TableColumn::right("24h %"),
-        output::TableColumn::right("24h Volume"),
-        output::TableColumn::right("Funding"),
-        output::TableColumn::right("Open Interest"),
+        output::TableColumn::left("Type"),
+        output::TableColumn::left("Trigger"),
+        output::TableColumn::left("Time"),
     ];
 
     if args.watch {
         if !output_opts.json {
             watch::hide_cursor();
         }
-        let interval = tokio::time::interval(std::time::Duration::from_millis(250));
-        tokio::pin!(interval);
         let ctrl_c = tokio::signal::ctrl_c();
         tokio::pin!(ctrl_c);
 
-        let mut client: Option<ServerClient> = None;
         let mut last_updated = watch::format_timestamp();
-        let mut error: Option<String> = None;
-        let mut perp_markets: Vec<MarketRow> = Vec::new();
-        let mut spot_markets: Vec<MarketRow> = Vec::new();
+        let mut orders: Vec<OrderRow> = Vec::new();
 
-        loop {
-            tokio::select! {
-                _ = interval.tick() => {
-                    if client.is_none() {
-                        client = ServerClient::try_connect().await?;
-                        if client.is_none() {
-                            error = Some("Server not running. Start with: rhl server start".to_string());
-                        }
+        let render = |orders: &[OrderRow], last_updated: &str| {
+            watch::clear_screen();
+            println!(
+                "{}",
+                output::format_watch_header("Open Orders", last_updated)
+            );
+            println!();
+            if orders.is_empty() {
+                println!("{}", output::style_muted("No open orders"));
+            } else {
+                let rows: Vec<Vec<String>> = orders.iter().map(format_order_row).collect();
+                output::print_table_with_columns(&table_columns, rows);
+            }
+            println!();
+            println!("{}", output::style_muted("Press Ctrl+C to exit"));
+        };
+
+        if !output_opts.json {
+            render(&orders, &last_updated);
+        }
+
+        match fetch_open_orders(api, user).await {
+            Ok(initial) => {
+                orders = initial;
+                last_updated = watch::format_timestamp();
+                if output_opts.json {
+                    output::print_json_line(&serde_json::json!({
+                        "orders": orders,
+                        "timestamp": chrono::Utc::now().to_rfc3339(),
+                    }))?;
+                } else {
+                    render(&orders, &last_updated);
+                }
+            }
+            Err(e) => {
+                if !output_opts.json {
+                    watch::clear_screen();
+                    println!("{}", output::style_loss(format!("Error: {e}")));
+                    println!("{}", output::style_muted("Reconnecting..."));
+                } else {
+                    output::print_error(e.to_string());
+                }
+            }
+        }
+
+        'outer: loop {
+            let mut client = match ws::WsClient::connect(api.testnet).await {
+                Ok(c) => c,
+                Err(e) => {
+                    if !output_opts.json {
+                        watch::clear_screen();
+                        println!("{}", output::style_loss(format!("Error: {e}")));
+                        println!("{}", output::style_muted("Reconnecting..."));
+                    } else {
+                        output::print_error(e.to_string());
                     }
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    continue;
+                }
+            };
 
-                    if let Some(c) = client.as_mut() {
-                        match fetch_market_data(c, is_spot_only, is_perp_only).await {
-                            Ok((next_perp_markets, next_spot_markets)) => {
-                                perp_markets = next_perp_markets;
-                                spot_markets = next_spot_markets;
-                                last_updated = watch::format_timestamp();
-                                error = None;
+            if let Err(e) = client.subscribe(ws::sub_order_updates(user)).await {
+                if !output_opts.json {
+                    watch::clear_screen();
+                    println!("{}", output::style_loss(format!("Error: {e}")));
+                    println!("{}", output::style_muted("Reconnecting..."));
+                } else {
+                    output::print_error(e.to_string());
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                continue;
+            }
+
+            loop {
+                tokio::select! {
+                    msg = client.next_json() => {
+                        let msg = match msg {
+                            Ok(Some(m)) => m,
+                            Ok(None) => break,
+                            Err(e) => {
+                                if !output_opts.json {
+                                    watch::clear_screen();
+                                    println!("{}", output::style_loss(format!("Error: {e}")));
+                                    println!("{}", output::style_muted("Reconnecting..."));
+                                } else {
+                                    output::print_error(e.to_string());
+                                }
+                                break;
+                            }
+                        };
+
+                        let Ok(channel) = ws::WsClient::channel(&msg) else {
+                            continue;
+                        };
+                        if channel != "orderUpdates" {
+                            continue;
+                        }
 
+                        match fetch_open_orders(api, user).await {
+                            Ok(next) => {
+                                orders = next;
+                                last_updated = watch::format_timestamp();
                                 if output_opts.json {
                                     output::print_json_line(&serde_json::json!({
-                                        "perpMarkets": perp_markets,
-                                        "spotMarkets": spot_markets,
+                                        "orders": orders,
                                         "timestamp": chrono::Utc::now().to_rfc3339(),
                                     }))?;
-                                    continue;
+                                } else {
+                                    render(&orders, &last_updated);
                                 }
                             }
                             Err(e) => {
-                                client = None;
-                                error = Some(e.to_string());
+                                if !output_opts.json {
+                                    watch::clear_screen();
+                                    println!("{}", output::style_loss(format!("Error: {e}")));
+                                    println!("{}", output::style_muted("Reconnecting..."));
+                                } else {
+                                    output::print_error(e.to_string());
+                                }
                             }
                         }
                     }
+                    _ = &mut ctrl_c => break 'outer,
+                }
+            }
 
-                    if output_opts.json {
-                        continue;
-                    }
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
 
-                    watch::clear_screen();
-                    println!(
-                        "{}",
-                        output::format_watch_header(
-                            format!(
-                                "All Markets ({} perps, {} spot)",
-                                perp_markets.len(),
-                                spot_markets.len()
-                            ),
-                            &last_updated
-                        )
-                    );
-                    println!();
-                    if let Some(err) = &error {
-                        println!(
-                            "{} {}",
-                            output::style_loss(format!("Error: {err}")),
-                            output::style_muted("(reconnecting...)")
-                        );
-                        println!();
-                    }
+        if !output_opts.json {
+            watch::show_cursor();
+        }
+        return Ok(());
+    }
 
-                    let mut rows = Vec::new();
-                    for m in perp_markets.iter().chain(spot_markets.iter()) {
-                        let price_change = m.price_change.map_or_else(
-                            || output::style_muted("-"),
-                            |c| {
-                                let formatted = format!("{:+.2}%", c);
-                                if c >= 0.0 {
-                                    output::style_profit(formatted)
-                                } else {
-                                    output::style_loss(formatted)
-                                }
-                            },
-                        );
-                        let funding = m.funding.as_ref().map_or_else(
-                            || output::style_muted("-"),
-                            |f| {
-                                let pct = f.parse::<f64>().ok().map(|x| x * 100.0);
-                                pct.map_or_else(
-                                    || output::style_muted("-"),
-                                    |p| {
-                                        let formatted = format!("{p:.4}%");
-                                        if p >= 0.0 {
-                                            output::style_profit(formatted)
-                                        } else {
-                                            output::style_loss(formatted)
-                                        }
-                                    },
-                                )
-                            },
-                        );
-                        let open_interest = m
-                            .open_interest
-                            .as_ref()
-                            .map(|s| s.clone())
-                            .unwrap_or_else(|| output::style_muted("-"));
-                        rows.push(vec![
-                            m.coin.clone(),
-                            m.pair_name.clone(),
-                            m.price.clone(),
-                            price_change,
-                            m.volume_usd.clone(),
-                            funding,
-                            open_interest,
-                        ]);
-                    }
-                    if rows.is_empty() {
-                        println!("{}", output::style_muted("No data"));
+    let orders = fetch_open_orders(api, user).await?;
+    if output_opts.json {
+        output::print_json_pretty(&orders)?;
+        return Ok(());
+    }
+
+    if orders.is_empty() {
+        println!("{}", output::style_muted("No open orders"));
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = orders.iter().map(format_order_row).collect();
+    output::print_table_with_columns(&table_columns, rows);
+    Ok(())
+}
+
+/// Fills per `userFillsByTime` page; a full page means there may be more.
+const FILLS_PAGE_SIZE: usize = 2000;
+
+/// Fills in a time window, newest first. Without a window, the exchange's
+/// most recent fills.
+async fn fetch_fills(
+    api: &HlApi,
+    user: Address,
+    since: Option<i64>,
+    until: Option<i64>,
+) -> Result<Vec<Fill>> {
+    let mut fills = if since.is_none() && until.is_none() {
+        api.user_fills(user).await?
+    } else {
+        fetch_time_pages(
+            since.unwrap_or(0),
+            FILLS_PAGE_SIZE,
+            |f: &Fill| f.time,
+            |start| api.user_fills_by_time(user, start, until),
+        )
+        .await?
+    };
+    fills.sort_by(|a, b| b.time.cmp(&a.time).then(b.tid.cmp(&a.tid)));
+    Ok(fills)
+}
+
+fn format_fill_row(f: &Fill) -> Vec<String> {
+    let side = if f.side == "B" {
+        output::style_profit("Buy")
+    } else {
+        output::style_loss("Sell")
+    };
+    let pnl = f.closed_pnl.parse::<f64>().unwrap_or(0.0);
+    let closed_pnl = if pnl > 0.0 {
+        output::style_profit(&f.closed_pnl)
+    } else if pnl < 0.0 {
+        output::style_loss(&f.closed_pnl)
+    } else {
+        output::style_muted("-")
+    };
+    let mut liquidity = if f.crossed { "Taker" } else { "Maker" }.to_string();
+    if f.liquidation.is_some() {
+        liquidity.push_str(" (liquidation)");
+    }
+    vec![
+        format_order_timestamp(f.time as u64),
+        f.coin.clone(),
+        side,
+        f.px.clone(),
+        f.sz.clone(),
+        format!("{} {}", f.fee, f.fee_token).trim_end().to_string(),
+        closed_pnl,
+        liquidity,
+    ]
+}
+
+async fn account_fills(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: AccountFillsArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let user = resolve_user_address(cfg, &args.user)?;
+    let now = chrono::Utc::now().timestamp_millis();
+    let since = args
+        .since
+        .as_deref()
+        .map(|v| validation::validate_past_time(v, "--since", now))
+        .transpose()?;
+    let until = args
+        .until
+        .as_deref()
+        .map(|v| validation::validate_past_time(v, "--until", now))
+        .transpose()?;
+    if let (Some(since), Some(until)) = (since, until)
+        && since >= until
+    {
+        return Err(anyhow!("--since must be before --until"));
+    }
+    let limit = validation::validate_positive_u64(&args.limit, "limit")? as usize;
+    let coin = match args.coin.as_deref() {
+        Some(coin) => Some(resolve_coin(api, coin).await?),
+        None => None,
+    };
+    let matches = |f: &Fill| {
+        coin.as_ref().is_none_or(|c| &f.coin == c)
+            && since.is_none_or(|t| f.time >= t)
+            && until.is_none_or(|t| f.time <= t)
+    };
+    let table_columns = [
+        output::TableColumn::left("Time"),
+        output::TableColumn::left("Coin"),
+        output::TableColumn::left("Side"),
+        output::TableColumn::right("Price"),
+        output::TableColumn::right("Size"),
+        output::TableColumn::right("Fee"),
+        output::TableColumn::right("Closed PnL"),
+        output::TableColumn::left("Liquidity"),
+    ];
+
+    if args.watch {
+        if !output_opts.json {
+            watch::hide_cursor();
+        }
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        let mut last_updated = watch::format_timestamp();
+        let mut fills: Vec<Fill> = Vec::new();
+        let mut seen: std::collections::HashSet<u64> = std::collections::HashSet::new();
+
+        let render = |fills: &[Fill], last_updated: &str| {
+            watch::clear_screen();
+            println!("{}", output::format_watch_header("Fills", last_updated));
+            println!();
+            if fills.is_empty() {
+                println!("{}", output::style_muted("No fills"));
+            } else {
+                let rows: Vec<Vec<String>> = fills.iter().map(format_fill_row).collect();
+                output::print_table_with_columns(&table_columns, rows);
+            }
+            println!();
+            println!("{}", output::style_muted("Press Ctrl+C to exit"));
+        };
+
+        if !output_opts.json {
+            render(&fills, &last_updated);
+        }
+
+        match fetch_fills(api, user, since, None).await {
+            Ok(initial) => {
+                fills = initial.into_iter().filter(|f| matches(f)).collect();
+                fills.truncate(limit);
+                seen.extend(fills.iter().map(|f| f.tid));
+                last_updated = watch::format_timestamp();
+                if output_opts.json {
+                    output::print_json_line(&serde_json::json!({
+                        "fills": fills,
+                        "timestamp": chrono::Utc::now().to_rfc3339(),
+                    }))?;
+                } else {
+                    render(&fills, &last_updated);
+                }
+            }
+            Err(e) => {
+                if !output_opts.json {
+                    watch::clear_screen();
+                    println!("{}", output::style_loss(format!("Error: {e}")));
+                    println!("{}", output::style_muted("Reconnecting..."));
+                } else {
+                    output::print_error(e.to_string());
+                }
+            }
+        }
+
+        'outer: loop {
+            let mut client = match ws::WsClient::connect(api.testnet).await {
+                Ok(c) => c,
+                Err(e) => {
+                    if !output_opts.json {
+                        watch::clear_screen();
+                        println!("{}", output::style_loss(format!("Error: {e}")));
+                        println!("{}", output::style_muted("Reconnecting..."));
                     } else {
-                        output::print_table_with_columns(&table_columns, rows);
+                        output::print_error(e.to_string());
                     }
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    continue;
+                }
+            };
 
-                    println!();
-                    println!("{}", output::style_muted("Press Ctrl+C to exit"));
+            if let Err(e) = client.subscribe(ws::sub_user_fills(user)).await {
+                if !output_opts.json {
+                    watch::clear_screen();
+                    println!("{}", output::style_loss(format!("Error: {e}")));
+                    println!("{}", output::style_muted("Reconnecting..."));
+                } else {
+                    output::print_error(e.to_string());
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                continue;
+            }
+
+            loop {
+                tokio::select! {
+                    msg = client.next_json() => {
+                        let msg = match msg {
+                            Ok(Some(m)) => m,
+                            Ok(None) => break,
+                            Err(e) => {
+                                if !output_opts.json {
+                                    watch::clear_screen();
+                                    println!("{}", output::style_loss(format!("Error: {e}")));
+                                    println!("{}", output::style_muted("Reconnecting..."));
+                                } else {
+                                    output::print_error(e.to_string());
+                                }
+                                break;
+                            }
+                        };
+
+                        let Ok(channel) = ws::WsClient::channel(&msg) else {
+                            continue;
+                        };
+                        if channel != "userFills" {
+                            continue;
+                        }
+                        // The first message after subscribing is a snapshot of
+                        // recent fills; merging it by trade id also picks up
+                        // fills missed while reconnecting.
+                        let Some(incoming) = ws::WsClient::data(&msg)
+                            .ok()
+                            .and_then(|d| d.get("fills"))
+                            .and_then(|f| serde_json::from_value::<Vec<Fill>>(f.clone()).ok())
+                        else {
+                            continue;
+                        };
+                        let mut new_fills: Vec<Fill> = incoming
+                            .into_iter()
+                            .filter(|f| matches(f) && seen.insert(f.tid))
+                            .collect();
+                        if new_fills.is_empty() {
+                            continue;
+                        }
+                        new_fills.sort_by(|a, b| b.time.cmp(&a.time).then(b.tid.cmp(&a.tid)));
+
+                        last_updated = watch::format_timestamp();
+                        if output_opts.json {
+                            output::print_json_line(&serde_json::json!({
+                                "fills": new_fills,
+                                "timestamp": chrono::Utc::now().to_rfc3339(),
+                            }))?;
+                        }
+                        fills.extend(new_fills);
+                        fills.sort_by(|a, b| b.time.cmp(&a.time).then(b.tid.cmp(&a.tid)));
+                        fills.truncate(limit);
+                        if !output_opts.json {
+                            render(&fills, &last_updated);
+                        }
+                    }
+                    _ = &mut ctrl_c => break 'outer,
                 }
-                _ = &mut ctrl_c => break,
             }
+
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
         }
 
         if !output_opts.json {
@@ -2165,1269 +2707,6135 @@ async fn markets_ls(_api: &HlApi, args: MarketsLsArgs, output_opts: OutputOption
         return Ok(());
     }
 
-    let Some(mut client) = ServerClient::try_connect().await? else {
-        return Err(anyhow!("Server not running. Start with: rhl server start"));
+    let mut fills: Vec<Fill> = fetch_fills(api, user, since, until)
+        .await?
+        .into_iter()
+        .filter(|f| matches(f))
+        .collect();
+    fills.truncate(limit);
+    if output_opts.json {
+        output::print_json_pretty(&fills)?;
+        return Ok(());
+    }
+
+    if fills.is_empty() {
+        println!("{}", output::style_muted("No fills"));
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = fills.iter().map(format_fill_row).collect();
+    output::print_table_with_columns(&table_columns, rows);
+    Ok(())
+}
+
+/// Entries per `userFunding` / `fundingHistory` page.
+const FUNDING_PAGE_SIZE: usize = 500;
+
+/// Page through a time-ordered info request. Each page resumes at the last
+/// timestamp seen, since more entries may share it; those are dropped from
+/// the previous page and come back in the next.
+async fn fetch_time_pages<T, F, Fut>(
+    start_time: i64,
+    page_size: usize,
+    time_of: impl Fn(&T) -> i64,
+    fetch: F,
+) -> Result<Vec<T>>
+where
+    F: Fn(i64) -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<T>>>,
+{
+    let mut entries = Vec::new();
+    let mut start = start_time;
+    loop {
+        let page = fetch(start).await?;
+        let full = page.len() >= page_size;
+        let last = page.iter().map(&time_of).max().unwrap_or(start);
+        entries.extend(page);
+        if !full || last <= start {
+            return Ok(entries);
+        }
+        entries.retain(|e| time_of(e) < last);
+        start = last;
+    }
+}
+
+/// Parse `--since` (default `default_since` ago) and `--until` into a window.
+fn parse_time_window(
+    since: Option<&str>,
+    until: Option<&str>,
+    default_since: &str,
+) -> Result<(i64, Option<i64>)> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let since = validation::validate_past_time(since.unwrap_or(default_since), "--since", now)?;
+    let until = until
+        .map(|v| validation::validate_past_time(v, "--until", now))
+        .transpose()?;
+    if until.is_some_and(|until| since >= until) {
+        return Err(anyhow!("--since must be before --until"));
+    }
+    Ok((since, until))
+}
+
+fn style_signed(value: f64, formatted: String) -> String {
+    if value > 0.0 {
+        output::style_profit(formatted)
+    } else if value < 0.0 {
+        output::style_loss(formatted)
+    } else {
+        formatted
+    }
+}
+
+/// Net funding of one coin over the window.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FundingTotal {
+    coin: String,
+    payments: usize,
+    /// Received minus paid, in USDC
+    usdc: f64,
+}
+
+fn funding_totals(payments: &[UserFunding]) -> Vec<FundingTotal> {
+    let mut totals: Vec<FundingTotal> = Vec::new();
+    for p in payments {
+        let usdc = p.delta.usdc.parse::<f64>().unwrap_or(0.0);
+        match totals.iter_mut().find(|t| t.coin == p.delta.coin) {
+            Some(total) => {
+                total.payments += 1;
+                total.usdc += usdc;
+            }
+            None => totals.push(FundingTotal {
+                coin: p.delta.coin.clone(),
+                payments: 1,
+                usdc,
+            }),
+        }
+    }
+    // Largest cost first
+    totals.sort_by(|a, b| a.usdc.total_cmp(&b.usdc));
+    totals
+}
+
+async fn account_funding(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: AccountFundingArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let user = resolve_user_address(cfg, &args.user)?;
+    let (since, until) = parse_time_window(args.since.as_deref(), args.until.as_deref(), "7d")?;
+    let coin = match args.coin.as_deref() {
+        Some(coin) => Some(resolve_coin(api, coin).await?),
+        None => None,
     };
 
-    let (perp_markets, spot_markets) =
-        fetch_market_data(&mut client, is_spot_only, is_perp_only).await?;
+    let mut payments: Vec<UserFunding> = fetch_time_pages(
+        since,
+        FUNDING_PAGE_SIZE,
+        |p: &UserFunding| p.time,
+        |start| api.user_funding(user, start, until),
+    )
+    .await?
+    .into_iter()
+    .filter(|p| coin.as_ref().is_none_or(|c| &p.delta.coin == c))
+    .collect();
+    payments.sort_by_key(|p| std::cmp::Reverse(p.time));
+    let totals = funding_totals(&payments);
+    let net: f64 = totals.iter().map(|t| t.usdc).sum();
+
     if output_opts.json {
-        output::print_json_pretty(&serde_json::json!({
-            "perpMarkets": perp_markets,
-            "spotMarkets": spot_markets,
-        }))?;
+        return output::print_json_pretty(&serde_json::json!({
+            "payments": payments,
+            "totals": totals,
+            "net": net,
+        }));
+    }
+    if payments.is_empty() {
+        println!("{}", output::style_muted("No funding payments"));
         return Ok(());
     }
 
-    println!(
-        "{}",
-        output::style_header(format!(
-            "All Markets ({} perps, {} spot):",
-            perp_markets.len(),
-            spot_markets.len()
-        ))
+    let rows: Vec<Vec<String>> = payments
+        .iter()
+        .map(|p| {
+            let usdc = p.delta.usdc.parse::<f64>().unwrap_or(0.0);
+            let rate = p.delta.funding_rate.parse::<f64>().unwrap_or(0.0) * 100.0;
+            vec![
+                format_order_timestamp(p.time as u64),
+                p.delta.coin.clone(),
+                p.delta.szi.clone(),
+                format!("{rate:.4}%"),
+                style_signed(usdc, format!("{usdc:.4}")),
+            ]
+        })
+        .collect();
+    output::print_table_with_columns(
+        &[
+            output::TableColumn::left("Time"),
+            output::TableColumn::left("Coin"),
+            output::TableColumn::right("Position"),
+            output::TableColumn::right("Rate"),
+            output::TableColumn::right("Payment"),
+        ],
+        rows,
     );
 
-    let mut rows = Vec::new();
-    for m in perp_markets.iter().chain(spot_markets.iter()) {
-        let price_change = m.price_change.map_or_else(
-            || output::style_muted("-"),
-            |c| {
-                let formatted = format!("{:+.2}%", c);
-                if c >= 0.0 {
-                    output::style_profit(formatted)
+    println!();
+    println!("{}", output::style_header("Totals"));
+    let rows: Vec<Vec<String>> = totals
+        .iter()
+        .map(|t| {
+            vec![
+                t.coin.clone(),
+                t.payments.to_string(),
+                style_signed(t.usdc, format!("{:.4}", t.usdc)),
+            ]
+        })
+        .collect();
+    output::print_table_with_columns(
+        &[
+            output::TableColumn::left("Coin"),
+            output::TableColumn::right("Payments"),
+            output::TableColumn::right("Net USDC"),
+        ],
+        rows,
+    );
+    println!();
+    println!(
+        "Net funding: {} USDC (positive = received)",
+        style_signed(net, format!("{net:.4}"))
+    );
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct SpotBalanceRow {
+    token: String,
+    total: String,
+    hold: String,
+    available: String,
+}
+
+async fn fetch_balances(api: &HlApi, user: Address) -> Result<(Vec<SpotBalanceRow>, String)> {
+    let (clearinghouse, spot_state) = tokio::try_join!(
+        api.clearinghouse_state(user),
+        api.spot_clearinghouse_state(user)
+    )?;
+
+    let spot_balances: Vec<SpotBalanceRow> = spot_state
+        .balances
+        .into_iter()
+        .filter(|b| b.total.parse::<f64>().unwrap_or(0.0) != 0.0)
+        .map(|b| {
+            let total = b.total.parse::<f64>().unwrap_or(0.0);
+            let hold = b.hold.parse::<f64>().unwrap_or(0.0);
+            let available = total - hold;
+            SpotBalanceRow {
+                token: b.coin,
+                total: b.total,
+                hold: b.hold,
+                available: available.to_string(),
+            }
+        })
+        .collect();
+
+    let perp_balance = clearinghouse.margin_summary.account_value.clone();
+    Ok((spot_balances, perp_balance))
+}
+
+async fn account_balances(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: AccountUserWatchArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let user = resolve_user_address(cfg, &args.user)?;
+    let table_columns = [
+        output::TableColumn::left("Token"),
+        output::TableColumn::right("Total"),
+        output::TableColumn::right("Hold"),
+        output::TableColumn::right("Available"),
+    ];
+
+    if args.watch {
+        if !output_opts.json {
+            watch::hide_cursor();
+        }
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        let mut last_updated = watch::format_timestamp();
+        let mut spot_balances: Vec<SpotBalanceRow> = Vec::new();
+        let mut perp_balance = "0".to_string();
+
+        let render = |spot_balances: &[SpotBalanceRow], perp_balance: &str, last_updated: &str| {
+            watch::clear_screen();
+            println!("{}", output::format_watch_header("Balances", last_updated));
+            println!();
+            println!(
+                "{}{} USD",
+                output::style_bold("Perpetuals Balance: "),
+                perp_balance
+            );
+            println!();
+            println!("{}", output::style_header("Spot Balances:"));
+            if spot_balances.is_empty() {
+                println!("{}", output::style_muted("No spot balances"));
+            } else {
+                let rows: Vec<Vec<String>> = spot_balances
+                    .iter()
+                    .map(|b| {
+                        vec![
+                            b.token.clone(),
+                            b.total.clone(),
+                            b.hold.clone(),
+                            b.available.clone(),
+                        ]
+                    })
+                    .collect();
+                output::print_table_with_columns(&table_columns, rows);
+            }
+            println!();
+            println!("{}", output::style_muted("Press Ctrl+C to exit"));
+        };
+
+        if !output_opts.json {
+            render(&spot_balances, &perp_balance, &last_updated);
+        }
+
+        'outer: loop {
+            let mut client = match ws::WsClient::connect(api.testnet).await {
+                Ok(c) => c,
+                Err(e) => {
+                    if !output_opts.json {
+                        watch::clear_screen();
+                        println!("{}", output::style_loss(format!("Error: {e}")));
+                        println!("{}", output::style_muted("Reconnecting..."));
+                    } else {
+                        output::print_error(e.to_string());
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    continue;
+                }
+            };
+
+            if let Err(e) = client
+                .subscribe(ws::sub_all_dexs_clearinghouse_state(user))
+                .await
+            {
+                if !output_opts.json {
+                    watch::clear_screen();
+                    println!("{}", output::style_loss(format!("Error: {e}")));
+                    println!("{}", output::style_muted("Reconnecting..."));
                 } else {
-                    output::style_loss(formatted)
+                    output::print_error(e.to_string());
                 }
-            },
-        );
-        let funding = m.funding.as_ref().map_or_else(
-            || output::style_muted("-"),
-            |f| {
-                let pct = f.parse::<f64>().ok().map(|x| x * 100.0);
-                pct.map_or_else(
-                    || output::style_muted("-"),
-                    |p| {
-                        let formatted = format!("{p:.4}%");
-                        if p >= 0.0 {
-                            output::style_profit(formatted)
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                continue;
+            }
+
+            loop {
+                tokio::select! {
+                    msg = client.next_json() => {
+                        let msg = match msg {
+                            Ok(Some(m)) => m,
+                            Ok(None) => break,
+                            Err(e) => {
+                                if !output_opts.json {
+                                    watch::clear_screen();
+                                    println!("{}", output::style_loss(format!("Error: {e}")));
+                                    println!("{}", output::style_muted("Reconnecting..."));
+                                } else {
+                                    output::print_error(e.to_string());
+                                }
+                                break;
+                            }
+                        };
+
+                        let Ok(channel) = ws::WsClient::channel(&msg) else {
+                            continue;
+                        };
+                        if channel != "allDexsClearinghouseState" {
+                            continue;
+                        }
+
+                        let Ok(data) = ws::WsClient::data(&msg) else {
+                            continue;
+                        };
+                        let Ok(event) = serde_json::from_value::<AllDexsClearinghouseStateEvent>(data.clone()) else {
+                            continue;
+                        };
+
+                        if let Some((_, main)) = event.clearinghouse_states.first() {
+                            perp_balance = main.margin_summary.account_value.clone();
                         } else {
-                            output::style_loss(formatted)
+                            perp_balance = "0".to_string();
                         }
-                    },
-                )
-            },
-        );
-        let open_interest = m
-            .open_interest
-            .as_ref()
-            .map(|s| s.clone())
-            .unwrap_or_else(|| output::style_muted("-"));
-        rows.push(vec![
-            m.coin.clone(),
-            m.pair_name.clone(),
-            m.price.clone(),
-            price_change,
-            m.volume_usd.clone(),
-            funding,
-            open_interest,
-        ]);
+
+                        if let Ok(spot_state) = api.spot_clearinghouse_state(user).await {
+                            spot_balances = spot_state
+                                .balances
+                                .into_iter()
+                                .filter(|b| b.total.parse::<f64>().unwrap_or(0.0) != 0.0)
+                                .map(|b| {
+                                    let total = b.total.parse::<f64>().unwrap_or(0.0);
+                                    let hold = b.hold.parse::<f64>().unwrap_or(0.0);
+                                    let available = total - hold;
+                                    SpotBalanceRow {
+                                        token: b.coin,
+                                        total: b.total,
+                                        hold: b.hold,
+                                        available: available.to_string(),
+                                    }
+                                })
+                                .collect();
+                        }
+
+                        last_updated = watch::format_timestamp();
+
+                        if output_opts.json {
+                            output::print_json_line(&serde_json::json!({
+                                "spotBalances": spot_balances,
+                                "perpBalance": perp_balance,
+                                "timestamp": chrono::Utc::now().to_rfc3339(),
+                            }))?;
+                        } else {
+                            render(&spot_balances, &perp_balance, &last_updated);
+                        }
+                    }
+                    _ = &mut ctrl_c => break 'outer,
+                }
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+
+        if !output_opts.json {
+            watch::show_cursor();
+        }
+        return Ok(());
+    }
+
+    let (spot_balances, perp_balance) = fetch_balances(api, user).await?;
+    if output_opts.json {
+        output::print_json_pretty(&serde_json::json!({
+            "spotBalances": spot_balances,
+            "perpBalance": perp_balance,
+        }))?;
+        return Ok(());
+    }
+
+    println!(
+        "{}{} USD",
+        output::style_bold("Perpetuals Balance: "),
+        perp_balance
+    );
+    println!();
+    println!("{}", output::style_header("Spot Balances:"));
+    if spot_balances.is_empty() {
+        println!("{}", output::style_muted("No spot balances"));
+        return Ok(());
+    }
+    let rows: Vec<Vec<String>> = spot_balances
+        .iter()
+        .map(|b| {
+            vec![
+                b.token.clone(),
+                b.total.clone(),
+                b.hold.clone(),
+                b.available.clone(),
+            ]
+        })
+        .collect();
+    output::print_table_with_columns(&table_columns, rows);
+    Ok(())
+}
+
+async fn account_portfolio(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: AccountUserWatchArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let user = resolve_user_address(cfg, &args.user)?;
+    let position_table_columns = [
+        output::TableColumn::left("Coin"),
+        output::TableColumn::right("Size"),
+        output::TableColumn::right("Entry"),
+        output::TableColumn::right("Value"),
+        output::TableColumn::right("PnL"),
+        output::TableColumn::right("Leverage"),
+    ];
+    let balance_table_columns = [
+        output::TableColumn::left("Token"),
+        output::TableColumn::right("Total"),
+        output::TableColumn::right("Hold"),
+    ];
+
+    if args.watch {
+        if !output_opts.json {
+            watch::hide_cursor();
+        }
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        let mut last_updated = watch::format_timestamp();
+        let mut data = PortfolioData {
+            positions: Vec::new(),
+            spot_balances: Vec::new(),
+            account_value: "0".to_string(),
+            total_margin_used: "0".to_string(),
+        };
+
+        let render = |data: &PortfolioData, last_updated: &str| {
+            watch::clear_screen();
+            println!("{}", output::format_watch_header("Portfolio", last_updated));
+            println!();
+            println!("{}", output::style_header("Account Summary"));
+            println!("Account Value: {}", output::style_bold(&data.account_value));
+            println!(
+                "Total Margin Used: {}",
+                output::style_bold(&data.total_margin_used)
+            );
+            println!();
+            println!("{}", output::style_header("Perpetual Positions:"));
+            if data.positions.is_empty() {
+                println!("{}", output::style_muted("No open positions"));
+            } else {
+                let rows: Vec<Vec<String>> = data
+                    .positions
+                    .iter()
+                    .map(|p| {
+                        vec![
+                            p.coin.clone(),
+                            p.size.clone(),
+                            p.entry_px.clone().unwrap_or_else(|| "-".to_string()),
+                            p.position_value.clone(),
+                            format_pnl_cell(&p.unrealized_pnl),
+                            p.leverage.clone(),
+                        ]
+                    })
+                    .collect();
+                output::print_table_with_columns(&position_table_columns, rows);
+            }
+            println!();
+            println!("{}", output::style_header("Spot Balances:"));
+            if data.spot_balances.is_empty() {
+                println!("{}", output::style_muted("No spot balances"));
+            } else {
+                let rows: Vec<Vec<String>> = data
+                    .spot_balances
+                    .iter()
+                    .map(|b| vec![b.token.clone(), b.total.clone(), b.hold.clone()])
+                    .collect();
+                output::print_table_with_columns(&balance_table_columns, rows);
+            }
+            println!();
+            println!("{}", output::style_muted("Press Ctrl+C to exit"));
+        };
+
+        if !output_opts.json {
+            render(&data, &last_updated);
+        }
+
+        'outer: loop {
+            let mut client = match ws::WsClient::connect(api.testnet).await {
+                Ok(c) => c,
+                Err(e) => {
+                    if !output_opts.json {
+                        watch::clear_screen();
+                        println!("{}", output::style_loss(format!("Error: {e}")));
+                        println!("{}", output::style_muted("Reconnecting..."));
+                    } else {
+                        output::print_error(e.to_string());
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    continue;
+                }
+            };
+
+            if let Err(e) = client
+                .subscribe(ws::sub_all_dexs_clearinghouse_state(user))
+                .await
+            {
+                if !output_opts.json {
+                    watch::clear_screen();
+                    println!("{}", output::style_loss(format!("Error: {e}")));
+                    println!("{}", output::style_muted("Reconnecting..."));
+                } else {
+                    output::print_error(e.to_string());
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                continue;
+            }
+
+            loop {
+                tokio::select! {
+                    msg = client.next_json() => {
+                        let msg = match msg {
+                            Ok(Some(m)) => m,
+                            Ok(None) => break,
+                            Err(e) => {
+                                if !output_opts.json {
+                                    watch::clear_screen();
+                                    println!("{}", output::style_loss(format!("Error: {e}")));
+                                    println!("{}", output::style_muted("Reconnecting..."));
+                                } else {
+                                    output::print_error(e.to_string());
+                                }
+                                break;
+                            }
+                        };
+
+                        let Ok(channel) = ws::WsClient::channel(&msg) else {
+                            continue;
+                        };
+                        if channel != "allDexsClearinghouseState" {
+                            continue;
+                        }
+
+                        let Ok(data_msg) = ws::WsClient::data(&msg) else {
+                            continue;
+                        };
+                        let Ok(event) = serde_json::from_value::<AllDexsClearinghouseStateEvent>(data_msg.clone()) else {
+                            continue;
+                        };
+
+                        if let Some((_, main)) = event.clearinghouse_states.first() {
+                            data.account_value = main.margin_summary.account_value.clone();
+                            data.total_margin_used = main.margin_summary.total_margin_used.clone();
+                        } else {
+                            data.account_value = "0".to_string();
+                            data.total_margin_used = "0".to_string();
+                        }
+
+                        data.positions = event
+                            .clearinghouse_states
+                            .into_iter()
+                            .flat_map(|(_, st)| st.asset_positions.into_iter().map(|ap| ap.position))
+                            .filter(|p| p.szi.parse::<f64>().unwrap_or(0.0) != 0.0)
+                            .map(|p| PortfolioPositionRow {
+                                coin: p.coin,
+                                size: p.szi,
+                                entry_px: p.entry_px,
+                                position_value: p.position_value,
+                                unrealized_pnl: p.unrealized_pnl,
+                                leverage: format!("{}x {}", p.leverage.value, p.leverage.leverage_type),
+                            })
+                            .collect();
+
+                        if let Ok(spot_state) = api.spot_clearinghouse_state(user).await {
+                            data.spot_balances = spot_state
+                                .balances
+                                .into_iter()
+                                .filter(|b| b.total.parse::<f64>().unwrap_or(0.0) != 0.0)
+                                .map(|b| PortfolioSpotBalanceRow {
+                                    token: b.coin,
+                                    total: b.total,
+                                    hold: b.hold,
+                                })
+                                .collect();
+                        }
+
+                        last_updated = watch::format_timestamp();
+
+                        if output_opts.json {
+                            let mut v = serde_json::to_value(&data)?;
+                            if let Some(obj) = v.as_object_mut() {
+                                obj.insert(
+                                    "timestamp".to_string(),
+                                    serde_json::Value::String(chrono::Utc::now().to_rfc3339()),
+                                );
+                            }
+                            output::print_json_line(&v)?;
+                        } else {
+                            render(&data, &last_updated);
+                        }
+                    }
+                    _ = &mut ctrl_c => break 'outer,
+                }
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+
+        if !output_opts.json {
+            watch::show_cursor();
+        }
+        return Ok(());
+    }
+
+    let data = fetch_portfolio(api, user).await?;
+    if output_opts.json {
+        output::print_json_pretty(&data)?;
+        return Ok(());
+    }
+
+    println!("{}", output::style_header("Account Summary"));
+    println!("Account Value: {}", output::style_bold(&data.account_value));
+    println!(
+        "Total Margin Used: {}",
+        output::style_bold(&data.total_margin_used)
+    );
+    println!();
+    println!("{}", output::style_header("Perpetual Positions:"));
+    if data.positions.is_empty() {
+        println!("{}", output::style_muted("No open positions"));
+    } else {
+        let rows: Vec<Vec<String>> = data
+            .positions
+            .iter()
+            .map(|p| {
+                vec![
+                    p.coin.clone(),
+                    p.size.clone(),
+                    p.entry_px.clone().unwrap_or_else(|| "-".to_string()),
+                    p.position_value.clone(),
+                    format_pnl_cell(&p.unrealized_pnl),
+                    p.leverage.clone(),
+                ]
+            })
+            .collect();
+        output::print_table_with_columns(&position_table_columns, rows);
+    }
+
+    println!();
+    println!("{}", output::style_header("Spot Balances:"));
+    if data.spot_balances.is_empty() {
+        println!("{}", output::style_muted("No spot balances"));
+    } else {
+        let rows: Vec<Vec<String>> = data
+            .spot_balances
+            .iter()
+            .map(|b| vec![b.token.clone(), b.total.clone(), b.hold.clone()])
+            .collect();
+        output::print_table_with_columns(&balance_table_columns, rows);
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct PortfolioPositionRow {
+    coin: String,
+    size: String,
+    #[serde(rename = "entryPx")]
+    entry_px: Option<String>,
+    #[serde(rename = "positionValue")]
+    position_value: String,
+    #[serde(rename = "unrealizedPnl")]
+    unrealized_pnl: String,
+    leverage: String,
+}
+
+#[derive(serde::Serialize)]
+struct PortfolioSpotBalanceRow {
+    token: String,
+    total: String,
+    hold: String,
+}
+
+#[derive(serde::Serialize)]
+struct PortfolioData {
+    positions: Vec<PortfolioPositionRow>,
+    #[serde(rename = "spotBalances")]
+    spot_balances: Vec<PortfolioSpotBalanceRow>,
+    #[serde(rename = "accountValue")]
+    account_value: String,
+    #[serde(rename = "totalMarginUsed")]
+    total_margin_used: String,
+}
+
+async fn fetch_portfolio(api: &HlApi, user: Address) -> Result<PortfolioData> {
+    let (clearinghouse, spot_state) = tokio::try_join!(
+        api.clearinghouse_state(user),
+        api.spot_clearinghouse_state(user)
+    )?;
+
+    let positions: Vec<PortfolioPositionRow> = clearinghouse
+        .asset_positions
+        .into_iter()
+        .map(|p| p.position)
+        .filter(|p| p.szi.parse::<f64>().unwrap_or(0.0) != 0.0)
+        .map(|p| PortfolioPositionRow {
+            coin: p.coin,
+            size: p.szi,
+            entry_px: p.entry_px,
+            position_value: p.position_value,
+            unrealized_pnl: p.unrealized_pnl,
+            leverage: format!("{}x {}", p.leverage.value, p.leverage.leverage_type),
+        })
+        .collect();
+
+    let spot_balances: Vec<PortfolioSpotBalanceRow> = spot_state
+        .balances
+        .into_iter()
+        .filter(|b| b.total.parse::<f64>().unwrap_or(0.0) != 0.0)
+        .map(|b| PortfolioSpotBalanceRow {
+            token: b.coin,
+            total: b.total,
+            hold: b.hold,
+        })
+        .collect();
+
+    Ok(PortfolioData {
+        positions,
+        spot_balances,
+        account_value: clearinghouse.margin_summary.account_value,
+        total_margin_used: clearinghouse.margin_summary.total_margin_used,
+    })
+}
+
+async fn run_markets(cmd: MarketsCommand, api: &HlApi, output_opts: OutputOptions) -> Result<()> {
+    match cmd {
+        MarketsCommand::Ls(args) => markets_ls(api, args, output_opts).await,
+        MarketsCommand::Dexs => markets_dexs(api, output_opts).await,
+        MarketsCommand::Prices(args) => markets_prices(api, args, output_opts).await,
+    }
+}
+
+#[derive(serde::Serialize)]
+struct MarketRow {
+    coin: String,
+    /// Name to use in commands: the perp name, or `BASE/QUOTE` for spot
+    symbol: String,
+    #[serde(rename = "pairName")]
+    pair_name: String,
+    price: String,
+    #[serde(rename = "priceChange")]
+    price_change: Option<f64>,
+    #[serde(rename = "volumeUsd")]
+    volume_usd: String,
+    funding: Option<String>,
+    #[serde(rename = "openInterest")]
+    open_interest: Option<String>,
+}
+
+fn calculate_price_change(current: &str, prev: &str) -> Option<f64> {
+    let c = current.parse::<f64>().ok()?;
+    let p = prev.parse::<f64>().ok()?;
+    if p == 0.0 {
+        return None;
+    }
+    Some(((c - p) / p) * 100.0)
+}
+
+fn format_volume(volume: Option<&str>) -> String {
+    let Some(volume) = volume else {
+        return "N/A".to_string();
+    };
+    let volume = volume.trim();
+    if volume.is_empty() {
+        return "N/A".to_string();
+    }
+    let num = match volume.parse::<f64>() {
+        Ok(v) if v.is_finite() => v,
+        _ => return "N/A".to_string(),
+    };
+
+    // Match `toLocaleString(undefined, { maximumFractionDigits: 2 })` behavior.
+    let mut s = format!("{num:.2}");
+    if let Some(dot) = s.find('.') {
+        while s.ends_with('0') {
+            s.pop();
+        }
+        if s.len() == dot + 1 {
+            s.pop();
+        }
+    }
+
+    let (sign, rest) = s
+        .strip_prefix('-')
+        .map(|r| ("-", r))
+        .unwrap_or(("", s.as_str()));
+    let (int_part, frac_part) = rest.split_once('.').unwrap_or((rest, ""));
+
+    let mut grouped = String::with_capacity(rest.len() + rest.len() / 3);
+    for (idx, ch) in int_part.chars().rev().enumerate() {
+        if idx > 0 && idx % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(ch);
+    }
+    let int_grouped: String = grouped.chars().rev().collect();
+
+    if frac_part.is_empty() {
+        format!("{sign}{int_grouped}")
+    } else {
+        format!("{sign}{int_grouped}.{frac_part}")
+    }
+}
+
+async fn markets_ls(_api: &HlApi, args: MarketsLsArgs, output_opts: OutputOptions) -> Result<()> {
+    let is_spot_only = args.spot_only;
+    let is_perp_only = args.perp_only || args.dex.is_some();
+    let dex = args.dex.as_deref();
+    let table_columns = [
+        output::TableColumn::left("Coin"),
+        output::TableColumn::left("Pair"),
+        output::TableColumn::right("Price"),
+        output::TableColumn::right("24h %"),
+        output::TableColumn::right("24h Volume"),
+        output::TableColumn::right("Funding"),
+        output::TableColumn::right("Open Interest"),
+    ];
+
+    if args.watch {
+        if !output_opts.json {
+            watch::hide_cursor();
+        }
+        let interval = tokio::time::interval(std::time::Duration::from_millis(250));
+        tokio::pin!(interval);
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        let mut client: Option<ServerClient> = None;
+        let mut last_updated = watch::format_timestamp();
+        let mut error: Option<String> = None;
+        let mut perp_markets: Vec<MarketRow> = Vec::new();
+        let mut spot_markets: Vec<MarketRow> = Vec::new();
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    if client.is_none() {
+                        client = ServerClient::try_connect().await?;
+                        if client.is_none() {
+                            error = Some("Server not running. Start with: rhl server start".to_string());
+                        }
+                    }
+
+                    if let Some(c) = client.as_mut() {
+                        match fetch_market_data(c, is_spot_only, is_perp_only, dex).await {
+                            Ok((next_perp_markets, next_spot_markets)) => {
+                                perp_markets = next_perp_markets;
+                                spot_markets = next_spot_markets;
+                                last_updated = watch::format_timestamp();
+                                error = None;
+
+                                if output_opts.json {
+                                    output::print_json_line(&serde_json::json!({
+                                        "perpMarkets": perp_markets,
+                                        "spotMarkets": spot_markets,
+                                        "timestamp": chrono::Utc::now().to_rfc3339(),
+                                    }))?;
+                                    continue;
+                                }
+                            }
+                            Err(e) => {
+                                client = None;
+                                error = Some(e.to_string());
+                            }
+                        }
+                    }
+
+                    if output_opts.json {
+                        continue;
+                    }
+
+                    watch::clear_screen();
+                    println!(
+                        "{}",
+                        output::format_watch_header(
+                            format!(
+                                "All Markets ({} perps, {} spot)",
+                                perp_markets.len(),
+                                spot_markets.len()
+                            ),
+                            &last_updated
+                        )
+                    );
+                    println!();
+                    if let Some(err) = &error {
+                        println!(
+                            "{} {}",
+                            output::style_loss(format!("Error: {err}")),
+                            output::style_muted("(reconnecting...)")
+                        );
+                        println!();
+                    }
+
+                    let mut rows = Vec::new();
+                    for m in perp_markets.iter().chain(spot_markets.iter()) {
+                        let price_change = m.price_change.map_or_else(
+                            || output::style_muted("-"),
+                            |c| {
+                                let formatted = format!("{:+.2}%", c);
+                                if c >= 0.0 {
+                                    output::style_profit(formatted)
+                                } else {
+                                    output::style_loss(formatted)
+                                }
+                            },
+                        );
+                        let funding = m.funding.as_ref().map_or_else(
+                            || output::style_muted("-"),
+                            |f| {
+                                let pct = f.parse::<f64>().ok().map(|x| x * 100.0);
+                                pct.map_or_else(
+                                    || output::style_muted("-"),
+                                    |p| {
+                                        let formatted = format!("{p:.4}%");
+                                        if p >= 0.0 {
+                                            output::style_profit(formatted)
+                                        } else {
+                                            output::style_loss(formatted)
+                                        }
+                                    },
+                                )
+                            },
+                        );
+                        let open_interest = m
+                            .open_interest
+                            .clone()
+                            .unwrap_or_else(|| output::style_muted("-"));
+                        rows.push(vec![
+                            m.symbol.clone(),
+                            m.pair_name.clone(),
+                            m.price.clone(),
+                            price_change,
+                            m.volume_usd.clone(),
+                            funding,
+                            open_interest,
+                        ]);
+                    }
+                    if rows.is_empty() {
+                        println!("{}", output::style_muted("No data"));
+                    } else {
+                        output::print_table_with_columns(&table_columns, rows);
+                    }
+
+                    println!();
+                    println!("{}", output::style_muted("Press Ctrl+C to exit"));
+                }
+                _ = &mut ctrl_c => break,
+            }
+        }
+
+        if !output_opts.json {
+            watch::show_cursor();
+        }
+        return Ok(());
+    }
+
+    let Some(mut client) = ServerClient::try_connect().await? else {
+        return Err(anyhow!("Server not running. Start with: rhl server start"));
+    };
+
+    let (perp_markets, spot_markets) =
+        fetch_market_data(&mut client, is_spot_only, is_perp_only, dex).await?;
+    if output_opts.json {
+        output::print_json_pretty(&serde_json::json!({
+            "perpMarkets": perp_markets,
+            "spotMarkets": spot_markets,
+        }))?;
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        output::style_header(format!(
+            "All Markets ({} perps, {} spot):",
+            perp_markets.len(),
+            spot_markets.len()
+        ))
+    );
+
+    let mut rows = Vec::new();
+    for m in perp_markets.iter().chain(spot_markets.iter()) {
+        let price_change = m.price_change.map_or_else(
+            || output::style_muted("-"),
+            |c| {
+                let formatted = format!("{:+.2}%", c);
+                if c >= 0.0 {
+                    output::style_profit(formatted)
+                } else {
+                    output::style_loss(formatted)
+                }
+            },
+        );
+        let funding = m.funding.as_ref().map_or_else(
+            || output::style_muted("-"),
+            |f| {
+                let pct = f.parse::<f64>().ok().map(|x| x * 100.0);
+                pct.map_or_else(
+                    || output::style_muted("-"),
+                    |p| {
+                        let formatted = format!("{p:.4}%");
+                        if p >= 0.0 {
+                            output::style_profit(formatted)
+                        } else {
+                            output::style_loss(formatted)
+                        }
+                    },
+                )
+            },
+        );
+        let open_interest = m
+            .open_interest
+            .clone()
+            .unwrap_or_else(|| output::style_muted("-"));
+        rows.push(vec![
+            m.symbol.clone(),
+            m.pair_name.clone(),
+            m.price.clone(),
+            price_change,
+            m.volume_usd.clone(),
+            funding,
+            open_interest,
+        ]);
+    }
+    output::print_table_with_columns(&table_columns, rows);
+    Ok(())
+}
+
+async fn fetch_market_data(
+    client: &mut ServerClient,
+    spot_only: bool,
+    perp_only: bool,
+    dex_filter: Option<&str>,
+) -> Result<(Vec<MarketRow>, Vec<MarketRow>)> {
+    let spot_meta = client.get_spot_meta().await?.data;
+
+    let mut perp_markets = Vec::new();
+    if !spot_only {
+        let all_perp_metas = client.get_perp_meta().await?.data;
+        let asset_ctxs = client.get_asset_ctxs().await?.data;
+
+        let mut dex_found = false;
+        for (meta_index, perp_meta) in all_perp_metas.iter().enumerate() {
+            // Skip unexpected meta objects with no universe.
+            let Some(dex) = asset_index::perp_dex_name(meta_index, perp_meta) else {
+                continue;
+            };
+            if let Some(filter) = dex_filter {
+                if meta_index == 0 || !dex.eq_ignore_ascii_case(filter) {
+                    continue;
+                }
+                dex_found = true;
+            }
+
+            let collateral = spot_meta
+                .tokens
+                .get(perp_meta.collateral_token as usize)
+                .map(|t| t.name.clone())
+                .unwrap_or_else(|| "?".to_string());
+            let dex_suffix = if meta_index == 0 {
+                String::new()
+            } else {
+                format!(" @{dex}")
+            };
+            let dex_ctxs = asset_ctxs
+                .ctxs
+                .iter()
+                .find(|(name, _)| name == &dex)
+                .map(|(_, ctxs)| ctxs);
+
+            for (idx, market) in perp_meta.universe.iter().enumerate() {
+                let ctx = dex_ctxs.and_then(|ctxs| ctxs.get(idx));
+                let display_name = if meta_index == 0 {
+                    market.name.as_str()
+                } else {
+                    market
+                        .name
+                        .split_once(':')
+                        .map(|(_, rest)| rest)
+                        .unwrap_or(market.name.as_str())
+                };
+                perp_markets.push(MarketRow {
+                    coin: market.name.clone(),
+                    symbol: market.name.clone(),
+                    pair_name: format!(
+                        "{display_name}/{collateral} {}x{dex_suffix}",
+                        market.max_leverage
+                    ),
+                    price: ctx
+                        .map(|c| c.mark_px.clone())
+                        .unwrap_or_else(|| "?".to_string()),
+                    price_change: ctx
+                        .and_then(|c| calculate_price_change(&c.mark_px, &c.prev_day_px)),
+                    volume_usd: ctx
+                        .map(|c| format_volume(Some(&c.day_ntl_vlm)))
+                        .unwrap_or_else(|| "N/A".to_string()),
+                    funding: ctx.map(|c| c.funding.clone()),
+                    open_interest: ctx.and_then(|c| {
+                        if c.open_interest.trim().is_empty() {
+                            None
+                        } else {
+                            Some(format_volume(Some(&c.open_interest)))
+                        }
+                    }),
+                });
+            }
+        }
+        if let Some(filter) = dex_filter
+            && !dex_found
+        {
+            return Err(anyhow!(
+                "Unknown dex: {filter}. See `rhl markets dexs` for the list"
+            ));
+        }
+    }
+
+    let mut spot_markets = Vec::new();
+    if !perp_only {
+        let spot_ctxs = client.get_spot_asset_ctxs().await?.data;
+        let mut ctx_map = std::collections::HashMap::new();
+        for ctx in spot_ctxs {
+            ctx_map.insert(ctx.coin.clone(), ctx);
+        }
+        for pair in spot_meta.universe.iter() {
+            let base = spot_meta
+                .tokens
+                .get(pair.tokens.first().copied().unwrap_or_default() as usize)
+                .map(|t| t.name.clone())
+                .unwrap_or_else(|| "?".to_string());
+            let quote = spot_meta
+                .tokens
+                .get(pair.tokens.get(1).copied().unwrap_or_default() as usize)
+                .map(|t| t.name.clone())
+                .unwrap_or_else(|| "?".to_string());
+            let ctx = ctx_map.get(&pair.name);
+            spot_markets.push(MarketRow {
+                coin: pair.name.clone(),
+                symbol: asset_index::spot_pair_symbol(&spot_meta, pair)
+                    .unwrap_or_else(|| pair.name.clone()),
+                pair_name: format!("[Spot] {base}/{quote}"),
+                price: ctx
+                    .map(|c| c.mark_px.clone())
+                    .unwrap_or_else(|| "?".to_string()),
+                price_change: ctx.and_then(|c| calculate_price_change(&c.mark_px, &c.prev_day_px)),
+                volume_usd: ctx
+                    .map(|c| format_volume(Some(&c.day_ntl_vlm)))
+                    .unwrap_or_else(|| "N/A".to_string()),
+                funding: None,
+                open_interest: None,
+            });
+        }
+    }
+
+    Ok((perp_markets, spot_markets))
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DexRow {
+    /// Empty for the main dex
+    name: String,
+    index: usize,
+    collateral_token: u64,
+    collateral: String,
+    markets: usize,
+}
+
+async fn markets_dexs(api: &HlApi, output_opts: OutputOptions) -> Result<()> {
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let dexs: Vec<DexRow> = all_perp_metas
+        .iter()
+        .enumerate()
+        .filter_map(|(index, meta)| {
+            let name = asset_index::perp_dex_name(index, meta)?;
+            let collateral = spot_meta
+                .tokens
+                .iter()
+                .find(|t| u64::from(t.index) == meta.collateral_token)
+                .map(|t| t.name.clone())
+                .unwrap_or_else(|| "?".to_string());
+            Some(DexRow {
+                name,
+                index,
+                collateral_token: meta.collateral_token,
+                collateral,
+                markets: meta.universe.iter().filter(|a| !a.is_delisted).count(),
+            })
+        })
+        .collect();
+
+    if output_opts.json {
+        output::print_json_pretty(&dexs)?;
+        return Ok(());
+    }
+
+    println!(
+        "{}",
+        output::style_header(format!("Perp Dexes ({}):", dexs.len()))
+    );
+    let rows = dexs
+        .iter()
+        .map(|d| {
+            vec![
+                if d.name.is_empty() {
+                    output::style_muted("(main)")
+                } else {
+                    d.name.clone()
+                },
+                d.index.to_string(),
+                d.collateral.clone(),
+                d.markets.to_string(),
+            ]
+        })
+        .collect();
+    output::print_table_with_columns(
+        &[
+            output::TableColumn::left("Dex"),
+            output::TableColumn::right("Index"),
+            output::TableColumn::left("Collateral"),
+            output::TableColumn::right("Markets"),
+        ],
+        rows,
+    );
+    Ok(())
+}
+
+async fn markets_prices(
+    api: &HlApi,
+    args: MarketsPricesArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    if args.watch {
+        if !output_opts.json {
+            watch::hide_cursor();
+        }
+        let interval = tokio::time::interval(std::time::Duration::from_millis(500));
+        tokio::pin!(interval);
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    match api.all_mids().await {
+                        Ok(mids) => {
+                            if output_opts.json {
+                                output::print_json_line(&serde_json::json!({
+                                    "mids": mids,
+                                    "timestamp": chrono::Utc::now().to_rfc3339(),
+                                }))?;
+                            } else {
+                                watch::clear_screen();
+                                println!("Mid Prices ({}):", watch::format_timestamp());
+                                let mut rows: Vec<Vec<String>> = mids.iter().map(|(k,v)| vec![k.clone(), v.clone()]).collect();
+                                rows.sort_by(|a,b| a[0].cmp(&b[0]));
+                                output::print_table(&["Coin","Price"], rows);
+                            }
+                        }
+                        Err(e) => {
+                            if !output_opts.json {
+                                watch::clear_screen();
+                                println!("Mid Prices ({}):", watch::format_timestamp());
+                                println!("Error: {e}");
+                                println!("Reconnecting...");
+                            } else {
+                                output::print_error(e.to_string());
+                            }
+                        }
+                    }
+                }
+                _ = &mut ctrl_c => break,
+            }
+        }
+
+        if !output_opts.json {
+            watch::show_cursor();
+        }
+        return Ok(());
+    }
+
+    let mids = api.all_mids().await?;
+    if output_opts.json {
+        output::print_json_pretty(&mids)?;
+        return Ok(());
+    }
+
+    let mut rows: Vec<Vec<String>> = mids
+        .iter()
+        .map(|(k, v)| vec![k.clone(), v.clone()])
+        .collect();
+    rows.sort_by(|a, b| a[0].cmp(&b[0]));
+    output::print_table(&["Coin", "Price"], rows);
+    Ok(())
+}
+
+/// Resolve a coin argument (`BTC`, `xyz:TSLA`, `HYPE/USDC`, `@107`) to the exchange's
+/// coin name.
+async fn resolve_coin(api: &HlApi, coin: &str) -> Result<String> {
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    asset_index::resolve_coin(&all_perp_metas, &spot_meta, coin)
+}
+
+async fn run_asset(
+    cmd: AssetCommand,
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    match cmd {
+        AssetCommand::Price(args) => asset_price(api, args, output_opts).await,
+        AssetCommand::Book(args) => asset_book(api, args, output_opts).await,
+        AssetCommand::Leverage(args) => asset_leverage(cfg, api, args, output_opts).await,
+        AssetCommand::Funding(args) => asset_funding(api, args, output_opts).await,
+    }
+}
+
+async fn asset_price(api: &HlApi, args: AssetPriceArgs, output_opts: OutputOptions) -> Result<()> {
+    let coin = resolve_coin(api, &args.coin).await?;
+    // The server only caches main-dex mids.
+    let dex = asset_index::coin_dex(&coin);
+
+    if args.watch {
+        if !output_opts.json {
+            watch::hide_cursor();
+        }
+
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        let mut price = "-".to_string();
+        let mut last_updated = watch::format_timestamp();
+
+        let render = |price: &str, last_updated: &str| {
+            watch::clear_screen();
+            println!(
+                "{}",
+                output::format_watch_header(format!("{coin} Price"), last_updated)
+            );
+            println!();
+            println!(
+                "{}: {}",
+                output::style_header(&coin),
+                output::style_bold(price)
+            );
+            println!();
+            println!("{}", output::style_muted("Press Ctrl+C to exit"));
+        };
+
+        let mut server_client = match dex {
+            None => ServerClient::try_connect().await?,
+            Some(_) => None,
+        };
+
+        if !output_opts.json {
+            render(&price, &last_updated);
+        }
+
+        if let Some(c) = server_client.as_mut() {
+            let interval = tokio::time::interval(std::time::Duration::from_millis(500));
+            tokio::pin!(interval);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        match c.get_prices(None).await {
+                            Ok(cached) => {
+                                if let Some(next) = cached.data.get(&coin) {
+                                    price = next.clone();
+                                    last_updated = watch::format_timestamp();
+                                    if output_opts.json {
+                                        output::print_json_line(&serde_json::json!({
+                                            "coin": coin,
+                                            "price": price,
+                                            "timestamp": chrono::Utc::now().to_rfc3339(),
+                                        }))?;
+                                    } else {
+                                        render(&price, &last_updated);
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                if !output_opts.json {
+                                    watch::clear_screen();
+                                    println!("{}", output::style_loss(format!("Error: {e}")));
+                                    println!("{}", output::style_muted("Reconnecting..."));
+                                } else {
+                                    output::print_error(e.to_string());
+                                }
+                            }
+                        }
+                    }
+                    _ = &mut ctrl_c => break,
+                }
+            }
+        } else {
+            'outer: loop {
+                let mut client = match ws::WsClient::connect(api.testnet).await {
+                    Ok(c) => c,
+                    Err(e) => {
+                        if !output_opts.json {
+                            watch::clear_screen();
+                            println!("{}", output::style_loss(format!("Error: {e}")));
+                            println!("{}", output::style_muted("Reconnecting..."));
+                        } else {
+                            output::print_error(e.to_string());
+                        }
+                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                        continue;
+                    }
+                };
+
+                if let Err(e) = client.subscribe(ws::sub_all_mids_all_dexs()).await {
+                    if !output_opts.json {
+                        watch::clear_screen();
+                        println!("{}", output::style_loss(format!("Error: {e}")));
+                        println!("{}", output::style_muted("Reconnecting..."));
+                    } else {
+                        output::print_error(e.to_string());
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    continue;
+                }
+
+                loop {
+                    tokio::select! {
+                        msg = client.next_json() => {
+                            let msg = match msg {
+                                Ok(Some(m)) => m,
+                                Ok(None) => break,
+                                Err(e) => {
+                                    if !output_opts.json {
+                                        watch::clear_screen();
+                                        println!("{}", output::style_loss(format!("Error: {e}")));
+                                        println!("{}", output::style_muted("Reconnecting..."));
+                                    } else {
+                                        output::print_error(e.to_string());
+                                    }
+                                    break;
+                                }
+                            };
+
+                            let Ok(channel) = ws::WsClient::channel(&msg) else {
+                                continue;
+                            };
+                            if channel == "allMids" {
+                                let Ok(data) = ws::WsClient::data(&msg) else {
+                                    continue;
+                                };
+                                let Some(mids) = data.get("mids").and_then(|m| m.as_object()) else {
+                                    continue;
+                                };
+                                if let Some(next) = mids.get(&coin).and_then(|v| v.as_str()) {
+                                    price = next.to_string();
+                                    last_updated = watch::format_timestamp();
+                                    if output_opts.json {
+                                        output::print_json_line(&serde_json::json!({
+                                            "coin": coin,
+                                            "price": price,
+                                            "timestamp": chrono::Utc::now().to_rfc3339(),
+                                        }))?;
+                                    } else {
+                                        render(&price, &last_updated);
+                                    }
+                                }
+                            }
+                        }
+                        _ = &mut ctrl_c => break 'outer,
+                    }
+                }
+
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            }
+        }
+
+        if !output_opts.json {
+            watch::show_cursor();
+        }
+        return Ok(());
+    }
+
+    let server_client = match dex {
+        None => ServerClient::try_connect().await?,
+        Some(_) => None,
+    };
+    let mids = if let Some(mut client) = server_client {
+        match client.get_prices(None).await {
+            Ok(cached) => cached.data,
+            Err(_) => api.all_mids().await?,
+        }
+    } else {
+        api.all_mids_for_dex(dex).await?
+    };
+    let price = mids
+        .get(&coin)
+        .cloned()
+        .ok_or_else(|| anyhow!("Coin not found: {coin}"))?;
+
+    if output_opts.json {
+        output::print_json_pretty(&serde_json::json!({ "coin": coin, "price": price }))?;
+        return Ok(());
+    }
+
+    println!(
+        "{}: {}",
+        output::style_header(&coin),
+        output::style_bold(price)
+    );
+    Ok(())
+}
+
+async fn asset_book(api: &HlApi, args: AssetBookArgs, output_opts: OutputOptions) -> Result<()> {
+    let coin = resolve_coin(api, &args.coin).await?;
+
+    const MAX_LEVELS: usize = 10;
+    const BAR_WIDTH: usize = 20;
+    const PRICE_WIDTH: usize = 12;
+    const SIZE_WIDTH: usize = 12;
+    const ORDERS_WIDTH: usize = 4;
+
+    fn pad_end(value: &str, width: usize) -> String {
+        let len = value.chars().count();
+        if len >= width {
+            return value.to_string();
+        }
+        format!("{value}{}", " ".repeat(width - len))
+    }
+
+    fn create_depth_bar(ratio: f64, width: usize) -> String {
+        let ratio = ratio.clamp(0.0, 1.0);
+        let filled = ((ratio * width as f64).round() as usize).max(1).min(width);
+        "█".repeat(filled)
+    }
+
+    let render = |book: &hyperliquid_cli::hl_api::L2Book, is_watch: bool| {
+        let bids = book.levels.first().cloned().unwrap_or_default();
+        let asks = book.levels.get(1).cloned().unwrap_or_default();
+
+        if is_watch {
+            println!(
+                "{}",
+                output::format_watch_header(
+                    format!("{coin} Order Book"),
+                    watch::format_timestamp()
+                )
+            );
+        } else {
+            println!("{}", output::style_header(format!("{coin} Order Book")));
+        }
+        println!();
+
+        // Header
+        println!(
+            "{} {} {} {}",
+            output::style_muted(pad_end("price", PRICE_WIDTH)),
+            output::style_muted(pad_end("size", SIZE_WIDTH)),
+            output::style_muted(pad_end("#", ORDERS_WIDTH)),
+            output::style_muted(pad_end("depth", BAR_WIDTH)),
+        );
+
+        let display_bids: Vec<_> = bids.into_iter().take(MAX_LEVELS).collect();
+        let asks_to_process: Vec<_> = asks.into_iter().take(MAX_LEVELS).collect();
+
+        // Asks: cumulative from best ask (lowest) then reverse for display.
+        let mut ask_cumulative = 0.0_f64;
+        let mut asks_with_cumulative: Vec<(hyperliquid_cli::hl_api::BookLevel, f64)> =
+            asks_to_process
+                .into_iter()
+                .map(|level| {
+                    ask_cumulative += level.sz.parse::<f64>().unwrap_or(0.0);
+                    (level, ask_cumulative)
+                })
+                .collect();
+        asks_with_cumulative.reverse();
+
+        // Bids: cumulative in display order (best bid first).
+        let mut bid_cumulative = 0.0_f64;
+        let bids_with_cumulative: Vec<(hyperliquid_cli::hl_api::BookLevel, f64)> = display_bids
+            .into_iter()
+            .map(|level| {
+                bid_cumulative += level.sz.parse::<f64>().unwrap_or(0.0);
+                (level, bid_cumulative)
+            })
+            .collect();
+
+        let max_cumulative = asks_with_cumulative
+            .first()
+            .map(|(_, c)| *c)
+            .unwrap_or(0.0)
+            .max(bids_with_cumulative.last().map(|(_, c)| *c).unwrap_or(0.0));
+
+        if asks_with_cumulative.is_empty() {
+            println!("{}", output::style_muted("No asks"));
+        }
+
+        for (level, cumulative) in &asks_with_cumulative {
+            let bar = if max_cumulative > 0.0 {
+                create_depth_bar(*cumulative / max_cumulative, BAR_WIDTH)
+            } else {
+                create_depth_bar(0.0, BAR_WIDTH)
+            };
+            println!(
+                "{} {} {} {}",
+                output::style_loss(pad_end(&level.px, PRICE_WIDTH)),
+                pad_end(&level.sz, SIZE_WIDTH),
+                output::style_muted(pad_end(&level.n.to_string(), ORDERS_WIDTH)),
+                output::style_loss(bar)
+            );
+        }
+
+        if let (Some((best_ask, _)), Some((best_bid, _))) =
+            (asks_with_cumulative.last(), bids_with_cumulative.first())
+            && let (Ok(ask_px), Ok(bid_px)) =
+                (best_ask.px.parse::<f64>(), best_bid.px.parse::<f64>())
+        {
+            let spread = ask_px - bid_px;
+            let total_width = PRICE_WIDTH + SIZE_WIDTH + ORDERS_WIDTH + BAR_WIDTH + 6;
+            let side = "─".repeat(total_width / 2);
+            println!(
+                "{}",
+                output::style_warning(format!("{side} spread: {spread:.2} {side}"))
+            );
+        }
+
+        if bids_with_cumulative.is_empty() {
+            println!("{}", output::style_muted("No bids"));
+        }
+
+        for (level, cumulative) in &bids_with_cumulative {
+            let bar = if max_cumulative > 0.0 {
+                create_depth_bar(*cumulative / max_cumulative, BAR_WIDTH)
+            } else {
+                create_depth_bar(0.0, BAR_WIDTH)
+            };
+            println!(
+                "{} {} {} {}",
+                output::style_profit(pad_end(&level.px, PRICE_WIDTH)),
+                pad_end(&level.sz, SIZE_WIDTH),
+                output::style_muted(pad_end(&level.n.to_string(), ORDERS_WIDTH)),
+                output::style_profit(bar)
+            );
+        }
+
+        if is_watch {
+            println!();
+            println!("{}", output::style_muted("Press Ctrl+C to exit"));
+        }
+    };
+
+    if args.watch {
+        if !output_opts.json {
+            watch::hide_cursor();
+        }
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        'outer: loop {
+            let mut client = match ws::WsClient::connect(api.testnet).await {
+                Ok(c) => c,
+                Err(e) => {
+                    if !output_opts.json {
+                        watch::clear_screen();
+                        println!("{}", output::style_loss(format!("Error: {e}")));
+                        println!("{}", output::style_muted("Reconnecting..."));
+                    } else {
+                        output::print_error(e.to_string());
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    continue;
+                }
+            };
+
+            if let Err(e) = client.subscribe(ws::sub_l2_book(&coin)).await {
+                if !output_opts.json {
+                    watch::clear_screen();
+                    println!("{}", output::style_loss(format!("Error: {e}")));
+                    println!("{}", output::style_muted("Reconnecting..."));
+                } else {
+                    output::print_error(e.to_string());
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                continue;
+            }
+
+            loop {
+                tokio::select! {
+                    msg = client.next_json() => {
+                        let msg = match msg {
+                            Ok(Some(m)) => m,
+                            Ok(None) => break,
+                            Err(e) => {
+                                if !output_opts.json {
+                                    watch::clear_screen();
+                                    println!("{}", output::style_loss(format!("Error: {e}")));
+                                    println!("{}", output::style_muted("Reconnecting..."));
+                                } else {
+                                    output::print_error(e.to_string());
+                                }
+                                break;
+                            }
+                        };
+
+                        let Ok(channel) = ws::WsClient::channel(&msg) else {
+                            continue;
+                        };
+                        if channel == "l2Book" {
+                            let Ok(data) = ws::WsClient::data(&msg) else {
+                                continue;
+                            };
+                            let Ok(book) = serde_json::from_value::<hyperliquid_cli::hl_api::L2Book>(data.clone()) else {
+                                continue;
+                            };
+
+                            if output_opts.json {
+                                output::print_json_line(&serde_json::json!({
+                                    "coin": book.coin,
+                                    "bids": book.levels.first().cloned().unwrap_or_default(),
+                                    "asks": book.levels.get(1).cloned().unwrap_or_default(),
+                                    "time": book.time,
+                                    "timestamp": chrono::Utc::now().to_rfc3339(),
+                                }))?;
+                            } else {
+                                watch::clear_screen();
+                                render(&book, true);
+                            }
+                        }
+                    }
+                    _ = &mut ctrl_c => break 'outer,
+                }
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+
+        if !output_opts.json {
+            watch::show_cursor();
+        }
+        return Ok(());
+    }
+
+    let book = api.l2_book(&coin).await?;
+    if output_opts.json {
+        output::print_json_pretty(&book)?;
+        return Ok(());
+    }
+
+    render(&book, false);
+    Ok(())
+}
+
+async fn asset_leverage(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: AssetLeverageArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let user = resolve_user_address(cfg, &args.user)?;
+    let coin = resolve_coin(api, &args.coin).await?;
+
+    let render = |info: &LeverageInfo, is_watch: bool| {
+        if is_watch {
+            println!(
+                "{}",
+                output::format_watch_header(
+                    format!("{} Leverage", info.coin),
+                    watch::format_timestamp()
+                )
+            );
+            println!();
+        }
+
+        println!(
+            "{}",
+            output::style_header(format!("{} Leverage Info", info.coin))
+        );
+        println!(
+            "Leverage: {}",
+            output::style_bold(format!(
+                "{}x {}",
+                info.leverage.value, info.leverage.leverage_type
+            ))
+        );
+        println!(
+            "Max Leverage: {}",
+            output::style_bold(format!("{}x", info.max_leverage))
+        );
+        println!(
+            "Mark Price: {}",
+            output::style_bold(format!("${}", info.mark_px))
+        );
+        println!();
+
+        println!("{}", output::style_header("Position"));
+        if let Some(pos) = &info.position {
+            println!("Size: {}", output::style_bold(&pos.size));
+            println!("Value: {}", output::style_bold(format!("${}", pos.value)));
+            println!(
+                "Liquidation Price: {}",
+                output::style_bold(
+                    pos.liquidation_px
+                        .as_deref()
+                        .map_or_else(|| "-".to_string(), |px| format!("${px}"))
+                )
+            );
+        } else {
+            println!("{}", output::style_muted("No position"));
+        }
+        println!();
+
+        println!("{}", output::style_header("Trading Capacity"));
+        println!(
+            "Available to Trade: {}",
+            output::style_profit_bold(format!(
+                "{} / {}",
+                info.available_to_trade[0], info.available_to_trade[1]
+            ))
+        );
+        println!(
+            "Max Trade Size: {}",
+            output::style_bold(format!(
+                "{} / {}",
+                info.max_trade_szs[0], info.max_trade_szs[1]
+            ))
+        );
+        println!();
+
+        println!("{}", output::style_header("Margin"));
+        println!(
+            "Account Value: {}",
+            output::style_bold(format!("${}", info.margin.account_value))
+        );
+        println!(
+            "Total Margin Used: {}",
+            output::style_bold(format!("${}", info.margin.total_margin_used))
+        );
+        println!(
+            "Available Margin: {}",
+            output::style_profit_bold(format!("${}", info.margin.available_margin))
+        );
+
+        if is_watch {
+            println!();
+            println!("{}", output::style_muted("Press Ctrl+C to exit"));
+        }
+    };
+
+    if args.watch {
+        let mut info = fetch_leverage_info(api, user, &coin).await?;
+
+        if !output_opts.json {
+            watch::hide_cursor();
+            watch::clear_screen();
+            println!("{}", output::style_muted("Loading..."));
+        }
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+
+        'outer: loop {
+            let mut client = match ws::WsClient::connect(api.testnet).await {
+                Ok(c) => c,
+                Err(e) => {
+                    if !output_opts.json {
+                        watch::clear_screen();
+                        println!("{}", output::style_loss(format!("Error: {e}")));
+                        println!("{}", output::style_muted("Reconnecting..."));
+                    } else {
+                        output::print_error(e.to_string());
+                    }
+                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                    continue;
+                }
+            };
+
+            if let Err(e) = client
+                .subscribe(ws::sub_active_asset_data(user, &coin))
+                .await
+            {
+                if !output_opts.json {
+                    watch::clear_screen();
+                    println!("{}", output::style_loss(format!("Error: {e}")));
+                    println!("{}", output::style_muted("Reconnecting..."));
+                } else {
+                    output::print_error(e.to_string());
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                continue;
+            }
+
+            loop {
+                tokio::select! {
+                    msg = client.next_json() => {
+                        let msg = match msg {
+                            Ok(Some(m)) => m,
+                            Ok(None) => break,
+                            Err(e) => {
+                                if !output_opts.json {
+                                    watch::clear_screen();
+                                    println!("{}", output::style_loss(format!("Error: {e}")));
+                                    println!("{}", output::style_muted("Reconnecting..."));
+                                } else {
+                                    output::print_error(e.to_string());
+                                }
+                                break;
+                            }
+                        };
+
+                        let Ok(channel) = ws::WsClient::channel(&msg) else {
+                            continue;
+                        };
+                        if channel != "activeAssetData" {
+                            continue;
+                        }
+
+                        let Ok(data) = ws::WsClient::data(&msg) else {
+                            continue;
+                        };
+                        let Ok(active) = serde_json::from_value::<hyperliquid_cli::hl_api::ActiveAssetData>(data.clone()) else {
+                            continue;
+                        };
+
+                        info.coin = active.coin;
+                        info.leverage = active.leverage;
+                        info.max_trade_szs = active.max_trade_szs;
+                        info.available_to_trade = active.available_to_trade;
+                        info.mark_px = active.mark_px;
+
+                        if output_opts.json {
+                            let mut v = serde_json::to_value(&info)?;
+                            if let Some(obj) = v.as_object_mut() {
+                                obj.insert(
+                                    "timestamp".to_string(),
+                                    serde_json::Value::String(chrono::Utc::now().to_rfc3339()),
+                                );
+                            }
+                            output::print_json_line(&v)?;
+                        } else {
+                            watch::clear_screen();
+                            render(&info, true);
+                        }
+                    }
+                    _ = &mut ctrl_c => break 'outer,
+                }
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        }
+
+        if !output_opts.json {
+            watch::show_cursor();
+        }
+        return Ok(());
+    }
+
+    let info = fetch_leverage_info(api, user, &coin).await?;
+    if output_opts.json {
+        output::print_json_pretty(&info)?;
+        return Ok(());
+    }
+
+    render(&info, false);
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+struct LeveragePositionInfo {
+    size: String,
+    value: String,
+    #[serde(rename = "liquidationPx")]
+    liquidation_px: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+struct LeverageMarginInfo {
+    #[serde(rename = "accountValue")]
+    account_value: String,
+    #[serde(rename = "totalMarginUsed")]
+    total_margin_used: String,
+    #[serde(rename = "availableMargin")]
+    available_margin: String,
+}
+
+#[derive(serde::Serialize)]
+struct LeverageInfo {
+    coin: String,
+    leverage: hyperliquid_cli::hl_api::Leverage,
+    #[serde(rename = "maxLeverage")]
+    max_leverage: u32,
+    #[serde(rename = "markPx")]
+    mark_px: String,
+    #[serde(rename = "maxTradeSzs")]
+    max_trade_szs: [String; 2],
+    #[serde(rename = "availableToTrade")]
+    available_to_trade: [String; 2],
+    position: Option<LeveragePositionInfo>,
+    margin: LeverageMarginInfo,
+}
+
+/// Funding is paid every hour.
+const FUNDING_PERIODS_PER_YEAR: f64 = 24.0 * 365.0;
+
+async fn asset_funding(
+    api: &HlApi,
+    args: AssetFundingArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let coin = resolve_coin(api, &args.coin).await?;
+    if coin.starts_with('@') || coin.contains('/') {
+        return Err(anyhow!(
+            "{coin} is a spot market; funding only applies to perps"
+        ));
+    }
+    let (since, until) = parse_time_window(args.since.as_deref(), args.until.as_deref(), "7d")?;
+
+    let mut rates = fetch_time_pages(
+        since,
+        FUNDING_PAGE_SIZE,
+        |r: &FundingRate| r.time,
+        |start| api.funding_history(&coin, start, until),
+    )
+    .await?;
+    rates.sort_by_key(|r| std::cmp::Reverse(r.time));
+    let values: Vec<f64> = rates
+        .iter()
+        .filter_map(|r| r.funding_rate.parse::<f64>().ok())
+        .collect();
+    let average = (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64);
+
+    if output_opts.json {
+        let rates: Vec<serde_json::Value> = rates
+            .iter()
+            .map(|r| {
+                let rate = r.funding_rate.parse::<f64>().unwrap_or(0.0);
+                serde_json::json!({
+                    "time": r.time,
+                    "fundingRate": r.funding_rate,
+                    "premium": r.premium,
+                    "annualized": rate * FUNDING_PERIODS_PER_YEAR,
+                })
+            })
+            .collect();
+        return output::print_json_pretty(&serde_json::json!({
+            "coin": coin,
+            "rates": rates,
+            "average": average,
+            "averageAnnualized": average.map(|a| a * FUNDING_PERIODS_PER_YEAR),
+        }));
+    }
+    let Some(average) = average else {
+        println!("{}", output::style_muted("No funding history"));
+        return Ok(());
+    };
+
+    let rows: Vec<Vec<String>> = rates
+        .iter()
+        .map(|r| {
+            let rate = r.funding_rate.parse::<f64>().unwrap_or(0.0);
+            let premium = r.premium.parse::<f64>().unwrap_or(0.0) * 100.0;
+            vec![
+                format_order_timestamp(r.time as u64),
+                style_signed(rate, format!("{:.4}%", rate * 100.0)),
+                format!("{premium:.4}%"),
+                style_signed(
+                    rate,
+                    format!("{:.2}%", rate * FUNDING_PERIODS_PER_YEAR * 100.0),
+                ),
+            ]
+        })
+        .collect();
+    output::print_table_with_columns(
+        &[
+            output::TableColumn::left("Time"),
+            output::TableColumn::right("Rate (1h)"),
+            output::TableColumn::right("Premium"),
+            output::TableColumn::right("Annualized"),
+        ],
+        rows,
+    );
+    println!();
+    println!(
+        "{coin} average over {} hours: {} per hour, {} annualized (positive = longs pay shorts)",
+        values.len(),
+        style_signed(average, format!("{:.4}%", average * 100.0)),
+        style_signed(
+            average,
+            format!("{:.2}%", average * FUNDING_PERIODS_PER_YEAR * 100.0)
+        )
+    );
+    Ok(())
+}
+
+async fn fetch_leverage_info(api: &HlApi, user: Address, coin: &str) -> Result<LeverageInfo> {
+    let (active, clearinghouse, all_perp_metas) = tokio::try_join!(
+        api.active_asset_data(user, coin),
+        api.clearinghouse_state(user),
+        api.all_perp_metas(),
+    )?;
+
+    let mut max_leverage = 50;
+    for dex in &all_perp_metas {
+        if let Some(asset) = dex.universe.iter().find(|a| a.name == coin) {
+            max_leverage = asset.max_leverage;
+            break;
+        }
+    }
+
+    let position = clearinghouse
+        .asset_positions
+        .iter()
+        .map(|p| &p.position)
+        .find(|p| p.coin == coin && p.szi.parse::<f64>().unwrap_or(0.0) != 0.0)
+        .map(|p| LeveragePositionInfo {
+            size: p.szi.clone(),
+            value: p.position_value.clone(),
+            liquidation_px: p.liquidation_px.clone(),
+        });
+
+    let account_value_f = clearinghouse
+        .margin_summary
+        .account_value
+        .parse::<f64>()
+        .unwrap_or(0.0);
+    let total_margin_used_f = clearinghouse
+        .margin_summary
+        .total_margin_used
+        .parse::<f64>()
+        .unwrap_or(0.0);
+    let available_margin = (account_value_f - total_margin_used_f).max(0.0);
+
+    Ok(LeverageInfo {
+        coin: coin.to_string(),
+        leverage: active.leverage,
+        max_leverage,
+        mark_px: active.mark_px,
+        max_trade_szs: active.max_trade_szs,
+        available_to_trade: active.available_to_trade,
+        position,
+        margin: LeverageMarginInfo {
+            account_value: clearinghouse.margin_summary.account_value,
+            total_margin_used: clearinghouse.margin_summary.total_margin_used,
+            available_margin: format!("{available_margin:.2}"),
+        },
+    })
+}
+
+async fn run_order(
+    cmd: OrderCommand,
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    match cmd {
+        OrderCommand::Ls(args) => account_orders(cfg, api, args, output_opts).await,
+        OrderCommand::History(args) => order_history(cfg, api, args, output_opts).await,
+        OrderCommand::Status(args) => order_status(cfg, api, args, output_opts).await,
+        OrderCommand::Configure(args) => order_configure(api, args, output_opts).await,
+        OrderCommand::Limit(args) => order_limit(cfg, api, args, output_opts).await,
+        OrderCommand::Market(args) => order_market(cfg, api, args, output_opts).await,
+        OrderCommand::Stop(args) => order_trigger(cfg, api, args, TpSl::Sl, output_opts).await,
+        OrderCommand::Tp(args) => order_trigger(cfg, api, args, TpSl::Tp, output_opts).await,
+        OrderCommand::Scale(args) => order_scale(cfg, api, args, output_opts).await,
+        OrderCommand::Twap(args) => order_twap(cfg, api, args, output_opts).await,
+        OrderCommand::TwapCancel(args) => order_twap_cancel(cfg, api, args, output_opts).await,
+        OrderCommand::TwapWatch(args) => {
+            let user = resolve_user_address(cfg, &args.user)?;
+            let twap_id = validation::validate_positive_u64(&args.twap_id, "twap id")?;
+            let target = args
+                .size
+                .as_deref()
+                .map(|s| validation::validate_positive_number(s, "size"))
+                .transpose()?;
+            watch_twap_progress(api, user, twap_id, target, output_opts).await
+        }
+        OrderCommand::Chase(args) => order_chase(cfg, api, args, output_opts).await,
+        OrderCommand::Modify(args) => order_modify(cfg, api, args, output_opts).await,
+        OrderCommand::ModifyBatch(args) => order_modify_batch(cfg, api, args, output_opts).await,
+        OrderCommand::Cancel(args) => order_cancel(cfg, api, args, output_opts).await,
+        OrderCommand::CancelAll(args) => order_cancel_all(cfg, api, args, output_opts).await,
+        OrderCommand::Batch(args) => order_batch(cfg, api, args, output_opts).await,
+        OrderCommand::DeadMan(args) => order_dead_man(cfg, api, args, output_opts).await,
+        OrderCommand::Trail(args) => order_trail(cfg, api, args, output_opts).await,
+        OrderCommand::Close(args) => order_close(cfg, api, args, output_opts).await,
+        OrderCommand::Flatten(args) => order_flatten(cfg, api, args, output_opts).await,
+        OrderCommand::SetLeverage(args) => order_set_leverage(cfg, api, args, output_opts).await,
+        OrderCommand::Margin { command } => match command {
+            OrderMarginCommand::Add(args) => order_margin(cfg, api, args, true, output_opts).await,
+            OrderMarginCommand::Remove(args) => {
+                order_margin(cfg, api, args, false, output_opts).await
+            }
+        },
+    }
+}
+
+async fn order_configure(
+    api: &HlApi,
+    mut args: OrderConfigureArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    if let Some(coin) = &args.coin {
+        args.coin = Some(resolve_coin(api, coin).await?);
+    }
+    let defaults = args.order_defaults()?;
+    let limits = args.risk_limits()?;
+    let sets_defaults = defaults != order_config::OrderDefaults::default();
+    let sets_limits = limits != order_config::RiskLimits::default();
+    if let Some(alias) = &args.account
+        && db::get_account_by_alias(alias)?.is_none()
+    {
+        return Err(anyhow!("Account \"{alias}\" not found"));
+    }
+    for key in &args.unset {
+        let is_default = order_config::DEFAULT_KEYS.contains(&key.as_str());
+        let is_limit = order_config::RISK_KEYS.contains(&key.as_str());
+        if !is_default && !is_limit {
+            return Err(anyhow!(
+                "Unknown setting \"{key}\". Expected one of: {}, {}",
+                order_config::DEFAULT_KEYS.join(", "),
+                order_config::RISK_KEYS.join(", ")
+            ));
+        }
+        if is_limit && args.coin.is_some() {
+            return Err(anyhow!(
+                "{key} is a risk limit; risk limits are set per account, not per coin"
+            ));
+        }
+        if is_default && args.account.is_some() {
+            return Err(anyhow!(
+                "{key} is an order default; defaults are set per coin, not per account"
+            ));
+        }
+    }
+    if sets_limits && args.coin.is_some() {
+        return Err(anyhow!(
+            "Risk limits are set per account, not per coin; drop --coin"
+        ));
+    }
+    if sets_defaults && args.account.is_some() {
+        return Err(anyhow!(
+            "Order defaults are set per coin, not per account; drop --account"
+        ));
+    }
+
+    if !sets_defaults && !sets_limits && args.unset.is_empty() {
+        let cfg = order_config::load_order_config()?;
+        return print_order_config(&cfg, args.coin.as_deref(), output_opts);
+    }
+
+    let cfg = order_config::update_order_config(|cfg| {
+        let target = match &args.coin {
+            Some(coin) => cfg.coin_defaults_mut(coin),
+            None => &mut cfg.defaults,
+        };
+        *target = target.overridden_by(&defaults);
+        for key in &args.unset {
+            target.unset(key);
+        }
+
+        let target = match &args.account {
+            Some(alias) => cfg.accounts.entry(alias.clone()).or_default(),
+            None => &mut cfg.risk,
+        };
+        *target = target.overridden_by(&limits);
+        for key in &args.unset {
+            target.unset(key);
+        }
+    })?;
+
+    if args.show {
+        return print_order_config(&cfg, args.coin.as_deref(), output_opts);
+    }
+    if output_opts.json {
+        output::print_json_pretty(&cfg)?;
+    } else {
+        let scope = args
+            .coin
+            .as_deref()
+            .or(args.account.as_deref())
+            .map(|s| format!(" for {s}"))
+            .unwrap_or_default();
+        if sets_defaults {
+            output::print_success(format!("Order defaults updated{scope}"));
+        }
+        if sets_limits {
+            output::print_success(format!("Risk limits updated{scope}"));
+        }
+        if !args.unset.is_empty() {
+            output::print_success(format!("Unset {}{scope}", args.unset.join(", ")));
+        }
+    }
+    Ok(())
+}
+
+fn print_order_config(
+    cfg: &order_config::OrderConfig,
+    coin: Option<&str>,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    if let Some(coin) = coin {
+        let defaults = cfg.defaults_for(Some(coin));
+        if output_opts.json {
+            output::print_json_pretty(&serde_json::json!({
+                "coin": coin,
+                "defaults": defaults,
+            }))?;
+        } else {
+            output::print_success(format!("Order defaults for {coin}:"));
+            print_order_defaults(&defaults, cfg.slippage(Some(coin)));
+        }
+        return Ok(());
+    }
+
+    if output_opts.json {
+        return output::print_json_pretty(cfg);
+    }
+    output::print_success("Current configuration:");
+    print_order_defaults(&cfg.defaults, cfg.slippage(None));
+    for (coin, defaults) in &cfg.coins {
+        println!("  {coin}:");
+        for (name, value) in order_default_rows(defaults) {
+            if let Some(value) = value {
+                println!("    {name}: {value}");
+            }
+        }
+    }
+    print_risk_limits("Risk limits", &cfg.risk);
+    for (alias, limits) in &cfg.accounts {
+        print_risk_limits(&format!("Risk limits for {alias}"), limits);
+    }
+    Ok(())
+}
+
+fn order_default_rows(
+    defaults: &order_config::OrderDefaults,
+) -> [(&'static str, Option<String>); 5] {
+    [
+        ("Slippage", defaults.slippage.map(|s| format!("{s}%"))),
+        ("Time-in-force", defaults.tif.clone()),
+        ("Reduce-only", defaults.reduce_only.map(|r| r.to_string())),
+        ("Leverage", defaults.leverage.map(|l| format!("{l}x"))),
+        (
+            "Margin mode",
+            defaults.margin_mode.map(|m| m.as_str().to_string()),
+        ),
+    ]
+}
+
+/// Print effective defaults, filling in the built-in behavior for unset ones.
+fn print_order_defaults(defaults: &order_config::OrderDefaults, slippage: f64) {
+    for (name, value) in order_default_rows(defaults) {
+        let value = value.unwrap_or_else(|| match name {
+            "Slippage" => format!("{slippage}%"),
+            "Time-in-force" => "Gtc".to_string(),
+            "Reduce-only" => "false".to_string(),
+            _ => "account setting".to_string(),
+        });
+        println!("  {name}: {value}");
+    }
+}
+
+fn print_risk_limits(title: &str, limits: &order_config::RiskLimits) {
+    let usd = |v: Option<f64>| v.map(|v| format!("${v}"));
+    let coins = |v: &[String]| (!v.is_empty()).then(|| v.join(", "));
+    let rows = [
+        ("Max order", usd(limits.max_order_usd)),
+        ("Max position", usd(limits.max_position_usd)),
+        ("Max leverage", limits.max_leverage.map(|l| format!("{l}x"))),
+        ("Allowed coins", coins(&limits.allow_coins)),
+        ("Denied coins", coins(&limits.deny_coins)),
+        ("Confirm above", usd(limits.confirm_above_usd)),
+    ];
+    if rows.iter().all(|(_, value)| value.is_none()) {
+        return;
+    }
+    println!("  {title}:");
+    for (name, value) in rows {
+        if let Some(value) = value {
+            println!("    {name}: {value}");
+        }
+    }
+}
+
+/// Load the risk limits for the current account.
+fn account_risk_limits(cfg: &config::LoadedConfig) -> Result<order_config::RiskLimits> {
+    Ok(order_config::load_order_config()?
+        .risk_limits(cfg.account.as_ref().map(|a| a.alias.as_str())))
+}
+
+/// Positions and account value, if the limits need them for these orders.
+async fn fetch_exposure(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    limits: &order_config::RiskLimits,
+    orders: &[OrderIntent<'_>],
+) -> Result<Option<Exposure>> {
+    if !limits.needs_exposure() || orders.iter().all(|o| o.reduce_only || o.is_spot) {
+        return Ok(None);
+    }
+    let user = require_wallet_address(cfg)?;
+    let state = api.clearinghouse_state(user).await?;
+    let mut exposure = Exposure::from_state(&state);
+    let mut dexes: Vec<&str> = orders
+        .iter()
+        .filter_map(|o| asset_index::coin_dex(o.coin))
+        .collect();
+    dexes.sort_unstable();
+    dexes.dedup();
+    for dex in dexes {
+        let dex_state = api.clearinghouse_state_for_dex(user, Some(dex)).await?;
+        exposure.add_positions(&dex_state);
+    }
+    Ok(Some(exposure))
+}
+
+/// Ask before submitting orders above the confirmation threshold. Dry runs
+/// don't ask; the warning is returned for the preview instead. Without a
+/// terminal to ask on, the orders are refused.
+fn confirm_risk_threshold(
+    cfg: &config::LoadedConfig,
+    limits: &order_config::RiskLimits,
+    orders: &[OrderIntent<'_>],
+) -> Result<Option<String>> {
+    let notional = orders
+        .iter()
+        .filter(|o| !o.reduce_only)
+        .map(OrderIntent::notional)
+        .sum();
+    let Some(msg) = limits.confirmation(notional) else {
+        return Ok(None);
+    };
+    if cfg.dry_run {
+        Ok(Some(msg))
+    } else if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        Err(anyhow!(
+            "{msg}; confirm interactively or raise the threshold"
+        ))
+    } else if confirm_trade(cfg, &format!("{msg}. Submit anyway?"))? {
+        Ok(None)
+    } else {
+        Err(anyhow!(msg))
+    }
+}
+
+/// Check orders against the account's risk limits before signing. Returns
+/// the threshold warning a dry run should show.
+async fn check_risk_limits(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    orders: &[OrderIntent<'_>],
+) -> Result<Option<String>> {
+    let limits = account_risk_limits(cfg)?;
+    let exposure = fetch_exposure(cfg, api, &limits, orders).await?;
+    limits.check_orders(orders, exposure)?;
+    confirm_risk_threshold(cfg, &limits, orders)
+}
+
+/// The risk view of an order request for `coin`.
+fn order_intent<'a>(coin: &'a str, order: &OrderRequest, is_spot: bool) -> OrderIntent<'a> {
+    OrderIntent {
+        coin,
+        is_buy: order.is_buy,
+        size: order.sz.parse().unwrap_or(0.0),
+        price: order.limit_px.parse().unwrap_or(0.0),
+        reduce_only: order.reduce_only,
+        is_spot,
+    }
+}
+
+async fn order_limit(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: OrderLimitArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let (size, coin, price) = args.positionals()?;
+    let side = validation::validate_side_with_aliases(&args.side)?;
+    let sizing = OrderSizing::parse(size, &args.sizing)?;
+    let limit_px = validation::validate_positive_number(price, "price")?;
+    let is_buy = side == "buy";
+    let tp = parse_optional_price(args.tp.as_deref(), "take-profit price")?;
+    let sl = match attached_stop(&args.sizing, sizing) {
+        Some(stop) => Some(stop),
+        None => parse_optional_price(args.sl.as_deref(), "stop-loss price")?,
+    };
+    validate_bracket(is_buy, limit_px, tp, sl)?;
+
+    let cloid = resolve_cloid(args.cloid.as_deref())?;
+
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let coin = &asset_index::resolve_coin(&all_perp_metas, &spot_meta, coin)?;
+    let defaults = order_config::load_order_config()?.defaults_for(Some(coin));
+    let tif = validation::validate_tif(
+        args.tif
+            .as_deref()
+            .or(defaults.tif.as_deref())
+            .unwrap_or("Gtc"),
+    )?;
+    let reduce_only = args.reduce_only.resolve(&defaults);
+    let (leverage, margin_mode) = args.leverage.resolve(&defaults)?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, coin)?;
+    let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, coin)?;
+    let limit_px = precision.check_price(limit_px, "Price", args.strict)?;
+    let size = resolve_order_size(
+        cfg,
+        api,
+        coin,
+        is_buy,
+        sizing,
+        limit_px,
+        &precision,
+        args.strict,
+        output_opts,
+    )
+    .await?;
+    let tp = check_optional_price(&precision, tp, "Take-profit price", args.strict)?;
+    let sl = check_optional_price(&precision, sl, "Stop-loss price", args.strict)?;
+
+    let order = OrderRequest {
+        asset,
+        is_buy,
+        limit_px: precision.format_price(limit_px),
+        sz: precision.format_size(size),
+        reduce_only,
+        order_type: OrderType::Limit(Limit { tif }),
+        cloid: Some(cloid),
+    };
+
+    let warning =
+        check_risk_limits(cfg, api, &[order_intent(coin, &order, precision.is_spot)]).await?;
+    let mut preview = DryRun::default().warning(warning);
+    if !precision.is_spot && !reduce_only {
+        preview = apply_order_leverage(
+            cfg,
+            api,
+            coin,
+            asset,
+            leverage,
+            margin_mode,
+            preview,
+            output_opts,
+        )
+        .await?;
+    }
+
+    let slippage_pct = resolve_slippage_pct(None, Some(coin))?;
+    let (orders, legs) = build_bracket(order, tp, sl, slippage_pct, &precision);
+    submit_entry_orders(cfg, api, coin, orders, &legs, cloid, preview, output_opts).await
+}
+
+/// Switch `coin` to the requested leverage and margin mode before an order,
+/// if the account's current setting differs. A margin mode alone keeps the
+/// current leverage. Under `--dry-run` nothing is sent; the action is added to
+/// `preview` instead.
+#[allow(clippy::too_many_arguments)]
+async fn apply_order_leverage(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    coin: &str,
+    asset: u32,
+    leverage: Option<u32>,
+    margin_mode: Option<MarginMode>,
+    preview: DryRun,
+    output_opts: OutputOptions,
+) -> Result<DryRun> {
+    if leverage.is_none() && margin_mode.is_none() {
+        return Ok(preview);
+    }
+    let current = match account_address(cfg) {
+        Some(user) => Some(api.active_asset_data(user, coin).await?.leverage),
+        None => None,
+    };
+    let leverage = leverage
+        .or(current.as_ref().map(|l| l.value))
+        .ok_or_else(|| anyhow!("Pass --leverage along with the margin mode"))?;
+    let is_cross = match margin_mode {
+        Some(mode) => mode.is_cross(),
+        None => current.as_ref().is_none_or(|l| l.leverage_type == "cross"),
+    };
+    if let Some(current) = &current
+        && current.value == leverage
+        && (current.leverage_type == "cross") == is_cross
+    {
+        return Ok(preview);
+    }
+    account_risk_limits(cfg)?.check_leverage_setting(leverage)?;
+
+    if cfg.dry_run {
+        return preview.action(&Action::UpdateLeverage {
+            asset,
+            is_cross,
+            leverage,
+        });
+    }
+
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = L1Action::UpdateLeverage {
+        asset,
+        is_cross,
+        leverage,
+    }
+    .send(&exchange, wallet, cfg.vault_address())
+    .await?;
+    if let ExchangeResponse::Err(e) = resp {
+        return Err(anyhow!("Failed to set leverage for {coin}: {e}"));
+    }
+    if !output_opts.json {
+        output::print_success(format!(
+            "Leverage set to {leverage}x ({}) for {coin}",
+            if is_cross { "cross" } else { "isolated" }
+        ));
+    }
+    Ok(preview)
+}
+
+/// Sign and send an entry order (plus any bracket children) tagged with
+/// `cloid`, recording the cloid -> oid mapping locally. Under `--dry-run` the
+/// order is added to `preview` and printed instead.
+#[allow(clippy::too_many_arguments)]
+async fn submit_entry_orders(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    coin: &str,
+    orders: Vec<OrderRequest>,
+    legs: &[&str],
+    cloid: Uuid,
+    preview: DryRun,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let grouping = if legs.len() > 1 {
+        Grouping::NormalTpsl
+    } else {
+        Grouping::Na
+    };
+    if cfg.dry_run {
+        return preview
+            .action(&Action::Order { orders, grouping })?
+            .print(cfg, api, output_opts)
+            .await;
+    }
+
+    let user = require_wallet_address(cfg)?;
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let cloid = validation::format_cloid(&cloid);
+    // A cloid sent before may already have landed; the exchange would reject
+    // the resend, so report the existing order instead.
+    if db::get_client_order(&cloid)?.is_some()
+        && let Some(h) = api
+            .order_status(user, &OrderRef::Cloid(cloid.clone()))
+            .await?
+            .order
+    {
+        db::update_client_order(&cloid, Some(h.order.oid), client_order_status(&h.status))?;
+        if output_opts.json {
+            return output::print_json_pretty(&h);
+        }
+        output::print_success(format!(
+            "Order {cloid} was already placed as oid {} ({}); not sending it again",
+            h.order.oid,
+            style_order_status(&h.status)
+        ));
+        return Ok(());
+    }
+    db::record_client_order(&cloid, &format!("{user:#x}"), coin)?;
+    let resp = match grouping {
+        Grouping::NormalTpsl => {
+            exchange
+                .normal_tpsl(wallet, orders, cfg.vault_address())
+                .await?
+        }
+        Grouping::Na => {
+            exchange
+                .place_order(wallet, orders, cfg.vault_address())
+                .await?
+        }
+    };
+    record_client_order_outcome(&cloid, &resp)?;
+    if output_opts.json {
+        output::print_json_pretty(&resp)?;
+    } else {
+        print_exchange_response(&resp, legs);
+        println!(
+            "{}",
+            output::style_muted(format!("Client order ID: {cloid}"))
+        );
+    }
+    Ok(())
+}
+
+async fn order_market(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: OrderMarketArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let (size, coin) = args.positionals()?;
+    let side = validation::validate_side_with_aliases(&args.side)?;
+    let sizing = OrderSizing::parse(size, &args.sizing)?;
+    let is_buy = side == "buy";
+    let tp = parse_optional_price(args.tp.as_deref(), "take-profit price")?;
+    let sl = match attached_stop(&args.sizing, sizing) {
+        Some(stop) => Some(stop),
+        None => parse_optional_price(args.sl.as_deref(), "stop-loss price")?,
+    };
+    let cloid = resolve_cloid(args.cloid.as_deref())?;
+
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let coin = &asset_index::resolve_coin(&all_perp_metas, &spot_meta, coin)?;
+    let mids = api.all_mids_for_dex(asset_index::coin_dex(coin)).await?;
+    let defaults = order_config::load_order_config()?.defaults_for(Some(coin));
+    let reduce_only = args.reduce_only.resolve(&defaults);
+    let (leverage, margin_mode) = args.leverage.resolve(&defaults)?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, coin)?;
+    let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, coin)?;
+    let tp = check_optional_price(&precision, tp, "Take-profit price", args.strict)?;
+    let sl = check_optional_price(&precision, sl, "Stop-loss price", args.strict)?;
+
+    let mid_price = mid_price_for(&mids, coin)?;
+    validate_bracket(is_buy, mid_price, tp, sl)?;
+    let size = resolve_order_size(
+        cfg,
+        api,
+        coin,
+        is_buy,
+        sizing,
+        mid_price,
+        &precision,
+        args.strict,
+        output_opts,
+    )
+    .await?;
+
+    let slippage_pct = resolve_slippage_pct(args.slippage.as_deref(), Some(coin))?;
+
+    let mut preview = DryRun::default();
+    let limit_px = if args.book {
+        let book = api.l2_book(coin).await?;
+        let estimate = book::estimate_fill(&book, is_buy, size)?;
+        let expected_slippage = estimate.slippage_vs(mid_price);
+        if !output_opts.json {
+            println!(
+                "Estimated fill: {} {} @ avg {} (worst level {}, {} levels, {:.3}% from mid)",
+                precision.format_size(size),
+                coin,
+                precision.format_price(estimate.avg_px),
+                precision.format_price(estimate.worst_px),
+                estimate.levels,
+                expected_slippage * 100.0
+            );
+        }
+        if expected_slippage > slippage_pct {
+            let msg = format!(
+                "Expected slippage {:.3}% exceeds the {:.3}% limit",
+                expected_slippage * 100.0,
+                slippage_pct * 100.0
+            );
+            if cfg.dry_run {
+                preview = preview.warning(Some(msg));
+            } else if output_opts.json || !confirm_trade(cfg, &format!("{msg}. Submit anyway?"))? {
+                return Err(anyhow!(msg));
+            }
+        }
+        estimate.limit_px(is_buy, slippage_pct)
+    } else if is_buy {
+        mid_price * (1.0 + slippage_pct)
+    } else {
+        mid_price * (1.0 - slippage_pct)
+    };
+
+    let order = OrderRequest {
+        asset,
+        is_buy,
+        limit_px: precision.format_price(limit_px),
+        sz: precision.format_size(size),
+        reduce_only,
+        order_type: OrderType::Limit(Limit {
+            tif: hyperliquid::types::exchange::request::Tif::Ioc,
+        }),
+        cloid: Some(cloid),
+    };
+    let warning =
+        check_risk_limits(cfg, api, &[order_intent(coin, &order, precision.is_spot)]).await?;
+    preview = preview.warning(warning);
+    if !precision.is_spot && !reduce_only {
+        preview = apply_order_leverage(
+            cfg,
+            api,
+            coin,
+            asset,
+            leverage,
+            margin_mode,
+            preview,
+            output_opts,
+        )
+        .await?;
+    }
+
+    let (orders, legs) = build_bracket(order, tp, sl, slippage_pct, &precision);
+    submit_entry_orders(cfg, api, coin, orders, &legs, cloid, preview, output_opts).await
+}
+
+/// How the size of an entry order was requested.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum OrderSizing {
+    Coin(f64),
+    Usd(f64),
+    EquityPct(f64),
+    /// Lose `pct` percent of equity if `stop` is hit.
+    RiskPct {
+        pct: f64,
+        stop: f64,
+    },
+    /// Lose `usd` if `stop` is hit.
+    RiskUsd {
+        usd: f64,
+        stop: f64,
+    },
+}
+
+impl OrderSizing {
+    fn parse(size: Option<&str>, args: &SizingArgs) -> Result<Self> {
+        let stop = parse_optional_price(args.stop.as_deref(), "stop price")?;
+        if let Some(stop) = stop {
+            if let Some(risk) = &args.risk {
+                let pct = validation::validate_positive_number(
+                    risk.trim().trim_end_matches('%'),
+                    "risk",
+                )?;
+                if pct > 100.0 {
+                    return Err(anyhow!("risk must be between 0% and 100%"));
+                }
+                return Ok(Self::RiskPct { pct, stop });
+            }
+            if let Some(usd) = &args.risk_usd {
+                let usd = validation::validate_positive_number(usd, "risk-usd")?;
+                return Ok(Self::RiskUsd { usd, stop });
+            }
+            return Err(anyhow!("--stop requires --risk or --risk-usd"));
+        }
+        if let Some(usd) = &args.usd {
+            return Ok(Self::Usd(validation::validate_positive_number(usd, "usd")?));
+        }
+        if let Some(pct) = &args.equity_pct {
+            let pct = validation::validate_positive_number(pct, "equity-pct")?;
+            if pct > 100.0 {
+                return Err(anyhow!("equity-pct must be between 0 and 100"));
+            }
+            return Ok(Self::EquityPct(pct));
+        }
+        let size = size.ok_or_else(|| anyhow!("Order size is required"))?;
+        Ok(Self::Coin(validation::validate_positive_number(
+            size, "size",
+        )?))
+    }
+
+    fn stop(self) -> Option<f64> {
+        match self {
+            Self::RiskPct { stop, .. } | Self::RiskUsd { stop, .. } => Some(stop),
+            _ => None,
+        }
+    }
+
+    fn uses_equity(self) -> bool {
+        matches!(self, Self::EquityPct(_) | Self::RiskPct { .. })
+    }
+
+    /// USD lost if the stop is hit, for risk-based sizing.
+    fn risk_amount(self, account_value: Option<f64>) -> Result<Option<f64>> {
+        match self {
+            Self::RiskPct { pct, .. } => Ok(Some(positive_equity(account_value)? * pct / 100.0)),
+            Self::RiskUsd { usd, .. } => Ok(Some(usd)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Coin size before rounding, converting notional sizing at `reference_px`.
+    ///
+    /// Risk-based sizing charges `fee_rate` on both the entry and the stop exit,
+    /// so the loss per coin is the stop distance plus fees on both fills.
+    fn coin_size(
+        self,
+        reference_px: f64,
+        account_value: Option<f64>,
+        fee_rate: f64,
+    ) -> Result<f64> {
+        match self {
+            Self::Coin(size) => Ok(size),
+            Self::Usd(usd) => Ok(usd / reference_px),
+            Self::EquityPct(pct) => {
+                Ok(positive_equity(account_value)? * pct / 100.0 / reference_px)
+            }
+            Self::RiskPct { stop, .. } | Self::RiskUsd { stop, .. } => {
+                if stop == reference_px {
+                    return Err(anyhow!("Stop price must differ from the entry price"));
+                }
+                let loss_per_coin = (reference_px - stop).abs() + fee_rate * (reference_px + stop);
+                let risk = self.risk_amount(account_value)?.unwrap_or_default();
+                Ok(risk / loss_per_coin)
+            }
+        }
+    }
+}
+
+/// The risk stop, when `--attach-stop` asks for it to be placed as the stop-loss leg.
+fn attached_stop(args: &SizingArgs, sizing: OrderSizing) -> Option<f64> {
+    if args.attach_stop {
+        sizing.stop()
+    } else {
+        None
+    }
+}
+
+fn positive_equity(account_value: Option<f64>) -> Result<f64> {
+    let equity = account_value.ok_or_else(|| anyhow!("Account value is unavailable"))?;
+    if equity <= 0.0 {
+        return Err(anyhow!("Account has no equity to size against"));
+    }
+    Ok(equity)
+}
+
+/// Turn the requested sizing into a coin size rounded to `sz_decimals`.
+/// Notional sizing is always rounded; `strict` only applies to coin sizes.
+#[allow(clippy::too_many_arguments)]
+async fn resolve_order_size(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    coin: &str,
+    is_buy: bool,
+    sizing: OrderSizing,
+    reference_px: f64,
+    precision: &AssetPrecision,
+    strict: bool,
+    output_opts: OutputOptions,
+) -> Result<f64> {
+    if let OrderSizing::Coin(size) = sizing {
+        return precision.check_size(size, strict);
+    }
+    if let Some(stop) = sizing.stop() {
+        validate_bracket(is_buy, reference_px, None, Some(stop))?;
+    }
+
+    let account_value = if sizing.uses_equity() {
+        let user = require_wallet_address(cfg)?;
+        let dex = asset_index::coin_dex(coin);
+        let state = api.clearinghouse_state_for_dex(user, dex).await?;
+        Some(
+            state
+                .margin_summary
+                .account_value
+                .parse::<f64>()
+                .map_err(|_| anyhow!("Invalid account value"))?,
+        )
+    } else {
+        None
+    };
+    // Assume both fills pay the taker rate so the risk is never understated.
+    let fee_rate = if sizing.stop().is_some() {
+        let user = require_wallet_address(cfg)?;
+        let fees = api.user_fees(user).await?;
+        let rate = if precision.is_spot {
+            &fees.user_spot_cross_rate
+        } else {
+            &fees.user_cross_rate
+        };
+        rate.parse::<f64>()
+            .map_err(|_| anyhow!("Invalid fee rate: {rate}"))?
+    } else {
+        0.0
+    };
+    let size = sizing.coin_size(reference_px, account_value, fee_rate)?;
+    // Rounding a risk-based size up could lose more than the budget at the stop.
+    let size = if sizing.stop().is_some() {
+        precision.floor_size(size)
+    } else {
+        size
+    };
+    let size = precision.check_size(size, false)?;
+    if !output_opts.json {
+        println!(
+            "Size: {} {coin} (~${:.2} notional at {})",
+            precision.format_size(size),
+            size * reference_px,
+            precision.format_price(reference_px)
+        );
+        if let (Some(stop), Some(risk)) = (sizing.stop(), sizing.risk_amount(account_value)?) {
+            println!(
+                "Risk: ${risk:.2} to stop {} (fee rate {:.4}%)",
+                precision.format_price(stop),
+                fee_rate * 100.0
+            );
+        }
+    }
+    Ok(size)
+}
+
+/// Use the `--cloid` value, or generate a random client order ID.
+fn resolve_cloid(value: Option<&str>) -> Result<Uuid> {
+    match value {
+        Some(v) => validation::validate_cloid(v),
+        None => Ok(Uuid::new_v4()),
     }
-    output::print_table_with_columns(&table_columns, rows);
-    Ok(())
 }
 
-async fn fetch_market_data(
-    client: &mut ServerClient,
-    spot_only: bool,
-    perp_only: bool,
-) -> Result<(Vec<MarketRow>, Vec<MarketRow>)> {
-    let spot_meta = client.get_spot_meta().await?.data;
+/// Exchange order ID and `client_orders` status for the first (entry) order
+/// of a placement response.
+fn entry_order_outcome(resp: &ExchangeResponse) -> (Option<u64>, &'static str) {
+    let ExchangeResponse::Ok(data) = resp else {
+        return (None, "rejected");
+    };
+    let status = match &data.data {
+        Some(StatusType::Statuses(statuses)) => statuses.first(),
+        Some(StatusType::Status(status)) => Some(status),
+        _ => None,
+    };
+    status.map_or((None, "pending"), client_order_outcome)
+}
 
-    let mut perp_markets = Vec::new();
-    if !spot_only {
-        let all_perp_metas = client.get_perp_meta().await?.data;
-        let asset_ctxs = client.get_asset_ctxs().await?.data;
+fn client_order_outcome(status: &Status) -> (Option<u64>, &'static str) {
+    match status {
+        Status::Resting(r) => (Some(r.oid), "resting"),
+        Status::Filled(f) => (Some(f.oid), "filled"),
+        Status::Error(_) => (None, "rejected"),
+        _ => (None, "pending"),
+    }
+}
 
-        for (meta_index, perp_meta) in all_perp_metas.iter().enumerate() {
-            let dex = if meta_index == 0 {
-                Some(String::new())
-            } else {
-                perp_meta
-                    .universe
-                    .first()
-                    .and_then(|m| m.name.split_once(':').map(|(p, _)| p.to_string()))
-            };
+/// The local client order status for an exchange order status.
+fn client_order_status(status: &str) -> &str {
+    match order_status_kind(status) {
+        "open" => "resting",
+        "filled" => "filled",
+        "canceled" => "cancelled",
+        "rejected" => "rejected",
+        _ => status,
+    }
+}
 
-            // Skip unexpected meta objects with no universe.
-            let Some(dex) = dex else {
-                continue;
-            };
+fn record_client_order_outcome(cloid: &str, resp: &ExchangeResponse) -> Result<()> {
+    let (oid, status) = entry_order_outcome(resp);
+    db::update_client_order(cloid, oid, status)
+}
 
-            let collateral = spot_meta
-                .tokens
-                .get(perp_meta.collateral_token as usize)
-                .map(|t| t.name.clone())
-                .unwrap_or_else(|| "?".to_string());
-            let dex_suffix = if meta_index == 0 {
-                String::new()
-            } else {
-                format!(" @{dex}")
-            };
-            let dex_ctxs = asset_ctxs
-                .ctxs
-                .iter()
-                .find(|(name, _)| name == &dex)
-                .map(|(_, ctxs)| ctxs);
+fn parse_optional_price(value: Option<&str>, name: &str) -> Result<Option<f64>> {
+    value
+        .map(|v| validation::validate_positive_number(v, name))
+        .transpose()
+}
 
-            for (idx, market) in perp_meta.universe.iter().enumerate() {
-                let ctx = dex_ctxs.and_then(|ctxs| ctxs.get(idx));
-                let display_name = if meta_index == 0 {
-                    market.name.as_str()
-                } else {
-                    market
-                        .name
-                        .split_once(':')
-                        .map(|(_, rest)| rest)
-                        .unwrap_or(market.name.as_str())
-                };
-                perp_markets.push(MarketRow {
-                    coin: market.name.clone(),
-                    pair_name: format!(
-                        "{display_name}/{collateral} {}x{dex_suffix}",
-                        market.max_leverage
-                    ),
-                    price: ctx
-                        .map(|c| c.mark_px.clone())
-                        .unwrap_or_else(|| "?".to_string()),
-                    price_change: ctx
-                        .and_then(|c| calculate_price_change(&c.mark_px, &c.prev_day_px)),
-                    volume_usd: ctx
-                        .map(|c| format_volume(Some(&c.day_ntl_vlm)))
-                        .unwrap_or_else(|| "N/A".to_string()),
-                    funding: ctx.map(|c| c.funding.clone()),
-                    open_interest: ctx.and_then(|c| {
-                        if c.open_interest.trim().is_empty() {
-                            None
-                        } else {
-                            Some(format_volume(Some(&c.open_interest)))
-                        }
-                    }),
-                });
-            }
-        }
-    }
+fn check_optional_price(
+    precision: &AssetPrecision,
+    px: Option<f64>,
+    name: &str,
+    strict: bool,
+) -> Result<Option<f64>> {
+    px.map(|px| precision.check_price(px, name, strict))
+        .transpose()
+}
 
-    let mut spot_markets = Vec::new();
-    if !perp_only {
-        let spot_ctxs = client.get_spot_asset_ctxs().await?.data;
-        let mut ctx_map = std::collections::HashMap::new();
-        for ctx in spot_ctxs {
-            ctx_map.insert(ctx.coin.clone(), ctx);
+/// Check that take-profit and stop-loss prices sit on the correct side of the
+/// entry reference price for the given direction.
+fn validate_bracket(
+    is_buy: bool,
+    reference_px: f64,
+    tp: Option<f64>,
+    sl: Option<f64>,
+) -> Result<()> {
+    if let Some(tp) = tp {
+        let ok = if is_buy {
+            tp > reference_px
+        } else {
+            tp < reference_px
+        };
+        if !ok {
+            return Err(anyhow!(
+                "Take-profit price {tp} must be {} the entry price {reference_px}",
+                if is_buy { "above" } else { "below" }
+            ));
         }
-        for pair in spot_meta.universe.iter() {
-            let base = spot_meta
-                .tokens
-                .get(pair.tokens.first().copied().unwrap_or_default() as usize)
-                .map(|t| t.name.clone())
-                .unwrap_or_else(|| "?".to_string());
-            let quote = spot_meta
-                .tokens
-                .get(pair.tokens.get(1).copied().unwrap_or_default() as usize)
-                .map(|t| t.name.clone())
-                .unwrap_or_else(|| "?".to_string());
-            let ctx = ctx_map.get(&pair.name);
-            spot_markets.push(MarketRow {
-                coin: pair.name.clone(),
-                pair_name: format!("[Spot] {base}/{quote}"),
-                price: ctx
-                    .map(|c| c.mark_px.clone())
-                    .unwrap_or_else(|| "?".to_string()),
-                price_change: ctx.and_then(|c| calculate_price_change(&c.mark_px, &c.prev_day_px)),
-                volume_usd: ctx
-                    .map(|c| format_volume(Some(&c.day_ntl_vlm)))
-                    .unwrap_or_else(|| "N/A".to_string()),
-                funding: None,
-                open_interest: None,
-            });
+    }
+    if let Some(sl) = sl {
+        let ok = if is_buy {
+            sl < reference_px
+        } else {
+            sl > reference_px
+        };
+        if !ok {
+            return Err(anyhow!(
+                "Stop-loss price {sl} must be {} the entry price {reference_px}",
+                if is_buy { "below" } else { "above" }
+            ));
         }
     }
+    Ok(())
+}
 
-    Ok((perp_markets, spot_markets))
+/// Worst acceptable execution price for a market trigger order.
+fn market_trigger_limit_px(
+    trigger_px: f64,
+    is_buy: bool,
+    slippage_pct: f64,
+    precision: &AssetPrecision,
+) -> String {
+    let px = if is_buy {
+        trigger_px * (1.0 + slippage_pct)
+    } else {
+        trigger_px * (1.0 - slippage_pct)
+    };
+    precision.format_price(px)
 }
 
-async fn markets_prices(
+/// Append reduce-only market TP/SL children to an entry order. Returns the
+/// orders in submission order along with a label for each leg.
+fn build_bracket(
+    entry: OrderRequest,
+    tp: Option<f64>,
+    sl: Option<f64>,
+    slippage_pct: f64,
+    precision: &AssetPrecision,
+) -> (Vec<OrderRequest>, Vec<&'static str>) {
+    let asset = entry.asset;
+    let is_buy = !entry.is_buy;
+    let sz = entry.sz.clone();
+
+    let mut orders = vec![entry];
+    let mut legs = vec!["Entry"];
+    for (trigger_px, tpsl, label) in [(tp, TpSl::Tp, "Take profit"), (sl, TpSl::Sl, "Stop loss")] {
+        let Some(trigger_px) = trigger_px else {
+            continue;
+        };
+        orders.push(OrderRequest {
+            asset,
+            is_buy,
+            limit_px: market_trigger_limit_px(trigger_px, is_buy, slippage_pct, precision),
+            sz: sz.clone(),
+            reduce_only: true,
+            order_type: OrderType::Trigger(Trigger {
+                is_market: true,
+                trigger_px: precision.format_price(trigger_px),
+                tpsl,
+            }),
+            cloid: None,
+        });
+        legs.push(label);
+    }
+    (orders, legs)
+}
+
+async fn order_trigger(
+    cfg: &config::LoadedConfig,
     api: &HlApi,
-    args: MarketsPricesArgs,
+    mut args: OrderTriggerArgs,
+    tpsl: TpSl,
     output_opts: OutputOptions,
 ) -> Result<()> {
-    if args.watch {
-        if !output_opts.json {
-            watch::hide_cursor();
-        }
-        let interval = tokio::time::interval(std::time::Duration::from_millis(500));
-        tokio::pin!(interval);
-        let ctrl_c = tokio::signal::ctrl_c();
-        tokio::pin!(ctrl_c);
+    let side = validation::validate_side_with_aliases(&args.side)?;
+    let size = validation::validate_positive_number(&args.size, "size")?;
+    let trigger_px = validation::validate_positive_number(&args.trigger_price, "trigger price")?;
+    let limit_px = args
+        .limit
+        .as_deref()
+        .map(|p| validation::validate_positive_number(p, "limit price"))
+        .transpose()?;
+    let is_buy = side == "buy";
 
-        loop {
-            tokio::select! {
-                _ = interval.tick() => {
-                    match api.all_mids().await {
-                        Ok(mids) => {
-                            if output_opts.json {
-                                output::print_json_line(&serde_json::json!({
-                                    "mids": mids,
-                                    "timestamp": chrono::Utc::now().to_rfc3339(),
-                                }))?;
-                            } else {
-                                watch::clear_screen();
-                                println!("Mid Prices ({}):", watch::format_timestamp());
-                                let mut rows: Vec<Vec<String>> = mids.iter().map(|(k,v)| vec![k.clone(), v.clone()]).collect();
-                                rows.sort_by(|a,b| a[0].cmp(&b[0]));
-                                output::print_table(&["Coin","Price"], rows);
-                            }
-                        }
-                        Err(e) => {
-                            if !output_opts.json {
-                                watch::clear_screen();
-                                println!("Mid Prices ({}):", watch::format_timestamp());
-                                println!("Error: {e}");
-                                println!("Reconnecting...");
-                            } else {
-                                output::print_error(e.to_string());
-                            }
-                        }
-                    }
-                }
-                _ = &mut ctrl_c => break,
-            }
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    args.coin = asset_index::resolve_coin(&all_perp_metas, &spot_meta, &args.coin)?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &args.coin)?;
+    let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, &args.coin)?;
+    let size = precision.check_size(size, args.strict)?;
+    let trigger_px = precision.check_price(trigger_px, "Trigger price", args.strict)?;
+    let limit_px = check_optional_price(&precision, limit_px, "Limit price", args.strict)?;
+
+    // Market triggers still need a worst-case limit price; derive it from the
+    // trigger price and the configured slippage, like `order market` does from mid.
+    let is_market = limit_px.is_none();
+    let limit_px = match limit_px {
+        Some(px) => precision.format_price(px),
+        None => {
+            let slippage_pct = resolve_slippage_pct(args.slippage.as_deref(), Some(&args.coin))?;
+            market_trigger_limit_px(trigger_px, is_buy, slippage_pct, &precision)
         }
+    };
 
-        if !output_opts.json {
-            watch::show_cursor();
+    let order = OrderRequest {
+        asset,
+        is_buy,
+        limit_px,
+        sz: precision.format_size(size),
+        reduce_only: args.reduce_only,
+        order_type: OrderType::Trigger(Trigger {
+            is_market,
+            trigger_px: precision.format_price(trigger_px),
+            tpsl,
+        }),
+        cloid: None,
+    };
+    let warning = check_risk_limits(
+        cfg,
+        api,
+        &[order_intent(&args.coin, &order, precision.is_spot)],
+    )
+    .await?;
+
+    if cfg.dry_run {
+        let action = Action::Order {
+            orders: vec![order],
+            grouping: Grouping::Na,
+        };
+        return DryRun::default()
+            .warning(warning)
+            .action(&action)?
+            .print(cfg, api, output_opts)
+            .await;
+    }
+
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = exchange
+        .place_order(wallet, vec![order], cfg.vault_address())
+        .await?;
+    if output_opts.json {
+        output::print_json_pretty(&resp)?;
+    } else {
+        print_exchange_response(&resp, &[]);
+    }
+    Ok(())
+}
+
+/// Outcome category of an order status from the exchange.
+fn order_status_kind(status: &str) -> &'static str {
+    match status {
+        "open" => "open",
+        "filled" => "filled",
+        "triggered" => "triggered",
+        "rejected" => "rejected",
+        s if s.ends_with("Rejected") => "rejected",
+        s if s == "scheduledCancel" || s.to_ascii_lowercase().ends_with("canceled") => "canceled",
+        _ => "other",
+    }
+}
+
+/// Human-readable order status, with the reason for cancels and rejects.
+fn format_order_status(status: &str) -> String {
+    let (kind, reason) = if let Some(reason) = status.strip_suffix("Rejected") {
+        ("Rejected", reason)
+    } else if status == "scheduledCancel" {
+        ("Cancelled", "scheduled")
+    } else if let Some(reason) = status.strip_suffix("Canceled") {
+        ("Cancelled", reason)
+    } else {
+        return match status {
+            "open" => "Open",
+            "filled" => "Filled",
+            "triggered" => "Triggered",
+            "canceled" => "Cancelled",
+            "rejected" => "Rejected",
+            other => other,
         }
+        .to_string();
+    };
+    let reason = match reason {
+        "margin" | "perpMargin" => "insufficient margin".to_string(),
+        "vaultWithdrawal" => "vault withdrawal".to_string(),
+        "openInterestCap"
+        | "positionIncreaseAtOpenInterestCap"
+        | "positionFlipAtOpenInterestCap"
+        | "tooAggressiveAtOpenInterestCap"
+        | "openInterestIncrease" => "open interest cap".to_string(),
+        "selfTrade" => "self-trade prevention".to_string(),
+        "reduceOnly" => "reduce-only would increase the position".to_string(),
+        "siblingFilled" => "other TP/SL leg filled".to_string(),
+        "delisted" => "asset delisted".to_string(),
+        "liquidated" => "liquidation".to_string(),
+        "scheduled" => "dead man's switch".to_string(),
+        "tick" => "price not on tick size".to_string(),
+        "minTradeNtl" => "below minimum order value".to_string(),
+        "badAloPx" => "post-only would have crossed".to_string(),
+        "iocCancel" => "IOC found no liquidity".to_string(),
+        "badTriggerPx" => "invalid trigger price".to_string(),
+        "marketOrderNoLiquidity" => "no liquidity for market order".to_string(),
+        "insufficientSpotBalance" => "insufficient spot balance".to_string(),
+        "oracle" => "too far from oracle price".to_string(),
+        "perpMaxPosition" => "max position size".to_string(),
+        // Unknown reasons: split the camelCase name into words.
+        other => other
+            .chars()
+            .flat_map(|c| {
+                let space = c.is_ascii_uppercase().then_some(' ');
+                space.into_iter().chain(c.to_lowercase())
+            })
+            .collect(),
+    };
+    format!("{kind} ({reason})")
+}
+
+/// The `order_status_kind` an `order history --status` value selects.
+fn parse_status_filter(value: &str) -> Result<&'static str> {
+    match value.to_ascii_lowercase().as_str() {
+        "open" => Ok("open"),
+        "filled" => Ok("filled"),
+        "canceled" | "cancelled" => Ok("canceled"),
+        "triggered" => Ok("triggered"),
+        "rejected" => Ok("rejected"),
+        _ => Err(anyhow!(
+            "Status must be one of: open, filled, canceled, triggered, rejected"
+        )),
+    }
+}
+
+fn style_order_status(status: &str) -> String {
+    let text = format_order_status(status);
+    match order_status_kind(status) {
+        "filled" => output::style_profit(text),
+        "rejected" => output::style_loss(text),
+        "canceled" => output::style_warning(text),
+        _ => text,
+    }
+}
+
+fn format_historical_order_row(h: &HistoricalOrder) -> Vec<String> {
+    let o = &h.order;
+    let side = if o.side == "B" {
+        output::style_profit("Buy")
+    } else {
+        output::style_loss("Sell")
+    };
+    let order_type = match (o.order_type.as_str(), o.reduce_only) {
+        ("", false) => "Limit".to_string(),
+        ("", true) => "Limit (RO)".to_string(),
+        (t, false) => t.to_string(),
+        (t, true) => format!("{t} (RO)"),
+    };
+    vec![
+        format_order_timestamp(h.status_timestamp),
+        o.oid.to_string(),
+        o.coin.clone(),
+        side,
+        o.orig_sz.clone().unwrap_or_else(|| o.sz.clone()),
+        o.limit_px.clone(),
+        order_type,
+        style_order_status(&h.status),
+    ]
+}
+
+async fn order_history(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: OrderHistoryArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let user = resolve_user_address(cfg, &args.user)?;
+    let limit = validation::validate_positive_u64(&args.limit, "limit")? as usize;
+    let status = args
+        .status
+        .as_deref()
+        .map(parse_status_filter)
+        .transpose()?;
+    let coin = match args.coin.as_deref() {
+        Some(coin) => Some(resolve_coin(api, coin).await?),
+        None => None,
+    };
+
+    let mut orders: Vec<HistoricalOrder> = api
+        .historical_orders(user)
+        .await?
+        .into_iter()
+        .filter(|h| coin.as_ref().is_none_or(|c| &h.order.coin == c))
+        .filter(|h| status.is_none_or(|s| order_status_kind(&h.status) == s))
+        .collect();
+    orders.sort_by_key(|h| std::cmp::Reverse(h.status_timestamp));
+    orders.truncate(limit);
+
+    if output_opts.json {
+        output::print_json_pretty(&orders)?;
         return Ok(());
     }
+    if orders.is_empty() {
+        println!("{}", output::style_muted("No orders"));
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = orders.iter().map(format_historical_order_row).collect();
+    output::print_table_with_columns(
+        &[
+            output::TableColumn::left("Time"),
+            output::TableColumn::right("OID"),
+            output::TableColumn::left("Coin"),
+            output::TableColumn::left("Side"),
+            output::TableColumn::right("Size"),
+            output::TableColumn::right("Price"),
+            output::TableColumn::left("Type"),
+            output::TableColumn::left("Status"),
+        ],
+        rows,
+    );
+    Ok(())
+}
+
+async fn order_status(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: OrderStatusArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let user = resolve_user_address(cfg, &args.user)?;
+    let order_ref = if args.id.starts_with("0x") {
+        OrderRef::Cloid(validation::format_cloid(&validation::validate_cloid(
+            &args.id,
+        )?))
+    } else {
+        OrderRef::Oid(validation::validate_positive_u64(&args.id, "oid")?)
+    };
+
+    let resp = api.order_status(user, &order_ref).await?;
+    let Some(h) = resp.order else {
+        // Orders rejected before reaching the book are only known locally.
+        let known = match &order_ref {
+            OrderRef::Cloid(cloid) => db::get_client_order(cloid)?,
+            OrderRef::Oid(_) => None,
+        };
+        return Err(match known {
+            Some(known) => anyhow!(
+                "Order {} is unknown to the exchange (last known status here: {})",
+                args.id,
+                known.status
+            ),
+            None => anyhow!("Order {} not found", args.id),
+        });
+    };
 
-    let mids = api.all_mids().await?;
     if output_opts.json {
-        output::print_json_pretty(&mids)?;
+        output::print_json_pretty(&h)?;
         return Ok(());
     }
 
-    let mut rows: Vec<Vec<String>> = mids
-        .iter()
-        .map(|(k, v)| vec![k.clone(), v.clone()])
-        .collect();
-    rows.sort_by(|a, b| a[0].cmp(&b[0]));
-    output::print_table(&["Coin", "Price"], rows);
+    let o = &h.order;
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, &o.coin)?;
+    let filled = match (o.orig_sz.as_deref(), o.sz.parse::<f64>()) {
+        (Some(orig_sz), Ok(remaining)) => orig_sz
+            .parse::<f64>()
+            .map(|orig| format!("{} of {orig_sz}", precision.format_size(orig - remaining)))
+            .unwrap_or_else(|_| o.sz.clone()),
+        _ => o.sz.clone(),
+    };
+    let order_type = if o.order_type.is_empty() {
+        "Limit"
+    } else {
+        &o.order_type
+    };
+    println!("OID:        {}", o.oid);
+    if let Some(cloid) = &o.cloid {
+        println!("Client ID:  {cloid}");
+    }
+    println!(
+        "Status:     {} at {}",
+        style_order_status(&h.status),
+        format_order_timestamp(h.status_timestamp)
+    );
+    println!(
+        "Order:      {} {} {} @ {}{}",
+        if o.side == "B" { "Buy" } else { "Sell" },
+        o.orig_sz.as_deref().unwrap_or(&o.sz),
+        o.coin,
+        o.limit_px,
+        if o.reduce_only { " (reduce-only)" } else { "" }
+    );
+    println!(
+        "Type:       {order_type}{}",
+        o.tif
+            .as_deref()
+            .map(|tif| format!(" {tif}"))
+            .unwrap_or_default()
+    );
+    if o.is_trigger {
+        println!("Trigger:    {}", o.trigger_condition);
+    }
+    println!("Filled:     {filled}");
+    println!("Placed:     {}", format_order_timestamp(o.timestamp));
     Ok(())
 }
 
-async fn run_asset(
-    cmd: AssetCommand,
+async fn order_cancel(
     cfg: &config::LoadedConfig,
     api: &HlApi,
+    args: OrderCancelArgs,
     output_opts: OutputOptions,
 ) -> Result<()> {
-    match cmd {
-        AssetCommand::Price(args) => asset_price(api, args, output_opts).await,
-        AssetCommand::Book(args) => asset_book(api, args, output_opts).await,
-        AssetCommand::Leverage(args) => asset_leverage(cfg, api, args, output_opts).await,
+    let user = require_wallet_address(cfg)?;
+    if let Some(cloid_arg) = args.cloid {
+        return order_cancel_by_cloid(cfg, api, user, &cloid_arg, output_opts).await;
     }
-}
 
-async fn asset_price(api: &HlApi, args: AssetPriceArgs, output_opts: OutputOptions) -> Result<()> {
-    let coin = args.coin;
+    let open_orders = api.open_orders(user).await?;
+    if open_orders.is_empty() && args.oid.is_none() {
+        output::print_success("No open orders to cancel");
+        return Ok(());
+    }
 
-    if args.watch {
-        if !output_opts.json {
-            watch::hide_cursor();
-        }
+    let (order_id, coin) = if let Some(oid_arg) = args.oid {
+        let oid = validation::validate_positive_u64(&oid_arg, "oid")?;
+        let order = open_orders
+            .iter()
+            .find(|o| o.oid == oid)
+            .ok_or_else(|| anyhow!("Order {oid} not found in open orders"))?;
+        (oid, order.coin.clone())
+    } else {
+        let options = open_orders
+            .iter()
+            .map(|o| prompt::SelectOption {
+                value: (o.oid, o.coin.clone()),
+                label: format!(
+                    "{}: {} {} {} @ {}",
+                    o.oid,
+                    o.coin,
+                    if o.side == "B" { "Buy" } else { "Sell" },
+                    o.sz,
+                    o.limit_px
+                ),
+            })
+            .collect();
+        prompt::select("Select order to cancel:", options)?
+    };
 
-        let ctrl_c = tokio::signal::ctrl_c();
-        tokio::pin!(ctrl_c);
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &coin)?;
+    let cancels = vec![CancelRequest {
+        asset,
+        oid: order_id,
+    }];
 
-        let mut price = "-".to_string();
-        let mut last_updated = watch::format_timestamp();
+    if cfg.dry_run {
+        return dry_run_action(cfg, api, &Action::Cancel { cancels }, output_opts).await;
+    }
 
-        let render = |price: &str, last_updated: &str| {
-            watch::clear_screen();
-            println!(
-                "{}",
-                output::format_watch_header(format!("{coin} Price"), last_updated)
-            );
-            println!();
-            println!(
-                "{}: {}",
-                output::style_header(&coin),
-                output::style_bold(price)
-            );
-            println!();
-            println!("{}", output::style_muted("Press Ctrl+C to exit"));
-        };
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = exchange
+        .cancel_order(wallet, cancels, cfg.vault_address())
+        .await?;
 
-        let mut server_client = ServerClient::try_connect().await?;
+    if output_opts.json {
+        output::print_json_pretty(&resp)?;
+    } else {
+        output::print_success(format!("Order {order_id} cancelled"));
+    }
+    Ok(())
+}
 
-        if !output_opts.json {
-            render(&price, &last_updated);
-        }
+async fn order_cancel_by_cloid(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    user: Address,
+    cloid_arg: &str,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let cloid = validation::validate_cloid(cloid_arg)?;
+    let cloid_hex = validation::format_cloid(&cloid);
+
+    let open_orders = api.frontend_open_orders(user).await?;
+    let Some(order) = open_orders.iter().find(|o| {
+        o.cloid
+            .as_deref()
+            .is_some_and(|c| c.eq_ignore_ascii_case(&cloid_hex))
+    }) else {
+        return Err(match db::get_client_order(&cloid_hex)? {
+            Some(known) => anyhow!(
+                "Order with client ID {cloid_hex} is not open (last known status: {}{})",
+                known.status,
+                known
+                    .oid
+                    .map(|oid| format!(", oid {oid}"))
+                    .unwrap_or_default()
+            ),
+            None => anyhow!("Order with client ID {cloid_hex} not found in open orders"),
+        });
+    };
 
-        if let Some(c) = server_client.as_mut() {
-            let interval = tokio::time::interval(std::time::Duration::from_millis(500));
-            tokio::pin!(interval);
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &order.coin)?;
 
-            loop {
-                tokio::select! {
-                    _ = interval.tick() => {
-                        match c.get_prices(None).await {
-                            Ok(cached) => {
-                                if let Some(next) = cached.data.get(&coin) {
-                                    price = next.clone();
-                                    last_updated = watch::format_timestamp();
-                                    if output_opts.json {
-                                        output::print_json_line(&serde_json::json!({
-                                            "coin": coin,
-                                            "price": price,
-                                            "timestamp": chrono::Utc::now().to_rfc3339(),
-                                        }))?;
-                                    } else {
-                                        render(&price, &last_updated);
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                if !output_opts.json {
-                                    watch::clear_screen();
-                                    println!("{}", output::style_loss(format!("Error: {e}")));
-                                    println!("{}", output::style_muted("Reconnecting..."));
-                                } else {
-                                    output::print_error(e.to_string());
-                                }
-                            }
-                        }
-                    }
-                    _ = &mut ctrl_c => break,
-                }
-            }
+    let cancels = vec![CancelByCloidRequest { asset, cloid }];
+
+    if cfg.dry_run {
+        return dry_run_action(cfg, api, &Action::CancelByCloid { cancels }, output_opts).await;
+    }
+
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = exchange
+        .cancel_order_by_cloid(wallet, cancels, cfg.vault_address())
+        .await?;
+    ensure_success(&resp, "Cancel")?;
+    db::update_client_order(&cloid_hex, Some(order.oid), "cancelled")?;
+
+    if output_opts.json {
+        output::print_json_pretty(&resp)?;
+    } else {
+        output::print_success(format!("Order {} ({cloid_hex}) cancelled", order.oid));
+    }
+    Ok(())
+}
+
+/// Spread `total` over `count` prices evenly spaced from `from` to `to`.
+///
+/// Slice sizes grow linearly so the last is `skew` times the first, and are
+/// rounded down to `sz_decimals`; the rounding remainder goes to the last slice.
+fn build_scale_ladder(
+    total: f64,
+    from: f64,
+    to: f64,
+    count: usize,
+    skew: f64,
+    sz_decimals: u32,
+) -> Result<Vec<(f64, f64)>> {
+    if count < 2 {
+        return Err(anyhow!("Count must be at least 2"));
+    }
+    let factor = 10f64.powi(sz_decimals as i32);
+    let steps = (count - 1) as f64;
+    let weights: Vec<f64> = (0..count)
+        .map(|i| 1.0 + (skew - 1.0) * i as f64 / steps)
+        .collect();
+    let weight_sum: f64 = weights.iter().sum();
+
+    let mut ladder = Vec::with_capacity(count);
+    let mut allocated = 0.0;
+    for (i, weight) in weights.iter().enumerate() {
+        let px = from + (to - from) * i as f64 / steps;
+        let sz = if i + 1 == count {
+            ((total - allocated) * factor).round() / factor
         } else {
-            'outer: loop {
-                let mut client = match ws::WsClient::connect(api.testnet).await {
-                    Ok(c) => c,
-                    Err(e) => {
-                        if !output_opts.json {
-                            watch::clear_screen();
-                            println!("{}", output::style_loss(format!("Error: {e}")));
-                            println!("{}", output::style_muted("Reconnecting..."));
-                        } else {
-                            output::print_error(e.to_string());
-                        }
-                        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                        continue;
-                    }
-                };
+            (total * weight / weight_sum * factor).floor() / factor
+        };
+        if sz <= 0.0 {
+            return Err(anyhow!(
+                "Order {} rounds to zero size at {sz_decimals} size decimals. Use a larger size or fewer orders",
+                i + 1
+            ));
+        }
+        allocated += sz;
+        ladder.push((px, sz));
+    }
+    Ok(ladder)
+}
 
-                if let Err(e) = client.subscribe(ws::sub_all_mids_all_dexs()).await {
-                    if !output_opts.json {
-                        watch::clear_screen();
-                        println!("{}", output::style_loss(format!("Error: {e}")));
-                        println!("{}", output::style_muted("Reconnecting..."));
-                    } else {
-                        output::print_error(e.to_string());
-                    }
-                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                    continue;
-                }
+/// Confirm an `order scale` ladder. The risk threshold always applies;
+/// `--yes` only skips the "Place N orders?" prompt. Returns whether to go
+/// ahead and the threshold warning a dry run shows.
+fn confirm_scale(
+    cfg: &config::LoadedConfig,
+    limits: &order_config::RiskLimits,
+    intent: OrderIntent<'_>,
+    count: usize,
+    yes: bool,
+) -> Result<(bool, Option<String>)> {
+    let warning = confirm_risk_threshold(cfg, limits, &[intent])?;
+    if cfg.dry_run || yes {
+        return Ok((true, warning));
+    }
+    let confirmed = confirm_trade(cfg, &format!("Place {count} orders?"))?;
+    Ok((confirmed, warning))
+}
 
-                loop {
-                    tokio::select! {
-                        msg = client.next_json() => {
-                            let msg = match msg {
-                                Ok(Some(m)) => m,
-                                Ok(None) => break,
-                                Err(e) => {
-                                    if !output_opts.json {
-                                        watch::clear_screen();
-                                        println!("{}", output::style_loss(format!("Error: {e}")));
-                                        println!("{}", output::style_muted("Reconnecting..."));
-                                    } else {
-                                        output::print_error(e.to_string());
-                                    }
-                                    break;
-                                }
-                            };
+async fn order_scale(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    mut args: OrderScaleArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let side = validation::validate_side_with_aliases(&args.side)?;
+    let size = validation::validate_positive_number(&args.size, "size")?;
+    let from = validation::validate_positive_number(&args.from, "from price")?;
+    let to = validation::validate_positive_number(&args.to, "to price")?;
+    let count = validation::validate_positive_u64(&args.count, "count")?;
+    if !(2..=50).contains(&count) {
+        return Err(anyhow!("Count must be between 2 and 50"));
+    }
+    let skew = validation::validate_positive_number(&args.skew, "skew")?;
+    validation::validate_tif(&args.tif)?;
+    let is_buy = side == "buy";
 
-                            let Ok(channel) = ws::WsClient::channel(&msg) else {
-                                continue;
-                            };
-                            if channel == "allMids" {
-                                let Ok(data) = ws::WsClient::data(&msg) else {
-                                    continue;
-                                };
-                                let Some(mids) = data.get("mids").and_then(|m| m.as_object()) else {
-                                    continue;
-                                };
-                                if let Some(next) = mids.get(&coin).and_then(|v| v.as_str()) {
-                                    price = next.to_string();
-                                    last_updated = watch::format_timestamp();
-                                    if output_opts.json {
-                                        output::print_json_line(&serde_json::json!({
-                                            "coin": coin,
-                                            "price": price,
-                                            "timestamp": chrono::Utc::now().to_rfc3339(),
-                                        }))?;
-                                    } else {
-                                        render(&price, &last_updated);
-                                    }
-                                }
-                            }
-                        }
-                        _ = &mut ctrl_c => break 'outer,
-                    }
-                }
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    args.coin = asset_index::resolve_coin(&all_perp_metas, &spot_meta, &args.coin)?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &args.coin)?;
+    let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, &args.coin)?;
+    let size = precision.check_size(size, args.strict)?;
+    let from = precision.check_price(from, "From price", args.strict)?;
+    let to = precision.check_price(to, "To price", args.strict)?;
+    let ladder: Vec<(f64, f64)> =
+        build_scale_ladder(size, from, to, count as usize, skew, precision.sz_decimals)?
+            .into_iter()
+            .map(|(px, sz)| (precision.round_price(px), sz))
+            .collect();
 
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-            }
-        }
+    if !output_opts.json {
+        let columns = [
+            output::TableColumn::right("#"),
+            output::TableColumn::right("Price"),
+            output::TableColumn::right("Size"),
+            output::TableColumn::right("Notional"),
+        ];
+        let rows: Vec<Vec<String>> = ladder
+            .iter()
+            .enumerate()
+            .map(|(i, (px, sz))| {
+                vec![
+                    (i + 1).to_string(),
+                    precision.format_price(*px),
+                    precision.format_size(*sz),
+                    format!("{:.2}", px * sz),
+                ]
+            })
+            .collect();
+        let notional: f64 = ladder.iter().map(|(px, sz)| px * sz).sum();
+        println!("{}", output::format_table_with_columns(&columns, &rows));
+        println!(
+            "{} {size} {} across {} orders, notional {notional:.2}",
+            if is_buy { "Buy" } else { "Sell" },
+            args.coin,
+            ladder.len()
+        );
+    }
 
-        if !output_opts.json {
-            watch::show_cursor();
-        }
+    let total_size: f64 = ladder.iter().map(|(_, sz)| sz).sum();
+    let limits = account_risk_limits(cfg)?;
+    let intent = OrderIntent {
+        coin: &args.coin,
+        is_buy,
+        size: total_size,
+        price: ladder.iter().map(|(px, sz)| px * sz).sum::<f64>() / total_size,
+        reduce_only: args.reduce_only,
+        is_spot: precision.is_spot,
+    };
+    let exposure = fetch_exposure(cfg, api, &limits, &[intent]).await?;
+    limits.check_orders(&[intent], exposure)?;
+
+    let (confirmed, warning) = confirm_scale(cfg, &limits, intent, ladder.len(), args.yes)?;
+    if !confirmed {
+        output::print_success("Cancelled");
         return Ok(());
     }
 
-    let mids = if let Some(mut client) = ServerClient::try_connect().await? {
-        match client.get_prices(None).await {
-            Ok(cached) => cached.data,
-            Err(_) => api.all_mids().await?,
-        }
+    let orders: Vec<OrderRequest> = ladder
+        .iter()
+        .map(|(px, sz)| {
+            Ok(OrderRequest {
+                asset,
+                is_buy,
+                limit_px: precision.format_price(*px),
+                sz: precision.format_size(*sz),
+                reduce_only: args.reduce_only,
+                order_type: OrderType::Limit(Limit {
+                    tif: validation::validate_tif(&args.tif)?,
+                }),
+                cloid: None,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    if cfg.dry_run {
+        let action = Action::Order {
+            orders,
+            grouping: Grouping::Na,
+        };
+        return DryRun::default()
+            .warning(warning)
+            .action(&action)?
+            .print(cfg, api, output_opts)
+            .await;
+    }
+
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = exchange
+        .place_order(wallet, orders, cfg.vault_address())
+        .await?;
+
+    if output_opts.json {
+        output::print_json_pretty(&resp)?;
     } else {
-        api.all_mids().await?
+        let labels: Vec<String> = ladder
+            .iter()
+            .map(|(px, _)| format!("Order @ {}", precision.format_price(*px)))
+            .collect();
+        let legs: Vec<&str> = labels.iter().map(String::as_str).collect();
+        print_exchange_response(&resp, &legs);
+    }
+    Ok(())
+}
+
+async fn order_twap(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    mut args: OrderTwapArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let side = validation::validate_side_with_aliases(&args.side)?;
+    let size = validation::validate_positive_number(&args.size, "size")?;
+    let minutes = validation::validate_positive_u64(&args.minutes, "minutes")?;
+    if !(5..=1440).contains(&minutes) {
+        return Err(anyhow!("TWAP duration must be between 5 and 1440 minutes"));
+    }
+
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    args.coin = asset_index::resolve_coin(&all_perp_metas, &spot_meta, &args.coin)?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &args.coin)?;
+    let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, &args.coin)?;
+    let size = precision.check_size(size, args.strict)?;
+    let mids = api
+        .all_mids_for_dex(asset_index::coin_dex(&args.coin))
+        .await?;
+    let mid_price = mid_price_for(&mids, &args.coin)?;
+    let warning = check_risk_limits(
+        cfg,
+        api,
+        &[OrderIntent {
+            coin: &args.coin,
+            is_buy: side == "buy",
+            size,
+            price: mid_price,
+            reduce_only: args.reduce_only,
+            is_spot: precision.is_spot,
+        }],
+    )
+    .await?;
+
+    let twap = TwapRequest {
+        asset,
+        is_buy: side == "buy",
+        sz: precision.format_size(size),
+        reduce_only: args.reduce_only,
+        duration: minutes,
+        randomize: args.randomize,
     };
-    let price = mids
-        .get(&coin)
-        .cloned()
-        .ok_or_else(|| anyhow!("Coin not found: {coin}"))?;
+
+    if cfg.dry_run {
+        return DryRun::default()
+            .warning(warning)
+            .action(&Action::TwapOrder { twap })?
+            .print(cfg, api, output_opts)
+            .await;
+    }
+
+    let user = require_wallet_address(cfg)?;
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = exchange
+        .twap_order(wallet, twap, cfg.vault_address())
+        .await?;
 
     if output_opts.json {
-        output::print_json_pretty(&serde_json::json!({ "coin": coin, "price": price }))?;
-        return Ok(());
+        output::print_json_pretty(&resp)?;
+    } else {
+        print_exchange_response(&resp, &[]);
     }
 
-    println!(
-        "{}: {}",
-        output::style_header(&coin),
-        output::style_bold(price)
-    );
+    if args.watch {
+        let twap_id = match &resp {
+            ExchangeResponse::Ok(data) => match &data.data {
+                Some(StatusType::Status(Status::Running(t))) => Some(t.twap_id),
+                Some(StatusType::Statuses(statuses)) => statuses.iter().find_map(|s| match s {
+                    Status::Running(t) => Some(t.twap_id),
+                    _ => None,
+                }),
+                _ => None,
+            },
+            ExchangeResponse::Err(_) => None,
+        };
+        if let Some(twap_id) = twap_id {
+            watch_twap_progress(api, user, twap_id, Some(size), output_opts).await?;
+        }
+    }
     Ok(())
 }
 
-async fn asset_book(api: &HlApi, args: AssetBookArgs, output_opts: OutputOptions) -> Result<()> {
-    let coin = args.coin;
+async fn order_twap_cancel(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    mut args: OrderTwapCancelArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let twap_id = validation::validate_positive_u64(&args.twap_id, "twap id")?;
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    args.coin = asset_index::resolve_coin(&all_perp_metas, &spot_meta, &args.coin)?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &args.coin)?;
 
-    const MAX_LEVELS: usize = 10;
-    const BAR_WIDTH: usize = 20;
-    const PRICE_WIDTH: usize = 12;
-    const SIZE_WIDTH: usize = 12;
-    const ORDERS_WIDTH: usize = 4;
+    let action = L1Action::TwapCancel {
+        a: asset,
+        t: twap_id,
+    };
 
-    fn pad_end(value: &str, width: usize) -> String {
-        let len = value.chars().count();
-        if len >= width {
-            return value.to_string();
-        }
-        format!("{value}{}", " ".repeat(width - len))
+    if cfg.dry_run {
+        return print_dry_run(cfg, api, &action, Vec::new(), output_opts).await;
     }
 
-    fn create_depth_bar(ratio: f64, width: usize) -> String {
-        let ratio = ratio.clamp(0.0, 1.0);
-        let filled = ((ratio * width as f64).round() as usize).max(1).min(width);
-        "█".repeat(filled)
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = action.send(&exchange, wallet, cfg.vault_address()).await?;
+    ensure_success(&resp, "TWAP cancel")?;
+
+    if output_opts.json {
+        output::print_json_pretty(&resp)?;
+    } else {
+        output::print_success(format!("TWAP {twap_id} cancelled"));
     }
+    Ok(())
+}
 
-    let render = |book: &hyperliquid_cli::hl_api::L2Book, is_watch: bool| {
-        let bids = book.levels.first().cloned().unwrap_or_default();
-        let asks = book.levels.get(1).cloned().unwrap_or_default();
+/// Running totals for the slice fills of one TWAP order.
+#[derive(Debug, Default, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TwapProgress {
+    coin: Option<String>,
+    filled_sz: f64,
+    filled_ntl: f64,
+    slices: usize,
+    #[serde(skip)]
+    seen: std::collections::HashSet<String>,
+}
 
-        if is_watch {
-            println!(
-                "{}",
-                output::format_watch_header(
-                    format!("{coin} Order Book"),
-                    watch::format_timestamp()
-                )
-            );
-        } else {
-            println!("{}", output::style_header(format!("{coin} Order Book")));
+impl TwapProgress {
+    /// Fold the fills for `twap_id` from a `userTwapSliceFills` message into the
+    /// totals. Fills are keyed by trade id, so replayed snapshots are ignored.
+    fn apply(&mut self, data: &serde_json::Value, twap_id: u64) -> bool {
+        let Some(fills) = data.get("twapSliceFills").and_then(|f| f.as_array()) else {
+            return false;
+        };
+        let mut changed = false;
+        for entry in fills {
+            if entry.get("twapId").and_then(|t| t.as_u64()) != Some(twap_id) {
+                continue;
+            }
+            let Some(fill) = entry.get("fill") else {
+                continue;
+            };
+            let key = fill
+                .get("tid")
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| fill.to_string());
+            if !self.seen.insert(key) {
+                continue;
+            }
+            let px = fill
+                .get("px")
+                .and_then(|v| v.as_str())
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            let sz = fill
+                .get("sz")
+                .and_then(|v| v.as_str())
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0);
+            if self.coin.is_none() {
+                self.coin = fill.get("coin").and_then(|c| c.as_str()).map(String::from);
+            }
+            self.filled_sz += sz;
+            self.filled_ntl += sz * px;
+            self.slices += 1;
+            changed = true;
         }
-        println!();
-
-        // Header
-        println!(
-            "{} {} {} {}",
-            output::style_muted(pad_end("price", PRICE_WIDTH)),
-            output::style_muted(pad_end("size", SIZE_WIDTH)),
-            output::style_muted(pad_end("#", ORDERS_WIDTH)),
-            output::style_muted(pad_end("depth", BAR_WIDTH)),
-        );
+        changed
+    }
 
-        let display_bids: Vec<_> = bids.into_iter().take(MAX_LEVELS).collect();
-        let asks_to_process: Vec<_> = asks.into_iter().take(MAX_LEVELS).collect();
+    fn avg_px(&self) -> Option<f64> {
+        (self.filled_sz > 0.0).then(|| self.filled_ntl / self.filled_sz)
+    }
+}
 
-        // Asks: cumulative from best ask (lowest) then reverse for display.
-        let mut ask_cumulative = 0.0_f64;
-        let mut asks_with_cumulative: Vec<(hyperliquid_cli::hl_api::BookLevel, f64)> =
-            asks_to_process
-                .into_iter()
-                .map(|level| {
-                    ask_cumulative += level.sz.parse::<f64>().unwrap_or(0.0);
-                    (level, ask_cumulative)
-                })
-                .collect();
-        asks_with_cumulative.reverse();
+async fn watch_twap_progress(
+    api: &HlApi,
+    user: Address,
+    twap_id: u64,
+    target_sz: Option<f64>,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    if !output_opts.json {
+        watch::hide_cursor();
+    }
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
 
-        // Bids: cumulative in display order (best bid first).
-        let mut bid_cumulative = 0.0_f64;
-        let bids_with_cumulative: Vec<(hyperliquid_cli::hl_api::BookLevel, f64)> = display_bids
-            .into_iter()
-            .map(|level| {
-                bid_cumulative += level.sz.parse::<f64>().unwrap_or(0.0);
-                (level, bid_cumulative)
-            })
-            .collect();
+    // The coin is only known from the first fill; sizes are shown unrounded
+    // until then.
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let mut progress = TwapProgress::default();
+    let render = |progress: &TwapProgress, last_updated: &str| {
+        watch::clear_screen();
+        println!(
+            "{}",
+            output::format_watch_header(format!("TWAP {twap_id}"), last_updated)
+        );
+        println!();
+        let coin = progress.coin.as_deref().unwrap_or("-");
+        let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, coin).ok();
+        let format_size = |sz: f64| match &precision {
+            Some(precision) => precision.format_size(sz),
+            None => sz.to_string(),
+        };
+        let filled = match target_sz {
+            Some(target) => format!(
+                "{} / {} {coin} ({:.1}%)",
+                format_size(progress.filled_sz),
+                format_size(target),
+                progress.filled_sz / target * 100.0
+            ),
+            None => format!("{} {coin}", format_size(progress.filled_sz)),
+        };
+        println!("Filled:    {filled}");
+        println!(
+            "Avg price: {}",
+            progress
+                .avg_px()
+                .map(|px| format!("{px:.6}"))
+                .unwrap_or_else(|| "-".to_string())
+        );
+        println!("Slices:    {}", progress.slices);
+        println!();
+        println!("{}", output::style_muted("Press Ctrl+C to exit"));
+    };
+    let is_complete = |progress: &TwapProgress| {
+        target_sz.is_some_and(|target| progress.filled_sz >= target * (1.0 - 1e-9))
+    };
 
-        let max_cumulative = asks_with_cumulative
-            .first()
-            .map(|(_, c)| *c)
-            .unwrap_or(0.0)
-            .max(bids_with_cumulative.last().map(|(_, c)| *c).unwrap_or(0.0));
+    if !output_opts.json {
+        render(&progress, &watch::format_timestamp());
+    }
 
-        if asks_with_cumulative.is_empty() {
-            println!("{}", output::style_muted("No asks"));
-        }
+    'outer: loop {
+        let mut client = match ws::WsClient::connect(api.testnet).await {
+            Ok(c) => c,
+            Err(e) => {
+                if !output_opts.json {
+                    watch::clear_screen();
+                    println!("{}", output::style_loss(format!("Error: {e}")));
+                    println!("{}", output::style_muted("Reconnecting..."));
+                } else {
+                    output::print_error(e.to_string());
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+                continue;
+            }
+        };
 
-        for (level, cumulative) in &asks_with_cumulative {
-            let bar = if max_cumulative > 0.0 {
-                create_depth_bar(*cumulative / max_cumulative, BAR_WIDTH)
+        if let Err(e) = client.subscribe(ws::sub_user_twap_slice_fills(user)).await {
+            if !output_opts.json {
+                watch::clear_screen();
+                println!("{}", output::style_loss(format!("Error: {e}")));
+                println!("{}", output::style_muted("Reconnecting..."));
             } else {
-                create_depth_bar(0.0, BAR_WIDTH)
-            };
-            println!(
-                "{} {} {} {}",
-                output::style_loss(pad_end(&level.px, PRICE_WIDTH)),
-                pad_end(&level.sz, SIZE_WIDTH),
-                output::style_muted(pad_end(&level.n.to_string(), ORDERS_WIDTH)),
-                output::style_loss(bar)
-            );
+                output::print_error(e.to_string());
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+            continue;
         }
 
-        if let (Some((best_ask, _)), Some((best_bid, _))) =
-            (asks_with_cumulative.last(), bids_with_cumulative.first())
-            && let (Ok(ask_px), Ok(bid_px)) =
-                (best_ask.px.parse::<f64>(), best_bid.px.parse::<f64>())
-        {
-            let spread = ask_px - bid_px;
-            let total_width = PRICE_WIDTH + SIZE_WIDTH + ORDERS_WIDTH + BAR_WIDTH + 6;
-            let side = "─".repeat(total_width / 2);
-            println!(
-                "{}",
-                output::style_warning(format!("{side} spread: {spread:.2} {side}"))
-            );
+        loop {
+            tokio::select! {
+                msg = client.next_json() => {
+                    let msg = match msg {
+                        Ok(Some(m)) => m,
+                        Ok(None) => break,
+                        Err(e) => {
+                            if !output_opts.json {
+                                watch::clear_screen();
+                                println!("{}", output::style_loss(format!("Error: {e}")));
+                                println!("{}", output::style_muted("Reconnecting..."));
+                            } else {
+                                output::print_error(e.to_string());
+                            }
+                            break;
+                        }
+                    };
+
+                    let Ok(channel) = ws::WsClient::channel(&msg) else {
+                        continue;
+                    };
+                    if channel != "userTwapSliceFills" {
+                        continue;
+                    }
+                    let Ok(data) = ws::WsClient::data(&msg) else {
+                        continue;
+                    };
+                    if !progress.apply(data, twap_id) {
+                        continue;
+                    }
+
+                    if output_opts.json {
+                        output::print_json_line(&serde_json::json!({
+                            "twapId": twap_id,
+                            "targetSz": target_sz,
+                            "progress": progress,
+                            "avgPx": progress.avg_px(),
+                            "timestamp": chrono::Utc::now().to_rfc3339(),
+                        }))?;
+                    } else {
+                        render(&progress, &watch::format_timestamp());
+                    }
+                    if is_complete(&progress) {
+                        break 'outer;
+                    }
+                }
+                _ = &mut ctrl_c => break 'outer,
+            }
         }
 
-        if bids_with_cumulative.is_empty() {
-            println!("{}", output::style_muted("No bids"));
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    }
+
+    if !output_opts.json {
+        watch::show_cursor();
+        if is_complete(&progress) {
+            output::print_success(format!("TWAP {twap_id} complete"));
         }
+    }
+    Ok(())
+}
 
-        for (level, cumulative) in &bids_with_cumulative {
-            let bar = if max_cumulative > 0.0 {
-                create_depth_bar(*cumulative / max_cumulative, BAR_WIDTH)
-            } else {
-                create_depth_bar(0.0, BAR_WIDTH)
+/// Re-read a chase order from the exchange, recording any fill the websocket
+/// has not delivered yet. Returns the order's status, e.g. "open" or "filled".
+async fn sync_chase_order(
+    api: &HlApi,
+    user: Address,
+    oid: u64,
+    progress: &mut ChaseProgress,
+) -> Result<String> {
+    let resp = api.order_status(user, &OrderRef::Oid(oid)).await?;
+    let Some(order) = resp.order else {
+        return Ok(resp.status);
+    };
+    let orig = order
+        .order
+        .orig_sz
+        .as_deref()
+        .and_then(|v| v.parse::<f64>().ok());
+    if let (Some(orig), Ok(sz)) = (orig, order.order.sz.parse::<f64>()) {
+        progress.record_fill(oid, orig - sz);
+    }
+    Ok(order.status)
+}
+
+/// Fills of the orders placed by one `order chase`. Each modify replaces the
+/// resting order, so fills are tracked per order ID.
+#[derive(Debug, Default)]
+struct ChaseProgress {
+    /// Filled size by order ID
+    fills: std::collections::HashMap<u64, f64>,
+    /// The order currently resting, and its price as sent
+    oid: Option<u64>,
+    px: Option<String>,
+}
+
+impl ChaseProgress {
+    fn filled(&self) -> f64 {
+        self.fills.values().sum()
+    }
+
+    fn record_fill(&mut self, oid: u64, filled: f64) {
+        let entry = self.fills.entry(oid).or_default();
+        *entry = entry.max(filled);
+    }
+
+    /// Fold an `orderUpdates` message into the fills. Returns the status of
+    /// the resting order if the exchange closed it for any other reason than
+    /// a fill.
+    fn apply_updates(&mut self, data: &serde_json::Value) -> Option<String> {
+        let updates = data.as_array()?;
+        let mut closed = None;
+        for update in updates {
+            let Some(order) = update.get("order") else {
+                continue;
             };
-            println!(
-                "{} {} {} {}",
-                output::style_profit(pad_end(&level.px, PRICE_WIDTH)),
-                pad_end(&level.sz, SIZE_WIDTH),
-                output::style_muted(pad_end(&level.n.to_string(), ORDERS_WIDTH)),
-                output::style_profit(bar)
-            );
+            let Some(oid) = order.get("oid").and_then(|o| o.as_u64()) else {
+                continue;
+            };
+            if !self.fills.contains_key(&oid) && self.oid != Some(oid) {
+                continue;
+            }
+            let field = |key: &str| {
+                order
+                    .get(key)
+                    .and_then(|v| v.as_str())
+                    .and_then(|v| v.parse::<f64>().ok())
+            };
+            if let (Some(orig), Some(sz)) = (field("origSz"), field("sz")) {
+                self.record_fill(oid, orig - sz);
+            }
+            let status = update.get("status").and_then(|s| s.as_str());
+            if self.oid == Some(oid) && !matches!(status, Some("open") | Some("filled")) {
+                closed = Some(status.unwrap_or("closed").to_string());
+            }
         }
+        closed
+    }
+}
 
-        if is_watch {
-            println!();
-            println!("{}", output::style_muted("Press Ctrl+C to exit"));
+/// First status of an order, modify or cancel response.
+fn response_status(resp: &ExchangeResponse) -> Result<&Status> {
+    match resp {
+        ExchangeResponse::Err(e) => Err(anyhow!("{e}")),
+        ExchangeResponse::Ok(data) => match &data.data {
+            Some(StatusType::Statuses(statuses)) => statuses
+                .first()
+                .ok_or_else(|| anyhow!("Empty response from exchange")),
+            Some(StatusType::Status(status)) => Ok(status),
+            _ => Err(anyhow!("Unexpected response from exchange")),
+        },
+    }
+}
+
+/// Fail unless the exchange accepted an action answered with a bare
+/// `success` status, such as a cancel.
+fn ensure_success(resp: &ExchangeResponse, what: &str) -> Result<()> {
+    match response_status(resp) {
+        Ok(Status::Success) => Ok(()),
+        Ok(Status::Error(e)) => Err(anyhow!("{what} failed: {e}")),
+        Ok(other) => Err(anyhow!("{what} failed: unexpected status {other:?}")),
+        Err(e) => Err(anyhow!("{what} failed: {e}")),
+    }
+}
+
+async fn chase_subscribe(testnet: bool, coin: &str, user: Address) -> Result<ws::WsClient> {
+    let mut client = ws::WsClient::connect(testnet).await?;
+    client.subscribe(ws::sub_l2_book(coin)).await?;
+    client.subscribe(ws::sub_order_updates(user)).await?;
+    Ok(client)
+}
+
+async fn order_chase(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    mut args: OrderChaseArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let side = validation::validate_side_with_aliases(&args.side)?;
+    let is_buy = side == "buy";
+    let size = validation::validate_positive_number(&args.size, "size")?;
+    let cap = parse_optional_price(args.max_price.as_deref(), "max price")?;
+    let timeout = args
+        .timeout
+        .as_deref()
+        .map(|t| validation::validate_duration(t, "timeout"))
+        .transpose()?;
+
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    args.coin = asset_index::resolve_coin(&all_perp_metas, &spot_meta, &args.coin)?;
+    let coin = args.coin.as_str();
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, coin)?;
+    let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, coin)?;
+    let size = precision.check_size(size, args.strict)?;
+    let cap = check_optional_price(&precision, cap, "Max price", args.strict)?;
+    let touch = if is_buy { "bid" } else { "ask" };
+
+    let snapshot = api.l2_book(coin).await?;
+    let px = match book::chase_target(&snapshot, is_buy, cap)? {
+        book::ChaseTarget::Price(px) => px,
+        book::ChaseTarget::CapReached(px) => {
+            return Err(anyhow!("Best {touch} {px} is already past --max-price"));
         }
     };
+    let chase_order = |px: &str, sz: f64| OrderRequest {
+        asset,
+        is_buy,
+        limit_px: px.to_string(),
+        sz: precision.format_size(sz),
+        reduce_only: args.reduce_only,
+        order_type: OrderType::Limit(Limit { tif: Tif::Alo }),
+        cloid: None,
+    };
+    // Check the worst price the chase may reach, not just the first one.
+    let warning = check_risk_limits(
+        cfg,
+        api,
+        &[OrderIntent {
+            coin,
+            is_buy,
+            size,
+            price: cap.unwrap_or(px),
+            reduce_only: args.reduce_only,
+            is_spot: precision.is_spot,
+        }],
+    )
+    .await?;
+
+    if cfg.dry_run {
+        let action = Action::Order {
+            orders: vec![chase_order(&precision.format_price(px), size)],
+            grouping: Grouping::Na,
+        };
+        return DryRun::default()
+            .warning(warning)
+            .action(&action)?
+            .print(cfg, api, output_opts)
+            .await;
+    }
 
-    if args.watch {
-        if !output_opts.json {
-            watch::hide_cursor();
+    let user = require_wallet_address(cfg)?;
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let report = |event: &str, progress: &ChaseProgress| -> Result<()> {
+        let filled = progress.filled();
+        if output_opts.json {
+            output::print_json_line(&serde_json::json!({
+                "event": event,
+                "oid": progress.oid,
+                "px": progress.px,
+                "filled": filled,
+                "remaining": size - filled,
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            }))?;
+        } else {
+            let at = progress.px.as_deref().unwrap_or("-");
+            let detail = match progress.oid {
+                Some(oid) => format!("{event} at {at} (ID {oid})"),
+                None => event.to_string(),
+            };
+            output::print_success(format!(
+                "{detail}, filled {} / {size} {coin}",
+                precision.format_size(filled)
+            ));
         }
-        let ctrl_c = tokio::signal::ctrl_c();
-        tokio::pin!(ctrl_c);
+        Ok(())
+    };
 
-        'outer: loop {
-            let mut client = match ws::WsClient::connect(api.testnet).await {
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    let deadline = timeout.map(|t| tokio::time::Instant::now() + t);
+    let expired = async move {
+        match deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(expired);
+
+    let mut progress = ChaseProgress::default();
+    let mut latest = Some(snapshot);
+    let is_filled =
+        |progress: &ChaseProgress| precision.round_size(size - progress.filled()) <= 0.0;
+    // Every exit, including errors, goes through the cancel below so the
+    // post-only order is never left resting unmanaged.
+    let end = async {
+        let end = 'outer: loop {
+            let mut client = match chase_subscribe(api.testnet, coin, user).await {
                 Ok(c) => c,
                 Err(e) => {
-                    if !output_opts.json {
-                        watch::clear_screen();
-                        println!("{}", output::style_loss(format!("Error: {e}")));
-                        println!("{}", output::style_muted("Reconnecting..."));
-                    } else {
-                        output::print_error(e.to_string());
-                    }
+                    output::print_error(format!("{e} (reconnecting...)"));
                     tokio::time::sleep(std::time::Duration::from_millis(500)).await;
                     continue;
                 }
             };
 
-            if let Err(e) = client.subscribe(ws::sub_l2_book(&coin)).await {
-                if !output_opts.json {
-                    watch::clear_screen();
-                    println!("{}", output::style_loss(format!("Error: {e}")));
-                    println!("{}", output::style_muted("Reconnecting..."));
-                } else {
-                    output::print_error(e.to_string());
+            loop {
+                if is_filled(&progress) {
+                    break 'outer "Filled".to_string();
+                }
+                if let Some(book) = latest.take() {
+                    let px = match book::chase_target(&book, is_buy, cap)? {
+                        book::ChaseTarget::Price(px) => precision.format_price(px),
+                        book::ChaseTarget::CapReached(px) => {
+                            break 'outer format!("Best {touch} {px} passed --max-price");
+                        }
+                    };
+                    if progress.px.as_deref() != Some(px.as_str()) {
+                        let (event, resp) = match progress.oid {
+                            Some(oid) => {
+                                // Resize from the exchange's view of the order:
+                                // a fill not yet seen on the websocket would
+                                // otherwise be bought or sold twice.
+                                match sync_chase_order(api, user, oid, &mut progress)
+                                    .await?
+                                    .as_str()
+                                {
+                                    "open" => {}
+                                    "filled" => {
+                                        progress.oid = None;
+                                        progress.px = None;
+                                        latest = Some(book);
+                                        continue;
+                                    }
+                                    status => break 'outer format!("Order {status}"),
+                                }
+                                if is_filled(&progress) {
+                                    break 'outer "Filled".to_string();
+                                }
+                                let modify = ModifyRequest {
+                                    oid,
+                                    order: chase_order(&px, size - progress.filled()),
+                                };
+                                let resp = exchange
+                                    .batch_modify_orders(
+                                        wallet.clone(),
+                                        vec![modify],
+                                        cfg.vault_address(),
+                                    )
+                                    .await?;
+                                ("Moved", resp)
+                            }
+                            None => {
+                                let order = chase_order(&px, size - progress.filled());
+                                let resp = exchange
+                                    .place_order(wallet.clone(), vec![order], cfg.vault_address())
+                                    .await?;
+                                ("Placed", resp)
+                            }
+                        };
+                        match response_status(&resp)? {
+                            Status::Resting(r) => {
+                                progress.oid = Some(r.oid);
+                                progress.px = Some(px);
+                                progress.fills.entry(r.oid).or_default();
+                                report(event, &progress)?;
+                            }
+                            Status::Filled(f) => {
+                                progress.oid = Some(f.oid);
+                                progress.px = Some(f.avg_px.clone());
+                                progress.record_fill(f.oid, f.total_sz.parse().unwrap_or(0.0));
+                                report("Filled", &progress)?;
+                            }
+                            // A post-only order that would cross the book moved
+                            // since the update; wait for the next one.
+                            Status::Error(e) if e.contains("Post only") => {
+                                if let Some(oid) = progress.oid
+                                    && !api.open_orders(user).await?.iter().any(|o| o.oid == oid)
+                                {
+                                    progress.oid = None;
+                                    progress.px = None;
+                                }
+                            }
+                            // The order filled or was cancelled between the
+                            // status check and the modify.
+                            Status::Error(e) if progress.oid.is_some() => {
+                                let oid = progress.oid.unwrap_or_default();
+                                match sync_chase_order(api, user, oid, &mut progress)
+                                    .await?
+                                    .as_str()
+                                {
+                                    "open" => return Err(anyhow!("{event} order failed: {e}")),
+                                    "filled" => {
+                                        progress.oid = None;
+                                        progress.px = None;
+                                    }
+                                    status => break 'outer format!("Order {status}"),
+                                }
+                            }
+                            Status::Error(e) => return Err(anyhow!("{event} order failed: {e}")),
+                            _ => {}
+                        }
+                        continue;
+                    }
                 }
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                continue;
-            }
 
-            loop {
                 tokio::select! {
                     msg = client.next_json() => {
                         let msg = match msg {
                             Ok(Some(m)) => m,
                             Ok(None) => break,
                             Err(e) => {
-                                if !output_opts.json {
-                                    watch::clear_screen();
-                                    println!("{}", output::style_loss(format!("Error: {e}")));
-                                    println!("{}", output::style_muted("Reconnecting..."));
-                                } else {
-                                    output::print_error(e.to_string());
-                                }
+                                output::print_error(format!("{e} (reconnecting...)"));
                                 break;
                             }
                         };
-
                         let Ok(channel) = ws::WsClient::channel(&msg) else {
                             continue;
                         };
-                        if channel == "l2Book" {
-                            let Ok(data) = ws::WsClient::data(&msg) else {
-                                continue;
-                            };
-                            let Ok(book) = serde_json::from_value::<hyperliquid_cli::hl_api::L2Book>(data.clone()) else {
-                                continue;
-                            };
+                        let Ok(data) = ws::WsClient::data(&msg) else {
+                            continue;
+                        };
+                        match channel {
+                            "l2Book" => latest = serde_json::from_value(data.clone()).ok(),
+                            "orderUpdates" => {
+                                let filled = progress.filled();
+                                if let Some(status) = progress.apply_updates(data) {
+                                    break 'outer format!("Order {status}");
+                                }
+                                if progress.filled() > filled && !is_filled(&progress) {
+                                    report("Partially filled", &progress)?;
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ = &mut expired => break 'outer "Timed out".to_string(),
+                    _ = &mut ctrl_c => break 'outer "Interrupted".to_string(),
+                }
+            }
+
+            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        };
+        Ok::<_, anyhow::Error>(end)
+    }
+    .await;
+
+    if let Some(oid) = progress.oid
+        && !is_filled(&progress)
+    {
+        let cancelled = exchange
+            .cancel_order(
+                wallet,
+                vec![CancelRequest { asset, oid }],
+                cfg.vault_address(),
+            )
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|resp| ensure_success(&resp, "Cancel"));
+        if let Err(e) = cancelled {
+            output::print_error(format!("Failed to cancel order {oid}: {e}"));
+        }
+    }
+    let end = end?;
+
+    let filled = progress.filled();
+    if output_opts.json {
+        output::print_json_line(&serde_json::json!({
+            "event": "done",
+            "reason": end,
+            "filled": filled,
+            "remaining": size - filled,
+            "timestamp": chrono::Utc::now().to_rfc3339(),
+        }))?;
+    } else {
+        output::print_success(format!(
+            "{end}: filled {} / {size} {coin}",
+            precision.format_size(filled)
+        ));
+    }
+    Ok(())
+}
+
+/// Replacement values for a resting order; `None` keeps the current value.
+#[derive(Debug, Default, Clone, Copy)]
+struct OrderAmendment {
+    price: Option<f64>,
+    size: Option<f64>,
+    trigger_px: Option<f64>,
+}
+
+impl OrderAmendment {
+    /// Round the new values to the asset's precision (or reject them in strict mode).
+    fn check(self, precision: &AssetPrecision, strict: bool) -> Result<Self> {
+        Ok(Self {
+            price: check_optional_price(precision, self.price, "Price", strict)?,
+            size: self
+                .size
+                .map(|sz| precision.check_size(sz, strict))
+                .transpose()?,
+            trigger_px: check_optional_price(precision, self.trigger_px, "Trigger price", strict)?,
+        })
+    }
+}
+
+/// Build a modify request for a resting order, carrying over every field that
+/// is not being amended.
+fn build_modify_request(
+    order: &FrontendOpenOrder,
+    asset: u32,
+    amendment: OrderAmendment,
+    tif: Option<&str>,
+    precision: &AssetPrecision,
+) -> Result<ModifyRequest> {
+    if order.is_position_tpsl && amendment.size.is_none() && is_zero_size(&order.sz) {
+        return Err(anyhow!(
+            "Order {} is a position TP/SL with no fixed size; cancel it and place a new one instead",
+            order.oid
+        ));
+    }
+    let order_type = if order.is_trigger {
+        if tif.is_some() {
+            return Err(anyhow!(
+                "Order {} is a trigger order; --tif does not apply",
+                order.oid
+            ));
+        }
+        let trigger_px = match amendment.trigger_px {
+            Some(px) => precision.format_price(px),
+            None => order.trigger_px.clone(),
+        };
+        OrderType::Trigger(Trigger {
+            is_market: order.order_type.contains("Market"),
+            trigger_px,
+            tpsl: if order.order_type.starts_with("Take Profit") {
+                TpSl::Tp
+            } else {
+                TpSl::Sl
+            },
+        })
+    } else {
+        if amendment.trigger_px.is_some() {
+            return Err(anyhow!(
+                "Order {} is not a trigger order; --trigger-price does not apply",
+                order.oid
+            ));
+        }
+        let tif = match tif.or(order.tif.as_deref()) {
+            Some(t) => validation::validate_tif(t).with_context(|| {
+                format!(
+                    "Order {} has time-in-force \"{t}\" and cannot be modified",
+                    order.oid
+                )
+            })?,
+            None => Tif::Gtc,
+        };
+        OrderType::Limit(Limit { tif })
+    };
+
+    Ok(ModifyRequest {
+        oid: order.oid,
+        order: OrderRequest {
+            asset,
+            is_buy: order.side == "B",
+            limit_px: amendment
+                .price
+                .map(|p| precision.format_price(p))
+                .unwrap_or_else(|| order.limit_px.clone()),
+            sz: amendment
+                .size
+                .map(|s| precision.format_size(s))
+                .unwrap_or_else(|| order.sz.clone()),
+            reduce_only: order.reduce_only,
+            order_type,
+            cloid: order
+                .cloid
+                .as_deref()
+                .map(validation::validate_cloid)
+                .transpose()?,
+        },
+    })
+}
+
+fn is_zero_size(sz: &str) -> bool {
+    sz.parse::<f64>().is_ok_and(|v| v == 0.0)
+}
+
+fn parse_modify_spec(spec: &str) -> Result<(u64, OrderAmendment)> {
+    let parts: Vec<&str> = spec.split(':').collect();
+    let (oid, price, size) = match parts.as_slice() {
+        [oid, price] => (oid, price, None),
+        [oid, price, size] => (oid, price, Some(size)),
+        _ => {
+            return Err(anyhow!(
+                "Invalid modification \"{spec}\". Expected OID:PRICE or OID:PRICE:SIZE"
+            ));
+        }
+    };
+    Ok((
+        validation::validate_positive_u64(oid, "oid")?,
+        OrderAmendment {
+            price: Some(validation::validate_positive_number(price, "price")?),
+            size: size
+                .map(|s| validation::validate_positive_number(s, "size"))
+                .transpose()?,
+            trigger_px: None,
+        },
+    ))
+}
+
+async fn order_modify(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: OrderModifyArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let amendment = OrderAmendment {
+        price: parse_optional_price(args.price.as_deref(), "price")?,
+        size: args
+            .size
+            .as_deref()
+            .map(|s| validation::validate_positive_number(s, "size"))
+            .transpose()?,
+        trigger_px: parse_optional_price(args.trigger_price.as_deref(), "trigger price")?,
+    };
+    if let Some(tif) = &args.tif {
+        validation::validate_tif(tif)?;
+    }
+    if amendment.price.is_none()
+        && amendment.size.is_none()
+        && amendment.trigger_px.is_none()
+        && args.tif.is_none()
+    {
+        return Err(anyhow!(
+            "Nothing to modify. Pass --price, --size, --tif or --trigger-price"
+        ));
+    }
+
+    let user = require_wallet_address(cfg)?;
+    let open_orders = api.frontend_open_orders(user).await?;
+    if open_orders.is_empty() && args.oid.is_none() {
+        output::print_success("No open orders to modify");
+        return Ok(());
+    }
+
+    let order = if let Some(oid_arg) = args.oid {
+        let oid = validation::validate_positive_u64(&oid_arg, "oid")?;
+        open_orders
+            .into_iter()
+            .find(|o| o.oid == oid)
+            .ok_or_else(|| anyhow!("Order {oid} not found in open orders"))?
+    } else {
+        let options = open_orders
+            .into_iter()
+            .map(|o| prompt::SelectOption {
+                label: format!(
+                    "{}: {} {} {} @ {}",
+                    o.oid,
+                    o.coin,
+                    if o.side == "B" { "Buy" } else { "Sell" },
+                    o.sz,
+                    o.limit_px
+                ),
+                value: o,
+            })
+            .collect();
+        prompt::select("Select order to modify:", options)?
+    };
+
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &order.coin)?;
+    let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, &order.coin)?;
+    let amendment = amendment.check(&precision, args.strict)?;
+    let modify = build_modify_request(&order, asset, amendment, args.tif.as_deref(), &precision)?;
+    let warning = check_risk_limits(
+        cfg,
+        api,
+        &[order_intent(&order.coin, &modify.order, precision.is_spot)],
+    )
+    .await?;
+
+    if cfg.dry_run {
+        return DryRun::default()
+            .warning(warning)
+            .action(&Action::Modify(modify))?
+            .print(cfg, api, output_opts)
+            .await;
+    }
 
-                            if output_opts.json {
-                                output::print_json_line(&serde_json::json!({
-                                    "coin": book.coin,
-                                    "bids": book.levels.first().cloned().unwrap_or_default(),
-                                    "asks": book.levels.get(1).cloned().unwrap_or_default(),
-                                    "time": book.time,
-                                    "timestamp": chrono::Utc::now().to_rfc3339(),
-                                }))?;
-                            } else {
-                                watch::clear_screen();
-                                render(&book, true);
-                            }
-                        }
-                    }
-                    _ = &mut ctrl_c => break 'outer,
-                }
-            }
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = exchange
+        .modify_order(wallet, modify, cfg.vault_address())
+        .await?;
 
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-        }
+    if output_opts.json {
+        output::print_json_pretty(&resp)?;
+    } else if matches!(&resp, ExchangeResponse::Ok(data) if data.data.is_none()) {
+        output::print_success(format!("Order {} modified", order.oid));
+    } else {
+        print_exchange_response(&resp, &[]);
+    }
+    Ok(())
+}
 
-        if !output_opts.json {
-            watch::show_cursor();
-        }
-        return Ok(());
+async fn order_modify_batch(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: OrderModifyBatchArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let specs = args
+        .modifies
+        .iter()
+        .map(|s| parse_modify_spec(s))
+        .collect::<Result<Vec<_>>>()?;
+    if let Some(tif) = &args.tif {
+        validation::validate_tif(tif)?;
     }
 
-    let book = api.l2_book(&coin).await?;
-    if output_opts.json {
-        output::print_json_pretty(&book)?;
-        return Ok(());
+    let user = require_wallet_address(cfg)?;
+    let (open_orders, all_perp_metas, spot_meta) = tokio::try_join!(
+        api.frontend_open_orders(user),
+        api.all_perp_metas(),
+        api.spot_meta()
+    )?;
+
+    let modifies = specs
+        .iter()
+        .map(|(oid, amendment)| {
+            let order = open_orders
+                .iter()
+                .find(|o| o.oid == *oid)
+                .ok_or_else(|| anyhow!("Order {oid} not found in open orders"))?;
+            let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &order.coin)?;
+            let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, &order.coin)?;
+            let amendment = amendment.check(&precision, args.strict)?;
+            let modify =
+                build_modify_request(order, asset, amendment, args.tif.as_deref(), &precision)?;
+            Ok((order.coin.as_str(), precision.is_spot, modify))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let intents: Vec<OrderIntent> = modifies
+        .iter()
+        .map(|(coin, is_spot, modify)| order_intent(coin, &modify.order, *is_spot))
+        .collect();
+    let warning = check_risk_limits(cfg, api, &intents).await?;
+    let modifies: Vec<ModifyRequest> = modifies.into_iter().map(|(_, _, m)| m).collect();
+
+    if cfg.dry_run {
+        return DryRun::default()
+            .warning(warning)
+            .action(&Action::BatchModify { modifies })?
+            .print(cfg, api, output_opts)
+            .await;
     }
 
-    render(&book, false);
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = exchange
+        .batch_modify_orders(wallet, modifies, cfg.vault_address())
+        .await?;
+
+    if output_opts.json {
+        output::print_json_pretty(&resp)?;
+    } else if matches!(&resp, ExchangeResponse::Ok(data) if data.data.is_none()) {
+        output::print_success(format!("Modified {} orders", specs.len()));
+    } else {
+        let labels: Vec<String> = specs
+            .iter()
+            .map(|(oid, _)| format!("Order {oid}"))
+            .collect();
+        let legs: Vec<&str> = labels.iter().map(String::as_str).collect();
+        print_exchange_response(&resp, &legs);
+    }
     Ok(())
 }
 
-async fn asset_leverage(
+async fn order_cancel_all(
     cfg: &config::LoadedConfig,
     api: &HlApi,
-    args: AssetLeverageArgs,
+    args: OrderCancelAllArgs,
     output_opts: OutputOptions,
 ) -> Result<()> {
-    let user = resolve_user_address(cfg, &args.user)?;
-    let coin = args.coin;
+    let user = require_wallet_address(cfg)?;
+    let open_orders = api.open_orders(user).await?;
+    if open_orders.is_empty() {
+        output::print_success("No open orders to cancel");
+        return Ok(());
+    }
 
-    let render = |info: &LeverageInfo, is_watch: bool| {
-        if is_watch {
-            println!(
-                "{}",
-                output::format_watch_header(
-                    format!("{} Leverage", info.coin),
-                    watch::format_timestamp()
-                )
-            );
-            println!();
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let coin = args
+        .coin
+        .as_deref()
+        .map(|coin| asset_index::resolve_coin(&all_perp_metas, &spot_meta, coin))
+        .transpose()?;
+    let mut orders_to_cancel = open_orders;
+    if let Some(coin) = &coin {
+        orders_to_cancel.retain(|o| &o.coin == coin);
+        if orders_to_cancel.is_empty() {
+            output::print_success(format!("No open orders for {coin}"));
+            return Ok(());
         }
+    }
 
-        println!(
-            "{}",
-            output::style_header(format!("{} Leverage Info", info.coin))
-        );
-        println!(
-            "Leverage: {}",
-            output::style_bold(format!(
-                "{}x {}",
-                info.leverage.value, info.leverage.leverage_type
-            ))
-        );
-        println!(
-            "Max Leverage: {}",
-            output::style_bold(format!("{}x", info.max_leverage))
-        );
-        println!(
-            "Mark Price: {}",
-            output::style_bold(format!("${}", info.mark_px))
-        );
-        println!();
-
-        println!("{}", output::style_header("Position"));
-        if let Some(pos) = &info.position {
-            println!("Size: {}", output::style_bold(&pos.size));
-            println!("Value: {}", output::style_bold(format!("${}", pos.value)));
+    if !args.yes && !cfg.dry_run {
+        let confirm_msg = if let Some(coin) = &coin {
+            format!("Cancel all {} orders for {coin}?", orders_to_cancel.len())
         } else {
-            println!("{}", output::style_muted("No position"));
+            format!("Cancel all {} open orders?", orders_to_cancel.len())
+        };
+        let confirmed = confirm_trade(cfg, &confirm_msg)?;
+        if !confirmed {
+            output::print_success("Cancelled");
+            return Ok(());
         }
-        println!();
+    }
 
-        println!("{}", output::style_header("Trading Capacity"));
-        println!(
-            "Available to Trade: {}",
-            output::style_profit_bold(format!(
-                "{} / {}",
-                info.available_to_trade[0], info.available_to_trade[1]
-            ))
-        );
-        println!(
-            "Max Trade Size: {}",
-            output::style_bold(format!(
-                "{} / {}",
-                info.max_trade_szs[0], info.max_trade_szs[1]
-            ))
-        );
-        println!();
+    let cancels: Vec<CancelRequest> = orders_to_cancel
+        .iter()
+        .map(|o| {
+            let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &o.coin)?;
+            Ok(CancelRequest { asset, oid: o.oid })
+        })
+        .collect::<Result<Vec<_>>>()?;
 
-        println!("{}", output::style_header("Margin"));
-        println!(
-            "Account Value: {}",
-            output::style_bold(format!("${}", info.margin.account_value))
-        );
-        println!(
-            "Total Margin Used: {}",
-            output::style_bold(format!("${}", info.margin.total_margin_used))
-        );
-        println!(
-            "Available Margin: {}",
-            output::style_profit_bold(format!("${}", info.margin.available_margin))
-        );
+    if cfg.dry_run {
+        return dry_run_action(cfg, api, &Action::Cancel { cancels }, output_opts).await;
+    }
 
-        if is_watch {
-            println!();
-            println!("{}", output::style_muted("Press Ctrl+C to exit"));
-        }
-    };
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = exchange
+        .cancel_order(wallet, cancels, cfg.vault_address())
+        .await?;
 
-    if args.watch {
-        let mut info = fetch_leverage_info(api, user, &coin).await?;
+    if output_opts.json {
+        output::print_json_pretty(&resp)?;
+    } else {
+        output::print_success(format!("Cancelled {} orders", orders_to_cancel.len()));
+    }
+    Ok(())
+}
 
-        if !output_opts.json {
-            watch::hide_cursor();
-            watch::clear_screen();
-            println!("{}", output::style_muted("Loading..."));
-        }
-        let ctrl_c = tokio::signal::ctrl_c();
-        tokio::pin!(ctrl_c);
+/// Reduce-only IOC order closing `pct` percent of a position at mid +/- slippage.
+fn build_close_order(
+    position: &hl_api::Position,
+    asset: u32,
+    pct: f64,
+    mid_price: f64,
+    slippage_pct: f64,
+    precision: &AssetPrecision,
+) -> Result<OrderRequest> {
+    let szi: f64 = position
+        .szi
+        .parse()
+        .map_err(|_| anyhow!("Invalid position size for {}", position.coin))?;
+    if szi == 0.0 {
+        return Err(anyhow!("No open position in {}", position.coin));
+    }
+    let size = precision.round_size(szi.abs() * pct / 100.0);
+    if size <= 0.0 {
+        return Err(anyhow!(
+            "{pct}% of the {} position rounds to zero size",
+            position.coin
+        ));
+    }
 
-        'outer: loop {
-            let mut client = match ws::WsClient::connect(api.testnet).await {
-                Ok(c) => c,
-                Err(e) => {
-                    if !output_opts.json {
-                        watch::clear_screen();
-                        println!("{}", output::style_loss(format!("Error: {e}")));
-                        println!("{}", output::style_muted("Reconnecting..."));
-                    } else {
-                        output::print_error(e.to_string());
-                    }
-                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                    continue;
-                }
-            };
+    // Long positions close with a sell, short positions with a buy.
+    let is_buy = szi < 0.0;
+    let limit_px = if is_buy {
+        mid_price * (1.0 + slippage_pct)
+    } else {
+        mid_price * (1.0 - slippage_pct)
+    };
+    Ok(OrderRequest {
+        asset,
+        is_buy,
+        limit_px: precision.format_price(limit_px),
+        sz: precision.format_size(size),
+        reduce_only: true,
+        order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
+        cloid: None,
+    })
+}
 
-            if let Err(e) = client
-                .subscribe(ws::sub_active_asset_data(user, &coin))
-                .await
-            {
-                if !output_opts.json {
-                    watch::clear_screen();
-                    println!("{}", output::style_loss(format!("Error: {e}")));
-                    println!("{}", output::style_muted("Reconnecting..."));
-                } else {
-                    output::print_error(e.to_string());
-                }
-                tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                continue;
-            }
+/// Slippage as a fraction: the flag if given, else the configured default
+/// for `coin`.
+fn resolve_slippage_pct(arg: Option<&str>, coin: Option<&str>) -> Result<f64> {
+    Ok(match arg {
+        Some(s) => validation::validate_non_negative_number(s, "slippage")?,
+        None => order_config::load_order_config()?.slippage(coin),
+    } / 100.0)
+}
 
-            loop {
-                tokio::select! {
-                    msg = client.next_json() => {
-                        let msg = match msg {
-                            Ok(Some(m)) => m,
-                            Ok(None) => break,
-                            Err(e) => {
-                                if !output_opts.json {
-                                    watch::clear_screen();
-                                    println!("{}", output::style_loss(format!("Error: {e}")));
-                                    println!("{}", output::style_muted("Reconnecting..."));
-                                } else {
-                                    output::print_error(e.to_string());
-                                }
-                                break;
-                            }
-                        };
+fn mid_price_for(mids: &std::collections::HashMap<String, String>, coin: &str) -> Result<f64> {
+    mids.get(coin)
+        .ok_or_else(|| anyhow!("Cannot get mid price for {coin}"))?
+        .parse()
+        .map_err(|_| anyhow!("Invalid mid price for {coin}"))
+}
+
+/// Mid prices covering `coins`: the main dex, which also carries spot mids,
+/// plus each builder dex one of them trades on.
+async fn mids_for_coins<'a>(
+    api: &HlApi,
+    coins: impl IntoIterator<Item = &'a str>,
+) -> Result<std::collections::HashMap<String, String>> {
+    let mut dexes: Vec<&str> = coins
+        .into_iter()
+        .filter_map(asset_index::coin_dex)
+        .collect();
+    dexes.sort_unstable();
+    dexes.dedup();
+    let mut mids = api.all_mids().await?;
+    for dex in dexes {
+        mids.extend(api.all_mids_for_dex(Some(dex)).await?);
+    }
+    Ok(mids)
+}
 
-                        let Ok(channel) = ws::WsClient::channel(&msg) else {
-                            continue;
-                        };
-                        if channel != "activeAssetData" {
-                            continue;
-                        }
+/// A validated batch line, ready to be grouped into a signed action.
+enum BatchItem {
+    Order(OrderRequest),
+    Cancel(CancelRequest),
+    CancelByCloid(CancelByCloidRequest),
+}
 
-                        let Ok(data) = ws::WsClient::data(&msg) else {
-                            continue;
-                        };
-                        let Ok(active) = serde_json::from_value::<hyperliquid_cli::hl_api::ActiveAssetData>(data.clone()) else {
-                            continue;
-                        };
+#[derive(Debug, serde::Serialize)]
+struct BatchLineResult {
+    line: usize,
+    #[serde(rename = "type")]
+    kind: String,
+    coin: String,
+    status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    oid: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cloid: Option<String>,
+    detail: String,
+}
 
-                        info.coin = active.coin;
-                        info.leverage = active.leverage;
-                        info.max_trade_szs = active.max_trade_szs;
-                        info.available_to_trade = active.available_to_trade;
-                        info.mark_px = active.mark_px;
+impl BatchLineResult {
+    fn set(&mut self, status: &str, oid: Option<u64>, detail: impl Into<String>) {
+        self.status = status.to_string();
+        self.oid = oid;
+        self.detail = detail.into();
+    }
+}
 
-                        if output_opts.json {
-                            let mut v = serde_json::to_value(&info)?;
-                            if let Some(obj) = v.as_object_mut() {
-                                obj.insert(
-                                    "timestamp".to_string(),
-                                    serde_json::Value::String(chrono::Utc::now().to_rfc3339()),
-                                );
-                            }
-                            output::print_json_line(&v)?;
-                        } else {
-                            watch::clear_screen();
-                            render(&info, true);
-                        }
-                    }
-                    _ = &mut ctrl_c => break 'outer,
-                }
-            }
+fn required_field<'a>(value: &'a Option<String>, name: &str) -> Result<&'a str> {
+    value.as_deref().ok_or_else(|| anyhow!("Missing {name}"))
+}
 
-            tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+/// Validate one batch entry with the same rules as the matching subcommand.
+fn build_batch_item(
+    entry: &BatchEntry,
+    all_perp_metas: &[hl_api::PerpMeta],
+    spot_meta: &hl_api::SpotMeta,
+    mids: &std::collections::HashMap<String, String>,
+    strict: bool,
+) -> Result<BatchItem> {
+    let asset = asset_index::resolve_asset_index(all_perp_metas, spot_meta, &entry.coin)?;
+    let cloid = entry
+        .cloid
+        .as_deref()
+        .map(validation::validate_cloid)
+        .transpose()?;
+
+    if entry.kind == "cancel" {
+        return match (&entry.oid, cloid) {
+            (Some(oid), None) => Ok(BatchItem::Cancel(CancelRequest {
+                asset,
+                oid: validation::validate_positive_u64(oid, "oid")?,
+            })),
+            (None, Some(cloid)) => Ok(BatchItem::CancelByCloid(CancelByCloidRequest {
+                asset,
+                cloid,
+            })),
+            _ => Err(anyhow!("Cancel needs exactly one of oid or cloid")),
+        };
+    }
+
+    let side = validation::validate_side_with_aliases(required_field(&entry.side, "side")?)?;
+    let is_buy = side == "buy";
+    let precision = AssetPrecision::resolve(all_perp_metas, spot_meta, &entry.coin)?;
+    let size = validation::validate_positive_number(required_field(&entry.size, "size")?, "size")?;
+    let size = precision.check_size(size, strict)?;
+    let slippage_pct = resolve_slippage_pct(entry.slippage.as_deref(), Some(&entry.coin))?;
+
+    let (limit_px, order_type) = match entry.kind.as_str() {
+        "limit" => {
+            let px = validation::validate_positive_number(
+                required_field(&entry.price, "price")?,
+                "price",
+            )?;
+            let tif = validation::validate_tif(entry.tif.as_deref().unwrap_or("Gtc"))?;
+            (
+                precision.format_price(precision.check_price(px, "Price", strict)?),
+                OrderType::Limit(Limit { tif }),
+            )
+        }
+        "market" => {
+            let mid_price = mid_price_for(mids, &entry.coin)?;
+            let px = if is_buy {
+                mid_price * (1.0 + slippage_pct)
+            } else {
+                mid_price * (1.0 - slippage_pct)
+            };
+            (
+                precision.format_price(px),
+                OrderType::Limit(Limit { tif: Tif::Ioc }),
+            )
+        }
+        "stop" | "tp" => {
+            let trigger_px = validation::validate_positive_number(
+                required_field(&entry.trigger_price, "triggerPrice")?,
+                "trigger price",
+            )?;
+            let trigger_px = precision.check_price(trigger_px, "Trigger price", strict)?;
+            let limit_px = parse_optional_price(entry.price.as_deref(), "limit price")?;
+            let limit_px = check_optional_price(&precision, limit_px, "Limit price", strict)?;
+            let tpsl = if entry.kind == "tp" {
+                TpSl::Tp
+            } else {
+                TpSl::Sl
+            };
+            let limit = match limit_px {
+                Some(px) => precision.format_price(px),
+                None => market_trigger_limit_px(trigger_px, is_buy, slippage_pct, &precision),
+            };
+            (
+                limit,
+                OrderType::Trigger(Trigger {
+                    is_market: limit_px.is_none(),
+                    trigger_px: precision.format_price(trigger_px),
+                    tpsl,
+                }),
+            )
+        }
+        other => {
+            return Err(anyhow!(
+                "Unknown type {other:?}; expected limit, market, stop, tp or cancel"
+            ));
         }
+    };
 
-        if !output_opts.json {
-            watch::show_cursor();
+    Ok(BatchItem::Order(OrderRequest {
+        asset,
+        is_buy,
+        limit_px,
+        sz: precision.format_size(size),
+        reduce_only: entry.reduce_only,
+        order_type,
+        cloid,
+    }))
+}
+
+/// Copy per-order statuses from a batch response onto the lines that made up
+/// the action. A whole-action error is reported on every line.
+fn apply_batch_response(resp: &ExchangeResponse, lines: &[usize], results: &mut [BatchLineResult]) {
+    let statuses = match resp {
+        ExchangeResponse::Err(err) => {
+            for &i in lines {
+                results[i].set("error", None, err.clone());
+            }
+            return;
+        }
+        ExchangeResponse::Ok(data) => match &data.data {
+            Some(StatusType::Statuses(statuses)) => statuses.as_slice(),
+            Some(StatusType::Status(status)) => std::slice::from_ref(status),
+            _ => &[],
+        },
+    };
+    for (pos, &i) in lines.iter().enumerate() {
+        match statuses.get(pos) {
+            Some(Status::Resting(r)) => results[i].set("resting", Some(r.oid), ""),
+            Some(Status::Filled(f)) => results[i].set(
+                "filled",
+                Some(f.oid),
+                format!("{} @ {}", f.total_sz, f.avg_px),
+            ),
+            Some(Status::Error(e)) => results[i].set("error", None, e.clone()),
+            Some(Status::Success) => results[i].set("success", None, ""),
+            Some(Status::WaitingForFill) => results[i].set("waitingForFill", None, ""),
+            Some(Status::WaitingForTrigger) => results[i].set("waitingForTrigger", None, ""),
+            Some(Status::Running(t)) => {
+                results[i].set("running", None, format!("twapId {}", t.twap_id))
+            }
+            None => results[i].set("unknown", None, "No status returned"),
         }
-        return Ok(());
     }
+}
 
-    let info = fetch_leverage_info(api, user, &coin).await?;
-    if output_opts.json {
-        output::print_json_pretty(&info)?;
+async fn order_batch(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    args: OrderBatchArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let (path, input) = if args.input == "-" {
+        let mut input = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)
+            .context("Failed to read orders from stdin")?;
+        (None, input)
+    } else {
+        let input = std::fs::read_to_string(&args.input)
+            .with_context(|| format!("Failed to read {}", args.input))?;
+        (Some(args.input.as_str()), input)
+    };
+    let format = match args.format.as_deref() {
+        Some(f) => BatchFormat::parse(f)?,
+        None => BatchFormat::detect(path, &input),
+    };
+    let lines = if input.trim().is_empty() {
+        Vec::new()
+    } else {
+        order_batch::parse_batch(&input, format)?
+    };
+    if lines.is_empty() {
+        output::print_success("No orders in batch");
         return Ok(());
     }
 
-    render(&info, false);
-    Ok(())
-}
-
-#[derive(serde::Serialize)]
-struct LeveragePositionInfo {
-    size: String,
-    value: String,
-}
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let batch_coins: Vec<String> = lines
+        .iter()
+        .filter_map(|line| line.entry.as_ref().ok())
+        .filter_map(|entry| {
+            asset_index::resolve_coin(&all_perp_metas, &spot_meta, &entry.coin).ok()
+        })
+        .collect();
+    let mids = mids_for_coins(api, batch_coins.iter().map(String::as_str)).await?;
+
+    let mut results = Vec::with_capacity(lines.len());
+    let mut orders: Vec<(usize, OrderRequest)> = Vec::new();
+    let mut cancels: Vec<(usize, CancelRequest)> = Vec::new();
+    let mut cloid_cancels: Vec<(usize, CancelByCloidRequest)> = Vec::new();
+    for line in lines {
+        let i = results.len();
+        let mut result = BatchLineResult {
+            line: line.line,
+            kind: String::new(),
+            coin: String::new(),
+            status: "pending".to_string(),
+            oid: None,
+            cloid: None,
+            detail: String::new(),
+        };
+        match line.entry.and_then(|mut entry| {
+            result.kind = entry.kind.clone();
+            result.coin = entry.coin.clone();
+            result.cloid = entry
+                .cloid
+                .as_deref()
+                .map(validation::validate_cloid)
+                .transpose()?
+                .map(|c| validation::format_cloid(&c));
+            entry.coin = asset_index::resolve_coin(&all_perp_metas, &spot_meta, &entry.coin)?;
+            result.coin = entry.coin.clone();
+            build_batch_item(&entry, &all_perp_metas, &spot_meta, &mids, args.strict)
+        }) {
+            Ok(BatchItem::Order(o)) => orders.push((i, o)),
+            Ok(BatchItem::Cancel(c)) => cancels.push((i, c)),
+            Ok(BatchItem::CancelByCloid(c)) => cloid_cancels.push((i, c)),
+            Err(e) => result.set("invalid", None, e.to_string()),
+        }
+        results.push(result);
+    }
 
-#[derive(serde::Serialize)]
-struct LeverageMarginInfo {
-    #[serde(rename = "accountValue")]
-    account_value: String,
-    #[serde(rename = "totalMarginUsed")]
-    total_margin_used: String,
-    #[serde(rename = "availableMargin")]
-    available_margin: String,
-}
+    // Risk limits apply line by line, in file order, so one oversized entry
+    // does not block the rest of the batch.
+    let limits = account_risk_limits(cfg)?;
+    let intents: Vec<OrderIntent> = orders
+        .iter()
+        .map(|(i, o)| {
+            let is_spot = AssetPrecision::resolve(&all_perp_metas, &spot_meta, &results[*i].coin)
+                .is_ok_and(|p| p.is_spot);
+            order_intent(&results[*i].coin, o, is_spot)
+        })
+        .collect();
+    let mut exposure = fetch_exposure(cfg, api, &limits, &intents).await?;
+    let blocked: Vec<Option<String>> = intents
+        .iter()
+        .map(|intent| {
+            limits
+                .check_order(intent, exposure.as_mut())
+                .err()
+                .map(|e| e.to_string())
+        })
+        .collect();
+    let allowed: Vec<OrderIntent> = intents
+        .iter()
+        .zip(&blocked)
+        .filter(|(_, b)| b.is_none())
+        .map(|(intent, _)| *intent)
+        .collect();
+    let warning = confirm_risk_threshold(cfg, &limits, &allowed)?;
+    let orders: Vec<(usize, OrderRequest)> = orders
+        .into_iter()
+        .zip(blocked)
+        .filter_map(|((i, o), blocked)| match blocked {
+            Some(reason) => {
+                results[i].set("blocked", None, reason);
+                None
+            }
+            None => Some((i, o)),
+        })
+        .collect();
 
-#[derive(serde::Serialize)]
-struct LeverageInfo {
-    coin: String,
-    leverage: hyperliquid_cli::hl_api::Leverage,
-    #[serde(rename = "maxLeverage")]
-    max_leverage: u32,
-    #[serde(rename = "markPx")]
-    mark_px: String,
-    #[serde(rename = "maxTradeSzs")]
-    max_trade_szs: [String; 2],
-    #[serde(rename = "availableToTrade")]
-    available_to_trade: [String; 2],
-    position: Option<LeveragePositionInfo>,
-    margin: LeverageMarginInfo,
-}
+    let (cancel_lines, cancels): (Vec<usize>, Vec<_>) = cancels.into_iter().unzip();
+    let (cloid_cancel_lines, cloid_cancels): (Vec<usize>, Vec<_>) =
+        cloid_cancels.into_iter().unzip();
+    let (order_lines, orders): (Vec<usize>, Vec<_>) = orders.into_iter().unzip();
 
-async fn fetch_leverage_info(api: &HlApi, user: Address, coin: &str) -> Result<LeverageInfo> {
-    let (active, clearinghouse, all_perp_metas) = tokio::try_join!(
-        api.active_asset_data(user, coin),
-        api.clearinghouse_state(user),
-        api.all_perp_metas(),
-    )?;
+    if cfg.dry_run {
+        let mut preview = DryRun::default().warning(warning);
+        if !cancels.is_empty() {
+            preview = preview.action(&Action::Cancel { cancels })?;
+        }
+        if !cloid_cancels.is_empty() {
+            preview = preview.action(&Action::CancelByCloid {
+                cancels: cloid_cancels,
+            })?;
+        }
+        if !orders.is_empty() {
+            preview = preview.action(&Action::Order {
+                orders,
+                grouping: Grouping::Na,
+            })?;
+        }
+        for i in cancel_lines
+            .iter()
+            .chain(&cloid_cancel_lines)
+            .chain(&order_lines)
+        {
+            results[*i].status = "dry-run".to_string();
+        }
+        if output_opts.json {
+            // One document: the actions plus the outcome of every line.
+            preview = preview.field("lines", &results)?;
+        }
+        preview.print(cfg, api, output_opts).await?;
+    } else {
+        let user = require_wallet_address(cfg)?;
+        let wallet = require_wallet_signer(cfg)?;
+        let exchange = Exchange::new(exchange_chain(cfg.testnet));
+
+        // Cancels go first so a batch can replace resting orders.
+        if !cancels.is_empty() {
+            let resp = exchange
+                .cancel_order(wallet.clone(), cancels, cfg.vault_address())
+                .await?;
+            apply_batch_response(&resp, &cancel_lines, &mut results);
+        }
+        if !cloid_cancels.is_empty() {
+            let resp = exchange
+                .cancel_order_by_cloid(wallet.clone(), cloid_cancels, cfg.vault_address())
+                .await?;
+            apply_batch_response(&resp, &cloid_cancel_lines, &mut results);
+            for &i in &cloid_cancel_lines {
+                if let (Some(cloid), "success") = (&results[i].cloid, results[i].status.as_str()) {
+                    db::update_client_order(cloid, None, "cancelled")?;
+                }
+            }
+        }
+        if !orders.is_empty() {
+            let user = format!("{user:#x}");
+            for &i in &order_lines {
+                if let Some(cloid) = &results[i].cloid {
+                    db::record_client_order(cloid, &user, &results[i].coin)?;
+                }
+            }
+            let resp = exchange
+                .place_order(wallet, orders, cfg.vault_address())
+                .await?;
+            apply_batch_response(&resp, &order_lines, &mut results);
+            if let ExchangeResponse::Ok(data) = &resp
+                && let Some(StatusType::Statuses(statuses)) = &data.data
+            {
+                for (&i, status) in order_lines.iter().zip(statuses) {
+                    if let Some(cloid) = &results[i].cloid {
+                        let (oid, status) = client_order_outcome(status);
+                        db::update_client_order(cloid, oid, status)?;
+                    }
+                }
+            }
+        }
+    }
 
-    let mut max_leverage = 50;
-    for dex in &all_perp_metas {
-        if let Some(asset) = dex.universe.iter().find(|a| a.name == coin) {
-            max_leverage = asset.max_leverage;
-            break;
+    if output_opts.json {
+        if !cfg.dry_run {
+            output::print_json_pretty(&results)?;
         }
+    } else {
+        let rows = results
+            .iter()
+            .map(|r| {
+                vec![
+                    r.line.to_string(),
+                    r.kind.clone(),
+                    r.coin.clone(),
+                    r.status.clone(),
+                    r.oid.map(|oid| oid.to_string()).unwrap_or_default(),
+                    r.detail.clone(),
+                ]
+            })
+            .collect();
+        output::print_table(
+            &["Line", "Type", "Coin", "Status", "Order ID", "Detail"],
+            rows,
+        );
     }
 
-    let position = clearinghouse
-        .asset_positions
+    let failed = results
         .iter()
-        .map(|p| &p.position)
-        .find(|p| p.coin == coin && p.szi.parse::<f64>().unwrap_or(0.0) != 0.0)
-        .map(|p| LeveragePositionInfo {
-            size: p.szi.clone(),
-            value: p.position_value.clone(),
-        });
+        .filter(|r| {
+            matches!(
+                r.status.as_str(),
+                "invalid" | "blocked" | "error" | "unknown"
+            )
+        })
+        .count();
+    if failed > 0 {
+        return Err(anyhow!(
+            "{failed} of {} batch entries failed",
+            results.len()
+        ));
+    }
+    Ok(())
+}
 
-    let account_value_f = clearinghouse
-        .margin_summary
-        .account_value
-        .parse::<f64>()
-        .unwrap_or(0.0);
-    let total_margin_used_f = clearinghouse
-        .margin_summary
-        .total_margin_used
-        .parse::<f64>()
-        .unwrap_or(0.0);
-    let available_margin = (account_value_f - total_margin_used_f).max(0.0);
+/// The exchange rejects scheduled cancels less than 5 seconds out.
+const MIN_DEAD_MAN_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
 
-    Ok(LeverageInfo {
-        coin: coin.to_string(),
-        leverage: active.leverage,
-        max_leverage,
-        mark_px: active.mark_px,
-        max_trade_szs: active.max_trade_szs,
-        available_to_trade: active.available_to_trade,
-        position,
-        margin: LeverageMarginInfo {
-            account_value: clearinghouse.margin_summary.account_value,
-            total_margin_used: clearinghouse.margin_summary.total_margin_used,
-            available_margin: format!("{available_margin:.2}"),
+fn check_schedule_cancel_response(resp: &ExchangeResponse) -> Result<()> {
+    match resp {
+        ExchangeResponse::Err(e) => Err(anyhow!("Schedule cancel failed: {e}")),
+        ExchangeResponse::Ok(data) => match &data.data {
+            Some(StatusType::Status(Status::Error(e))) => {
+                Err(anyhow!("Schedule cancel failed: {e}"))
+            }
+            _ => Ok(()),
         },
-    })
+    }
 }
 
-async fn run_order(
-    cmd: OrderCommand,
+async fn order_dead_man(
     cfg: &config::LoadedConfig,
     api: &HlApi,
+    args: OrderDeadManArgs,
     output_opts: OutputOptions,
 ) -> Result<()> {
-    match cmd {
-        OrderCommand::Ls(args) => account_orders(cfg, api, args, output_opts).await,
-        OrderCommand::Configure(args) => order_configure(args, output_opts),
-        OrderCommand::Limit(args) => order_limit(cfg, api, args, output_opts).await,
-        OrderCommand::Market(args) => order_market(cfg, api, args, output_opts).await,
-        OrderCommand::Cancel(args) => order_cancel(cfg, api, args, output_opts).await,
-        OrderCommand::CancelAll(args) => order_cancel_all(cfg, api, args, output_opts).await,
-        OrderCommand::SetLeverage(args) => order_set_leverage(cfg, api, args, output_opts).await,
+    if args.heartbeat {
+        let mut client = require_server(cfg, "--heartbeat").await?;
+        if !client.heartbeat().await? {
+            return Err(anyhow!(
+                "No kept-alive dead-man switch is armed; arm one with 'rhl order dead-man --in <DURATION> --keepalive'"
+            ));
+        }
+        if output_opts.json {
+            output::print_json_pretty(&serde_json::json!({ "armed": true }))?;
+        } else {
+            output::print_success("Heartbeat sent");
+        }
+        return Ok(());
     }
-}
-
-fn order_configure(args: OrderConfigureArgs, output_opts: OutputOptions) -> Result<()> {
-    if let Some(s) = args.slippage {
-        let slippage = validation::validate_non_negative_number(&s, "slippage")?;
-        let cfg = order_config::update_order_config(Some(slippage))?;
+    if args.clear {
+        let action = L1Action::ScheduleCancel { time: None };
+        if cfg.dry_run {
+            return print_dry_run(cfg, api, &action, Vec::new(), output_opts).await;
+        }
+        // Stop the server re-arming before clearing, or it would reschedule.
+        if let Some(mut client) = ServerClient::try_connect().await? {
+            client.disarm_dead_man().await?;
+        }
+        let wallet = require_wallet_signer(cfg)?;
+        let exchange = Exchange::new(exchange_chain(cfg.testnet));
+        let resp = action.send(&exchange, wallet, cfg.vault_address()).await?;
+        check_schedule_cancel_response(&resp)?;
         if output_opts.json {
-            output::print_json_pretty(&cfg)?;
+            output::print_json_pretty(&resp)?;
         } else {
-            output::print_success(format!("Slippage set to {slippage}%"));
+            output::print_success("Dead-man switch cleared");
         }
         return Ok(());
     }
 
-    let cfg = order_config::load_order_config();
+    let after = validation::validate_duration(args.after.as_deref().unwrap_or_default(), "in")?;
+    if after < MIN_DEAD_MAN_DELAY {
+        return Err(anyhow!("--in must be at least 5s"));
+    }
+    let heartbeat_file = args
+        .heartbeat_file
+        .as_deref()
+        .map(|path| {
+            std::path::absolute(path)
+                .ok()
+                .filter(|p| p.exists())
+                .ok_or_else(|| anyhow!("Heartbeat file not found: {path}"))
+        })
+        .transpose()?;
+    let timeout_ms = after.as_millis() as i64;
+    let cancel_at = chrono::Utc::now().timestamp_millis() + timeout_ms;
+    let action = L1Action::ScheduleCancel {
+        time: Some(cancel_at as u64),
+    };
+
+    if cfg.dry_run {
+        return print_dry_run(cfg, api, &action, Vec::new(), output_opts).await;
+    }
+
+    let user = require_signer_address(cfg)?;
+    let mut server = None;
+    if args.keepalive {
+        server = Some(require_server(cfg, "--keepalive").await?);
+    }
+
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let resp = action.send(&exchange, wallet, cfg.vault_address()).await?;
+    check_schedule_cancel_response(&resp)?;
+
+    let keepalive = match &mut server {
+        Some(client) => Some(
+            client
+                .arm_dead_man(&ArmDeadManParams {
+                    user: format!("{user:#x}"),
+                    timeout_ms,
+                    cancel_at,
+                    heartbeat_file: heartbeat_file.map(|p| p.display().to_string()),
+                    vault: cfg.vault_address().map(|v| format!("{v:#x}")),
+                })
+                .await?,
+        ),
+        None => None,
+    };
+
     if output_opts.json {
-        output::print_json_pretty(&cfg)?;
+        output::print_json_pretty(&serde_json::json!({
+            "cancelAt": cancel_at,
+            "keepalive": keepalive,
+            "response": resp,
+        }))?;
     } else {
-        output::print_success("Current configuration:");
-        println!("  Slippage: {}%", cfg.slippage);
+        output::print_success(format!(
+            "Open orders will be cancelled at {} unless re-armed",
+            watch::format_datetime_ms(cancel_at)
+        ));
+        if let Some(status) = keepalive {
+            let source = match &status.heartbeat_file {
+                Some(path) => format!("{path} is touched or heartbeat requests arrive"),
+                None => "heartbeat requests arrive".to_string(),
+            };
+            println!(
+                "{}",
+                output::style_muted(format!(
+                    "rhl-server will re-arm it every {} while {source}",
+                    format_age(Some(status.timeout_ms / 3))
+                ))
+            );
+        }
     }
     Ok(())
 }
 
-async fn order_limit(
+/// Connect to rhl-server for a job it runs on this account's behalf. The
+/// server must be on the same network as the command.
+async fn require_server(cfg: &config::LoadedConfig, feature: &str) -> Result<ServerClient> {
+    let mut client = ServerClient::try_connect()
+        .await?
+        .ok_or_else(|| anyhow!("{feature} needs rhl-server; start it with 'rhl server start'"))?;
+    if client.get_status().await?.testnet != cfg.testnet {
+        return Err(anyhow!(
+            "rhl-server is running on a different network; restart it with the same --testnet setting"
+        ));
+    }
+    Ok(client)
+}
+
+async fn order_trail(
     cfg: &config::LoadedConfig,
     api: &HlApi,
-    args: OrderLimitArgs,
+    args: OrderTrailArgs,
     output_opts: OutputOptions,
 ) -> Result<()> {
-    let side = validation::validate_side_with_aliases(&args.side)?;
-    let size = validation::validate_positive_number(&args.size, "size")?;
-    let limit_px = validation::validate_positive_number(&args.price, "price")?;
-    let tif = validation::validate_tif(&args.tif)?;
-    let is_buy = side == "buy";
-
-    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
-    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &args.coin)?;
-
-    let wallet = require_wallet_signer(cfg)?;
-    let exchange = Exchange::new(exchange_chain(cfg.testnet));
-    let order = OrderRequest {
-        asset,
-        is_buy,
-        limit_px: limit_px.to_string(),
-        sz: size.to_string(),
-        reduce_only: args.reduce_only,
-        order_type: OrderType::Limit(Limit { tif }),
-        cloid: None,
-    };
-
-    let resp = exchange.place_order(wallet, vec![order], None).await?;
-    if output_opts.json {
-        output::print_json_pretty(&resp)?;
-    } else {
-        print_exchange_response(&resp);
+    match args.command {
+        Some(OrderTrailCommand::Ls) => order_trail_ls(cfg, output_opts).await,
+        Some(OrderTrailCommand::Rm(rm)) => order_trail_rm(cfg, rm, output_opts).await,
+        None => order_trail_add(cfg, api, args, output_opts).await,
     }
-    Ok(())
 }
 
-async fn order_market(
+async fn order_trail_add(
     cfg: &config::LoadedConfig,
     api: &HlApi,
-    args: OrderMarketArgs,
+    args: OrderTrailArgs,
     output_opts: OutputOptions,
 ) -> Result<()> {
-    let side = validation::validate_side_with_aliases(&args.side)?;
-    let size = validation::validate_positive_number(&args.size, "size")?;
-    let is_buy = side == "buy";
+    let distance = TrailDistance::parse(args.distance.as_deref().unwrap_or_default())?;
+    let coin = resolve_coin(api, args.coin.as_deref().unwrap_or_default()).await?;
+    let slippage = resolve_slippage_pct(args.slippage.as_deref(), Some(&coin))?;
 
-    let (all_perp_metas, spot_meta, mids) =
-        tokio::try_join!(api.all_perp_metas(), api.spot_meta(), api.all_mids())?;
-    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &args.coin)?;
+    let user = require_wallet_address(cfg)?;
+    let dex = asset_index::coin_dex(&coin);
+    let (state, all_perp_metas, spot_meta, mids) = tokio::try_join!(
+        api.clearinghouse_state_for_dex(user, dex),
+        api.all_perp_metas(),
+        api.spot_meta(),
+        api.all_mids_for_dex(dex)
+    )?;
+    let szi = state
+        .asset_positions
+        .iter()
+        .map(|p| &p.position)
+        .find(|p| p.coin == coin)
+        .and_then(|p| p.szi.parse::<f64>().ok())
+        .filter(|szi| *szi != 0.0)
+        .ok_or_else(|| anyhow!("No open position in {coin}"))?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &coin)?;
+    let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, &coin)?;
+    let size = match args.size.as_deref() {
+        Some(size) => {
+            let size =
+                precision.check_size(validation::validate_positive_number(size, "size")?, false)?;
+            if size > szi.abs() {
+                return Err(anyhow!(
+                    "--size {size} is larger than the {coin} position ({})",
+                    szi.abs()
+                ));
+            }
+            size
+        }
+        None => szi.abs(),
+    };
+    let mid_price = mid_price_for(&mids, &coin)?;
+    let is_long = szi > 0.0;
+    let stop = TrailingStop::new(is_long, distance, mid_price);
+    let describe = |stop: &TrailingStop| {
+        format!(
+            "{} {} {coin} once the price {} {distance} from its {} (stop now {})",
+            if is_long { "sell" } else { "buy" },
+            precision.format_size(size),
+            if is_long { "falls" } else { "rises" },
+            if is_long { "high" } else { "low" },
+            precision.format_price(stop.stop_px())
+        )
+    };
 
-    let mid_price: f64 = mids
-        .get(&args.coin)
-        .ok_or_else(|| anyhow!("Cannot get mid price for {}", args.coin))?
-        .parse()
-        .map_err(|_| anyhow!("Invalid mid price for {}", args.coin))?;
+    if cfg.dry_run {
+        // The order the server would send if the stop fired now.
+        if !output_opts.json {
+            println!("Trailing stop: {}", describe(&stop));
+        }
+        let order = stop.close_order(asset, size, stop.stop_px(), slippage, &precision);
+        let action = Action::Order {
+            orders: vec![order],
+            grouping: Grouping::Na,
+        };
+        return dry_run_action(cfg, api, &action, output_opts).await;
+    }
 
-    let config_slippage = order_config::load_order_config().slippage;
-    let slippage_pct = if let Some(s) = args.slippage {
-        validation::validate_non_negative_number(&s, "slippage")?
-    } else {
-        config_slippage
-    } / 100.0;
+    let mut client = require_server(cfg, "order trail").await?;
+    let status = client
+        .add_trail(&AddTrailParams {
+            user: format!("{:#x}", require_signer_address(cfg)?),
+            vault: cfg.vault_address().map(|v| format!("{v:#x}")),
+            coin: coin.clone(),
+            asset,
+            sz_decimals: precision.sz_decimals,
+            is_long,
+            size,
+            distance,
+            slippage,
+            start_px: mid_price,
+        })
+        .await?;
 
-    let limit_px = if is_buy {
-        mid_price * (1.0 + slippage_pct)
+    if output_opts.json {
+        output::print_json_pretty(&status)?;
     } else {
-        mid_price * (1.0 - slippage_pct)
-    };
+        output::print_success(format!(
+            "Trailing stop {}: {}",
+            status.id,
+            describe(&status.stop)
+        ));
+        println!(
+            "{}",
+            output::style_muted(format!(
+                "rhl-server follows it; see 'rhl order trail ls', cancel with 'rhl order trail rm {}'",
+                status.id
+            ))
+        );
+    }
+    Ok(())
+}
 
-    let wallet = require_wallet_signer(cfg)?;
-    let exchange = Exchange::new(exchange_chain(cfg.testnet));
-    let order = OrderRequest {
-        asset,
-        is_buy,
-        limit_px: format!("{limit_px:.6}"),
-        sz: size.to_string(),
-        reduce_only: args.reduce_only,
-        order_type: OrderType::Limit(Limit {
-            tif: hyperliquid::types::exchange::request::Tif::Ioc,
-        }),
-        cloid: None,
-    };
+async fn order_trail_ls(cfg: &config::LoadedConfig, output_opts: OutputOptions) -> Result<()> {
+    let trails = require_server(cfg, "order trail")
+        .await?
+        .list_trails()
+        .await?;
+    if output_opts.json {
+        output::print_json_pretty(&trails)?;
+        return Ok(());
+    }
+    if trails.is_empty() {
+        output::print_success("No trailing stops");
+        return Ok(());
+    }
+
+    let rows = trails
+        .iter()
+        .map(|t| {
+            let state = match t.state {
+                TrailState::Active => output::style_profit("active"),
+                TrailState::Firing => "firing".to_string(),
+                TrailState::Fired => "fired".to_string(),
+                TrailState::Failed => output::style_loss("failed"),
+            };
+            vec![
+                t.id.to_string(),
+                match &t.vault {
+                    Some(vault) => format!("{} ({})", t.coin, output::format_short_address(vault)),
+                    None => t.coin.clone(),
+                },
+                if t.stop.is_long { "Sell" } else { "Buy" }.to_string(),
+                t.size.to_string(),
+                t.stop.distance.to_string(),
+                t.stop.extreme_px.to_string(),
+                format!("{:.6}", t.stop_px)
+                    .trim_end_matches('0')
+                    .trim_end_matches('.')
+                    .to_string(),
+                t.last_px
+                    .map(|px| px.to_string())
+                    .unwrap_or_else(|| output::style_muted("-")),
+                state,
+                t.result.clone().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    output::print_table_with_columns(
+        &[
+            output::TableColumn::right("ID"),
+            output::TableColumn::left("Coin"),
+            output::TableColumn::left("Close"),
+            output::TableColumn::right("Size"),
+            output::TableColumn::right("Distance"),
+            output::TableColumn::right("High/Low"),
+            output::TableColumn::right("Stop"),
+            output::TableColumn::right("Last"),
+            output::TableColumn::left("State"),
+            output::TableColumn::left("Result"),
+        ],
+        rows,
+    );
+    Ok(())
+}
 
-    let resp = exchange.place_order(wallet, vec![order], None).await?;
+async fn order_trail_rm(
+    cfg: &config::LoadedConfig,
+    args: OrderTrailRmArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let id = args
+        .id
+        .as_deref()
+        .map(|id| validation::validate_positive_u64(id, "id"))
+        .transpose()?;
+    let removed = require_server(cfg, "order trail")
+        .await?
+        .remove_trail(id)
+        .await?;
     if output_opts.json {
-        output::print_json_pretty(&resp)?;
+        output::print_json_pretty(&serde_json::json!({ "removed": removed }))?;
     } else {
-        print_exchange_response(&resp);
+        match id {
+            Some(id) => output::print_success(format!("Trailing stop {id} removed")),
+            None => output::print_success(format!("Removed {removed} trailing stops")),
+        }
     }
     Ok(())
 }
 
-async fn order_cancel(
+async fn order_close(
     cfg: &config::LoadedConfig,
     api: &HlApi,
-    args: OrderCancelArgs,
+    mut args: OrderCloseArgs,
     output_opts: OutputOptions,
 ) -> Result<()> {
-    let user = require_wallet_address(cfg)?;
-    let open_orders = api.open_orders(user).await?;
-    if open_orders.is_empty() && args.oid.is_none() {
-        output::print_success("No open orders to cancel");
-        return Ok(());
+    let pct = validation::validate_positive_number(&args.pct, "pct")?;
+    if pct > 100.0 {
+        return Err(anyhow!("pct must be between 0 and 100"));
     }
+    args.coin = resolve_coin(api, &args.coin).await?;
+    let slippage_pct = resolve_slippage_pct(args.slippage.as_deref(), Some(&args.coin))?;
 
-    let (order_id, coin) = if let Some(oid_arg) = args.oid {
-        let oid = validation::validate_positive_u64(&oid_arg, "oid")?;
-        let order = open_orders
-            .iter()
-            .find(|o| o.oid == oid)
-            .ok_or_else(|| anyhow!("Order {oid} not found in open orders"))?;
-        (oid, order.coin.clone())
-    } else {
-        let options = open_orders
-            .iter()
-            .map(|o| prompt::SelectOption {
-                value: (o.oid, o.coin.clone()),
-                label: format!(
-                    "{}: {} {} {} @ {}",
-                    o.oid,
-                    o.coin,
-                    if o.side == "B" { "Buy" } else { "Sell" },
-                    o.sz,
-                    o.limit_px
-                ),
-            })
-            .collect();
-        prompt::select("Select order to cancel:", options)?
-    };
+    let user = require_wallet_address(cfg)?;
+    let dex = asset_index::coin_dex(&args.coin);
+    let (state, all_perp_metas, spot_meta, mids) = tokio::try_join!(
+        api.clearinghouse_state_for_dex(user, dex),
+        api.all_perp_metas(),
+        api.spot_meta(),
+        api.all_mids_for_dex(dex)
+    )?;
+    let position = state
+        .asset_positions
+        .iter()
+        .map(|p| &p.position)
+        .find(|p| p.coin == args.coin)
+        .ok_or_else(|| anyhow!("No open position in {}", args.coin))?;
 
-    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
-    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &coin)?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &args.coin)?;
+    let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, &args.coin)?;
+    let mid_price = mid_price_for(&mids, &args.coin)?;
+    let order = build_close_order(position, asset, pct, mid_price, slippage_pct, &precision)?;
+
+    if cfg.dry_run {
+        let action = Action::Order {
+            orders: vec![order],
+            grouping: Grouping::Na,
+        };
+        return dry_run_action(cfg, api, &action, output_opts).await;
+    }
 
     let wallet = require_wallet_signer(cfg)?;
     let exchange = Exchange::new(exchange_chain(cfg.testnet));
     let resp = exchange
-        .cancel_order(
-            wallet,
-            vec![CancelRequest {
-                asset,
-                oid: order_id,
-            }],
-            None,
-        )
+        .place_order(wallet, vec![order], cfg.vault_address())
         .await?;
-
     if output_opts.json {
         output::print_json_pretty(&resp)?;
     } else {
-        output::print_success(format!("Order {order_id} cancelled"));
+        let label = format!("Close {}", args.coin);
+        print_exchange_response(&resp, &[label.as_str()]);
     }
     Ok(())
 }
 
-async fn order_cancel_all(
+async fn order_flatten(
     cfg: &config::LoadedConfig,
     api: &HlApi,
-    args: OrderCancelAllArgs,
+    args: OrderFlattenArgs,
     output_opts: OutputOptions,
 ) -> Result<()> {
     let user = require_wallet_address(cfg)?;
-    let open_orders = api.open_orders(user).await?;
-    if open_orders.is_empty() {
-        output::print_success("No open orders to cancel");
+    let (state, mut open_orders, all_perp_metas, spot_meta) = tokio::try_join!(
+        api.clearinghouse_state(user),
+        api.open_orders(user),
+        api.all_perp_metas(),
+        api.spot_meta()
+    )?;
+    // Builder-dex positions and orders live in separate clearinghouses.
+    let mut asset_positions = state.asset_positions;
+    for (dex_index, meta) in all_perp_metas.iter().enumerate().skip(1) {
+        if let Some(dex) = asset_index::perp_dex_name(dex_index, meta) {
+            let (dex_state, dex_orders) = tokio::try_join!(
+                api.clearinghouse_state_for_dex(user, Some(&dex)),
+                api.open_orders_for_dex(user, &dex)
+            )?;
+            asset_positions.extend(dex_state.asset_positions);
+            open_orders.extend(dex_orders);
+        }
+    }
+    open_orders.sort_by_key(|o| o.oid);
+    open_orders.dedup_by_key(|o| o.oid);
+    let positions: Vec<&hl_api::Position> = asset_positions
+        .iter()
+        .map(|p| &p.position)
+        .filter(|p| p.szi.parse::<f64>().is_ok_and(|szi| szi != 0.0))
+        .collect();
+    let mids = mids_for_coins(api, positions.iter().map(|p| p.coin.as_str())).await?;
+    let open_orders = if args.cancel_orders {
+        open_orders
+    } else {
+        Vec::new()
+    };
+    if positions.is_empty() && open_orders.is_empty() {
+        output::print_success("No open positions to close");
         return Ok(());
     }
 
-    let mut orders_to_cancel = open_orders;
-    if let Some(coin) = &args.coin {
-        orders_to_cancel.retain(|o| &o.coin == coin);
-        if orders_to_cancel.is_empty() {
-            output::print_success(format!("No open orders for {coin}"));
-            return Ok(());
+    if !args.yes && !cfg.dry_run {
+        let mut confirm_msg = format!("Close {} positions", positions.len());
+        if args.cancel_orders {
+            confirm_msg.push_str(&format!(" and cancel {} open orders", open_orders.len()));
         }
-    }
-
-    if !args.yes {
-        let confirm_msg = if let Some(coin) = &args.coin {
-            format!("Cancel all {} orders for {coin}?", orders_to_cancel.len())
-        } else {
-            format!("Cancel all {} open orders?", orders_to_cancel.len())
-        };
-        let confirmed = prompt::confirm(&confirm_msg, false)?;
-        if !confirmed {
+        confirm_msg.push('?');
+        if !confirm_trade(cfg, &confirm_msg)? {
             output::print_success("Cancelled");
             return Ok(());
         }
     }
 
-    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
-    let cancels: Vec<CancelRequest> = orders_to_cancel
+    let cancels = open_orders
         .iter()
         .map(|o| {
             let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &o.coin)?;
             Ok(CancelRequest { asset, oid: o.oid })
         })
         .collect::<Result<Vec<_>>>()?;
+    let closes = positions
+        .iter()
+        .map(|p| {
+            let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &p.coin)?;
+            let precision = AssetPrecision::resolve(&all_perp_metas, &spot_meta, &p.coin)?;
+            let mid_price = mid_price_for(&mids, &p.coin)?;
+            let slippage_pct = resolve_slippage_pct(args.slippage.as_deref(), Some(&p.coin))?;
+            build_close_order(p, asset, 100.0, mid_price, slippage_pct, &precision)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if cfg.dry_run {
+        let mut preview = DryRun::default();
+        if !cancels.is_empty() {
+            preview = preview.action(&Action::Cancel { cancels })?;
+        }
+        if !closes.is_empty() {
+            preview = preview.action(&Action::Order {
+                orders: closes,
+                grouping: Grouping::Na,
+            })?;
+        }
+        return preview.print(cfg, api, output_opts).await;
+    }
+
+    let wallet = require_wallet_signer(cfg)?;
+    let exchange = Exchange::new(exchange_chain(cfg.testnet));
+    let cancel_resp = if cancels.is_empty() {
+        None
+    } else {
+        Some(
+            exchange
+                .cancel_order(wallet.clone(), cancels, cfg.vault_address())
+                .await?,
+        )
+    };
+    let close_resp = if closes.is_empty() {
+        None
+    } else {
+        Some(
+            exchange
+                .place_order(wallet, closes, cfg.vault_address())
+                .await?,
+        )
+    };
+
+    if output_opts.json {
+        output::print_json_pretty(&serde_json::json!({
+            "cancel": cancel_resp,
+            "close": close_resp,
+        }))?;
+    } else {
+        if cancel_resp.is_some() {
+            output::print_success(format!("Cancelled {} orders", open_orders.len()));
+        }
+        if let Some(resp) = &close_resp {
+            let labels: Vec<String> = positions
+                .iter()
+                .map(|p| format!("Close {}", p.coin))
+                .collect();
+            let legs: Vec<&str> = labels.iter().map(String::as_str).collect();
+            print_exchange_response(resp, &legs);
+        }
+    }
+    Ok(())
+}
+
+async fn order_set_leverage(
+    cfg: &config::LoadedConfig,
+    api: &HlApi,
+    mut args: OrderSetLeverageArgs,
+    output_opts: OutputOptions,
+) -> Result<()> {
+    let leverage = validation::validate_positive_u64(&args.leverage, "leverage")? as u32;
+    account_risk_limits(cfg)?.check_leverage_setting(leverage)?;
+
+    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    args.coin = asset_index::resolve_coin(&all_perp_metas, &spot_meta, &args.coin)?;
+    let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &args.coin)?;
+
+    let is_cross = args.cross || !args.isolated;
+
+    if cfg.dry_run {
+        let action = Action::UpdateLeverage {
+            asset,
+            is_cross,
+            leverage,
+        };
+        return dry_run_action(cfg, api, &action, output_opts).await;
+    }
 
     let wallet = require_wallet_signer(cfg)?;
     let exchange = Exchange::new(exchange_chain(cfg.testnet));
-    let resp = exchange.cancel_order(wallet, cancels, None).await?;
+    let resp = L1Action::UpdateLeverage {
+        asset,
+        is_cross,
+        leverage,
+    }
+    .send(&exchange, wallet, cfg.vault_address())
+    .await?;
 
     if output_opts.json {
         output::print_json_pretty(&resp)?;
     } else {
-        output::print_success(format!("Cancelled {} orders", orders_to_cancel.len()));
+        output::print_success(format!(
+            "Leverage set to {leverage}x ({}) for {}",
+            if is_cross { "cross" } else { "isolated" },
+            args.coin
+        ));
+    }
+    Ok(())
+}
+
+/// Estimated liquidation price after changing an isolated position's margin
+/// by `margin_delta` USD.
+///
+/// Follows the exchange formula
+/// `liq = price - side * margin / |size| / (1 - side / maintenance_leverage)`,
+/// where maintenance leverage is twice the asset's max leverage, so the
+/// liquidation price moves by `side * margin_delta / |size| / (1 - side / maintenance_leverage)`.
+fn estimate_liquidation_px(liq_px: f64, szi: f64, margin_delta: f64, max_leverage: u32) -> f64 {
+    let side = szi.signum();
+    let maintenance = 1.0 / (2.0 * max_leverage as f64);
+    liq_px - side * margin_delta / szi.abs() / (1.0 - side * maintenance)
+}
+
+fn isolated_position<'a>(
+    state: &'a hl_api::ClearinghouseState,
+    coin: &str,
+) -> Result<&'a hl_api::Position> {
+    let position = state
+        .asset_positions
+        .iter()
+        .map(|p| &p.position)
+        .find(|p| p.coin == coin && p.szi.parse::<f64>().is_ok_and(|szi| szi != 0.0))
+        .ok_or_else(|| anyhow!("No open position in {coin}"))?;
+    if position.leverage.leverage_type != "isolated" {
+        return Err(anyhow!(
+            "{coin} position uses {} margin; switch with `order set-leverage {coin} <leverage> --isolated`",
+            position.leverage.leverage_type
+        ));
     }
-    Ok(())
+    Ok(position)
 }
 
-async fn order_set_leverage(
+async fn order_margin(
     cfg: &config::LoadedConfig,
     api: &HlApi,
-    args: OrderSetLeverageArgs,
+    mut args: OrderMarginArgs,
+    add: bool,
     output_opts: OutputOptions,
 ) -> Result<()> {
-    let leverage = validation::validate_positive_u64(&args.leverage, "leverage")? as u32;
+    let amount = validation::validate_positive_number(&args.amount, "amount")?;
+    // The exchange takes the margin delta in micro-USD.
+    let ntli = (amount * 1e6).round() as i64 * if add { 1 } else { -1 };
+    args.coin = resolve_coin(api, &args.coin).await?;
 
-    let (all_perp_metas, spot_meta) = tokio::try_join!(api.all_perp_metas(), api.spot_meta())?;
+    let user = require_wallet_address(cfg)?;
+    let dex = asset_index::coin_dex(&args.coin);
+    let (state, all_perp_metas, spot_meta) = tokio::try_join!(
+        api.clearinghouse_state_for_dex(user, dex),
+        api.all_perp_metas(),
+        api.spot_meta()
+    )?;
     let asset = asset_index::resolve_asset_index(&all_perp_metas, &spot_meta, &args.coin)?;
-
-    let is_cross = args.cross || !args.isolated;
+    let position = isolated_position(&state, &args.coin)?;
+    let liq_before = position
+        .liquidation_px
+        .as_deref()
+        .and_then(|px| px.parse::<f64>().ok());
+    let max_leverage = all_perp_metas
+        .iter()
+        .flat_map(|dex| &dex.universe)
+        .find(|a| a.name == args.coin)
+        .map(|a| a.max_leverage)
+        .ok_or_else(|| anyhow!("Unknown perp: {}", args.coin))?;
+    let szi: f64 = position.szi.parse()?;
+    let liq_estimate =
+        liq_before.map(|px| estimate_liquidation_px(px, szi, ntli as f64 / 1e6, max_leverage));
+
+    if cfg.dry_run {
+        if let (Some(before), Some(after), false) = (liq_before, liq_estimate, output_opts.json) {
+            println!("Liquidation price: ${before} -> ~${after:.2} (estimated)");
+        }
+        let action = Action::UpdateIsolatedMargin {
+            asset,
+            is_buy: true,
+            ntli,
+        };
+        return dry_run_action(cfg, api, &action, output_opts).await;
+    }
 
     let wallet = require_wallet_signer(cfg)?;
     let exchange = Exchange::new(exchange_chain(cfg.testnet));
-    let resp = exchange
-        .update_leverage(wallet, leverage, asset, is_cross)
-        .await?;
+    let resp = L1Action::UpdateIsolatedMargin {
+        asset,
+        is_buy: true,
+        ntli,
+    }
+    .send(&exchange, wallet, cfg.vault_address())
+    .await?;
+    if let ExchangeResponse::Err(err) = &resp {
+        return Err(anyhow!("Margin update failed: {err}"));
+    }
+
+    let state = api.clearinghouse_state_for_dex(user, dex).await?;
+    let liq_after = isolated_position(&state, &args.coin)
+        .ok()
+        .and_then(|p| p.liquidation_px.clone());
 
     if output_opts.json {
-        output::print_json_pretty(&resp)?;
+        output::print_json_pretty(&serde_json::json!({
+            "response": resp,
+            "liquidationPxBefore": position.liquidation_px,
+            "liquidationPxAfter": liq_after,
+        }))?;
     } else {
         output::print_success(format!(
-            "Leverage set to {leverage}x ({}) for {}",
-            if is_cross { "cross" } else { "isolated" },
+            "{} ${amount} {} {} isolated margin",
+            if add { "Added" } else { "Removed" },
+            if add { "to" } else { "from" },
             args.coin
         ));
+        println!(
+            "Liquidation price: ${} -> ${}",
+            position.liquidation_px.as_deref().unwrap_or("-"),
+            liq_after.as_deref().unwrap_or("-")
+        );
     }
     Ok(())
 }
@@ -3439,16 +8847,24 @@ async fn run_referral(
     output_opts: OutputOptions,
 ) -> Result<()> {
     match cmd {
-        ReferralCommand::Set(args) => referral_set(cfg, args, output_opts).await,
+        ReferralCommand::Set(args) => referral_set(cfg, api, args, output_opts).await,
         ReferralCommand::Status => referral_status(cfg, api, output_opts).await,
     }
 }
 
 async fn referral_set(
     cfg: &config::LoadedConfig,
+    api: &HlApi,
     args: ReferralSetArgs,
     output_opts: OutputOptions,
 ) -> Result<()> {
+    if cfg.dry_run {
+        let action = Action::SetReferrer {
+            code: args.code.clone(),
+        };
+        return dry_run_action(cfg, api, &action, output_opts).await;
+    }
+
     let wallet = require_wallet_signer(cfg)?;
     let exchange = Exchange::new(exchange_chain(cfg.testnet));
     let resp = exchange.set_referrer(wallet, args.code.clone()).await?;
@@ -3519,36 +8935,6 @@ fn format_age(ms: Option<i64>) -> String {
     format!("{minutes}m {}s", seconds % 60)
 }
 
-#[cfg(test)]
-mod server_format_tests {
-    use super::*;
-
-    #[test]
-    fn format_uptime_matches_js_helpers() {
-        assert_eq!(format_uptime(30_000), "30s");
-        assert_eq!(format_uptime(90_000), "1m 30s");
-        assert_eq!(format_uptime(3_660_000), "1h 1m");
-        assert_eq!(format_uptime(90_000_000), "1d 1h");
-        assert_eq!(format_uptime(0), "0s");
-        assert_eq!(format_uptime(60_000), "1m 0s");
-        assert_eq!(format_uptime(3_600_000), "1h 0m");
-        assert_eq!(format_uptime(86_400_000), "1d 0h");
-        assert_eq!(format_uptime(172_800_000 + 3_600_000 * 5), "2d 5h");
-    }
-
-    #[test]
-    fn format_age_matches_js_helpers() {
-        assert_eq!(format_age(None), "unknown");
-        assert_eq!(format_age(Some(500)), "500ms");
-        assert_eq!(format_age(Some(30_000)), "30s");
-        assert_eq!(format_age(Some(90_000)), "1m 30s");
-        assert_eq!(format_age(Some(1_000)), "1s");
-        assert_eq!(format_age(Some(60_000)), "1m 0s");
-        assert_eq!(format_age(Some(0)), "0ms");
-        assert_eq!(format_age(Some(999)), "999ms");
-    }
-}
-
 fn find_rhl_server_exe() -> Result<std::path::PathBuf> {
     let exe = std::env::current_exe().context("Failed to locate current executable")?;
     let dir = exe
@@ -3707,6 +9093,22 @@ async fn server_status(output_opts: OutputOptions) -> Result<()> {
                     }
                 );
                 println!("Uptime:     {}", format_uptime(status.uptime));
+                if let Some(dead_man) = &status.dead_man {
+                    println!(
+                        "Dead-man:   cancels at {} (re-armed while heartbeats arrive within {})",
+                        watch::format_datetime_ms(dead_man.cancel_at),
+                        format_age(Some(dead_man.timeout_ms))
+                    );
+                    if let Some(vault) = &dead_man.vault {
+                        println!("            vault: {vault}");
+                    }
+                    if let Some(err) = &dead_man.last_error {
+                        println!("            last error: {err}");
+                    }
+                }
+                if status.active_trails > 0 {
+                    println!("Trailing:   {} active stops", status.active_trails);
+                }
                 println!();
                 println!("Cache:");
                 println!(
@@ -3870,3 +9272,643 @@ async fn run_upgrade(output_opts: OutputOptions) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod order_tests {
+    use super::*;
+
+    const ETH: AssetPrecision = AssetPrecision {
+        sz_decimals: 4,
+        is_spot: false,
+    };
+
+    #[test]
+    fn validate_bracket_checks_sides() {
+        assert!(validate_bracket(true, 100.0, Some(110.0), Some(90.0)).is_ok());
+        assert!(validate_bracket(false, 100.0, Some(90.0), Some(110.0)).is_ok());
+        assert!(validate_bracket(true, 100.0, None, None).is_ok());
+
+        assert!(validate_bracket(true, 100.0, Some(95.0), None).is_err());
+        assert!(validate_bracket(true, 100.0, None, Some(105.0)).is_err());
+        assert!(validate_bracket(false, 100.0, Some(105.0), None).is_err());
+        assert!(validate_bracket(false, 100.0, None, Some(95.0)).is_err());
+    }
+
+    #[test]
+    fn build_bracket_adds_opposite_reduce_only_children() {
+        let entry = OrderRequest {
+            asset: 3,
+            is_buy: true,
+            limit_px: "100".to_string(),
+            sz: "2".to_string(),
+            reduce_only: false,
+            order_type: OrderType::Limit(Limit {
+                tif: hyperliquid::types::exchange::request::Tif::Gtc,
+            }),
+            cloid: None,
+        };
+        let (orders, legs) = build_bracket(entry, Some(110.0), Some(90.0), 0.01, &ETH);
+        assert_eq!(legs, vec!["Entry", "Take profit", "Stop loss"]);
+        assert_eq!(orders.len(), 3);
+        for child in &orders[1..] {
+            assert_eq!(child.asset, 3);
+            assert!(!child.is_buy);
+            assert!(child.reduce_only);
+            assert_eq!(child.sz, "2");
+        }
+        assert!(matches!(
+            &orders[1].order_type,
+            OrderType::Trigger(Trigger { tpsl: TpSl::Tp, .. })
+        ));
+        assert!(matches!(
+            &orders[2].order_type,
+            OrderType::Trigger(Trigger { tpsl: TpSl::Sl, .. })
+        ));
+        assert_eq!(orders[2].limit_px, "89.1");
+    }
+
+    fn resting_order(is_trigger: bool) -> FrontendOpenOrder {
+        FrontendOpenOrder {
+            coin: "ETH".to_string(),
+            limit_px: "3000".to_string(),
+            oid: 42,
+            side: "A".to_string(),
+            sz: "1.5".to_string(),
+            timestamp: 0,
+            orig_sz: None,
+            order_type: if is_trigger {
+                "Take Profit Market".to_string()
+            } else {
+                "Limit".to_string()
+            },
+            is_trigger,
+            trigger_condition: String::new(),
+            trigger_px: if is_trigger {
+                "3100".to_string()
+            } else {
+                "0.0".to_string()
+            },
+            reduce_only: is_trigger,
+            is_position_tpsl: false,
+            tif: (!is_trigger).then(|| "Alo".to_string()),
+            cloid: None,
+        }
+    }
+
+    #[test]
+    fn modify_keeps_unchanged_fields() {
+        let amendment = OrderAmendment {
+            price: Some(3050.0),
+            ..Default::default()
+        };
+        let req = build_modify_request(&resting_order(false), 1, amendment, None, &ETH).unwrap();
+        assert_eq!(req.oid, 42);
+        assert_eq!(req.order.asset, 1);
+        assert!(!req.order.is_buy);
+        assert_eq!(req.order.limit_px, "3050");
+        assert_eq!(req.order.sz, "1.5");
+        assert!(matches!(
+            req.order.order_type,
+            OrderType::Limit(Limit { tif: Tif::Alo })
+        ));
+    }
+
+    #[test]
+    fn modify_trigger_order_keeps_kind() {
+        let amendment = OrderAmendment {
+            trigger_px: Some(3200.0),
+            ..Default::default()
+        };
+        let req = build_modify_request(&resting_order(true), 1, amendment, None, &ETH).unwrap();
+        assert!(req.order.reduce_only);
+        let OrderType::Trigger(trigger) = req.order.order_type else {
+            panic!("expected trigger order");
+        };
+        assert!(trigger.is_market);
+        assert!(matches!(trigger.tpsl, TpSl::Tp));
+        assert_eq!(trigger.trigger_px, "3200");
+
+        assert!(
+            build_modify_request(&resting_order(true), 1, amendment, Some("Gtc"), &ETH).is_err()
+        );
+        assert!(build_modify_request(&resting_order(false), 1, amendment, None, &ETH).is_err());
+    }
+
+    #[test]
+    fn modify_keeps_client_order_id() {
+        let mut order = resting_order(false);
+        order.cloid = Some("0x00000000000000000000000000000001".to_string());
+        let req = build_modify_request(&order, 1, OrderAmendment::default(), None, &ETH).unwrap();
+        assert_eq!(
+            req.order
+                .cloid
+                .map(|c| validation::format_cloid(&c))
+                .as_deref(),
+            order.cloid.as_deref()
+        );
+    }
+
+    #[test]
+    fn modify_rejects_unknown_tif_and_unsized_position_tpsl() {
+        let mut order = resting_order(false);
+        order.tif = Some("FrontendMarket".to_string());
+        assert!(build_modify_request(&order, 1, OrderAmendment::default(), None, &ETH).is_err());
+
+        let mut order = resting_order(true);
+        order.is_position_tpsl = true;
+        order.sz = "0.0".to_string();
+        assert!(build_modify_request(&order, 1, OrderAmendment::default(), None, &ETH).is_err());
+    }
+
+    #[test]
+    fn order_size_positional_is_left_out_when_sizing_by_flag() {
+        let parse = |args: &[&str]| {
+            let cli = Cli::try_parse_from(args)?;
+            let Command::Order { command } = cli.command else {
+                unreachable!()
+            };
+            match *command {
+                OrderCommand::Limit(args) => args.positionals().map(|(size, coin, price)| {
+                    (
+                        size.map(str::to_string),
+                        coin.to_string(),
+                        price.to_string(),
+                    )
+                }),
+                _ => unreachable!(),
+            }
+        };
+        assert_eq!(
+            parse(&["rhl", "order", "limit", "buy", "0.5", "ETH", "3000"]).unwrap(),
+            (
+                Some("0.5".to_string()),
+                "ETH".to_string(),
+                "3000".to_string()
+            )
+        );
+        assert_eq!(
+            parse(&[
+                "rhl", "order", "limit", "buy", "ETH", "3000", "--usd", "100"
+            ])
+            .unwrap(),
+            (None, "ETH".to_string(), "3000".to_string())
+        );
+        assert!(
+            parse(&[
+                "rhl", "order", "limit", "buy", "1", "ETH", "3000", "--usd", "100"
+            ])
+            .is_err()
+        );
+        assert!(parse(&["rhl", "order", "limit", "buy", "ETH", "3000"]).is_err());
+        assert!(
+            parse(&[
+                "rhl",
+                "order",
+                "limit",
+                "buy",
+                "ETH",
+                "3000",
+                "--usd",
+                "1",
+                "--equity-pct",
+                "1"
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn reduce_only_flags_override_configured_default() {
+        let reduce_only = |args: &[&str], default: Option<bool>| {
+            let cli = Cli::try_parse_from(
+                ["rhl", "order", "market", "buy", "1", "ETH"]
+                    .iter()
+                    .chain(args),
+            )
+            .unwrap();
+            let Command::Order { command } = cli.command else {
+                unreachable!()
+            };
+            let OrderCommand::Market(args) = *command else {
+                unreachable!()
+            };
+            args.reduce_only.resolve(&order_config::OrderDefaults {
+                reduce_only: default,
+                ..Default::default()
+            })
+        };
+        assert!(!reduce_only(&[], None));
+        assert!(reduce_only(&[], Some(true)));
+        assert!(!reduce_only(&["--no-reduce-only"], Some(true)));
+        assert!(reduce_only(&["--reduce-only"], Some(false)));
+        assert!(reduce_only(&["--no-reduce-only", "--reduce-only"], None));
+    }
+
+    #[test]
+    fn ensure_success_requires_success_status() {
+        let resp = |v: serde_json::Value| serde_json::from_value::<ExchangeResponse>(v).unwrap();
+        let ok = resp(serde_json::json!({
+            "status": "ok",
+            "response": { "type": "cancel", "data": { "statuses": ["success"] } }
+        }));
+        assert!(ensure_success(&ok, "Cancel").is_ok());
+
+        let filled = resp(serde_json::json!({
+            "status": "ok",
+            "response": { "type": "cancel", "data": { "statuses": [
+                { "error": "Order was never placed, already canceled, or filled." }
+            ] } }
+        }));
+        assert!(ensure_success(&filled, "Cancel").is_err());
+        assert!(
+            ensure_success(
+                &resp(serde_json::json!({ "status": "err", "response": "x" })),
+                "Cancel"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn dry_run_lists_orders_in_action() {
+        let entry = OrderRequest {
+            asset: 1,
+            is_buy: true,
+            limit_px: "3000".to_string(),
+            sz: "0.5".to_string(),
+            reduce_only: false,
+            order_type: OrderType::Limit(Limit { tif: Tif::Alo }),
+            cloid: None,
+        };
+        let (orders, _) = build_bracket(entry, None, Some(2900.0), 0.01, &ETH);
+        let action = Action::Order {
+            orders,
+            grouping: Grouping::NormalTpsl,
+        };
+        let preview = dry_run_orders(&action);
+        assert_eq!(preview.len(), 2);
+        assert_eq!(preview[0].order_type, "Limit Alo");
+        assert_eq!(preview[0].side, "buy");
+        assert_eq!(preview[1].order_type, "Stop Market @ 2900");
+        assert!(preview[1].reduce_only);
+
+        let cancel = Action::Cancel {
+            cancels: vec![CancelRequest { asset: 1, oid: 7 }],
+        };
+        assert!(dry_run_orders(&cancel).is_empty());
+    }
+
+    #[test]
+    fn notional_sizing_converts_to_coin_size() {
+        let args = SizingArgs::default();
+        assert_eq!(
+            OrderSizing::parse(Some("1.5"), &args).unwrap(),
+            OrderSizing::Coin(1.5)
+        );
+        assert!(OrderSizing::parse(None, &args).is_err());
+
+        let usd = SizingArgs {
+            usd: Some("2500".to_string()),
+            ..Default::default()
+        };
+        let sizing = OrderSizing::parse(None, &usd).unwrap();
+        assert_eq!(sizing.coin_size(2000.0, None, 0.0).unwrap(), 1.25);
+
+        let pct = SizingArgs {
+            equity_pct: Some("5".to_string()),
+            ..Default::default()
+        };
+        let sizing = OrderSizing::parse(None, &pct).unwrap();
+        assert_eq!(sizing.coin_size(2000.0, Some(10000.0), 0.0).unwrap(), 0.25);
+        assert!(sizing.coin_size(2000.0, Some(0.0), 0.0).is_err());
+    }
+
+    #[test]
+    fn risk_sizing_accounts_for_stop_distance_and_fees() {
+        let risk = SizingArgs {
+            risk: Some("1%".to_string()),
+            stop: Some("1900".to_string()),
+            ..Default::default()
+        };
+        let sizing = OrderSizing::parse(None, &risk).unwrap();
+        assert_eq!(
+            sizing,
+            OrderSizing::RiskPct {
+                pct: 1.0,
+                stop: 1900.0
+            }
+        );
+        // $100 of risk over a $100 stop distance, before fees
+        assert_eq!(sizing.coin_size(2000.0, Some(10000.0), 0.0).unwrap(), 1.0);
+        // fees on both fills: 0.001 * (2000 + 1900) = 3.9 per coin
+        let with_fees = sizing.coin_size(2000.0, Some(10000.0), 0.001).unwrap();
+        assert!((with_fees - 100.0 / 103.9).abs() < 1e-12);
+
+        let risk_usd = SizingArgs {
+            risk_usd: Some("200".to_string()),
+            stop: Some("2100".to_string()),
+            ..Default::default()
+        };
+        let sizing = OrderSizing::parse(None, &risk_usd).unwrap();
+        assert_eq!(sizing.coin_size(2000.0, None, 0.0).unwrap(), 2.0);
+
+        let no_risk = SizingArgs {
+            stop: Some("1900".to_string()),
+            ..Default::default()
+        };
+        assert!(OrderSizing::parse(None, &no_risk).is_err());
+    }
+
+    #[test]
+    fn margin_change_moves_liquidation_price() {
+        // 10 ETH long, 50x max leverage: maintenance is 1%
+        let after = estimate_liquidation_px(2500.0, 10.0, 990.0, 50);
+        assert!((after - 2400.0).abs() < 1e-9);
+        // removing margin from a short pulls liquidation down towards the price
+        let after = estimate_liquidation_px(3500.0, -10.0, -1010.0, 50);
+        assert!((after - 3400.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn close_order_takes_opposite_side() {
+        let position = |szi: &str| hl_api::Position {
+            coin: "ETH".to_string(),
+            szi: szi.to_string(),
+            entry_px: None,
+            position_value: "0".to_string(),
+            unrealized_pnl: "0".to_string(),
+            leverage: hl_api::Leverage {
+                leverage_type: "cross".to_string(),
+                value: 10,
+            },
+            liquidation_px: None,
+        };
+
+        let long = build_close_order(&position("1.5"), 1, 50.0, 3000.0, 0.01, &ETH).unwrap();
+        assert!(!long.is_buy);
+        assert!(long.reduce_only);
+        assert_eq!(long.sz, "0.75");
+        assert_eq!(long.limit_px, "2970");
+
+        let short = build_close_order(&position("-2"), 1, 100.0, 3000.0, 0.01, &ETH).unwrap();
+        assert!(short.is_buy);
+        assert_eq!(short.sz, "2");
+        assert_eq!(short.limit_px, "3030");
+
+        assert!(build_close_order(&position("0"), 1, 100.0, 3000.0, 0.01, &ETH).is_err());
+    }
+
+    #[test]
+    fn scale_ladder_spreads_size_and_price() {
+        let ladder = build_scale_ladder(1.0, 3000.0, 2900.0, 5, 1.0, 2).unwrap();
+        let prices: Vec<f64> = ladder.iter().map(|(px, _)| *px).collect();
+        assert_eq!(prices, vec![3000.0, 2975.0, 2950.0, 2925.0, 2900.0]);
+        assert!(ladder.iter().all(|(_, sz)| *sz == 0.2));
+
+        let skewed = build_scale_ladder(10.0, 100.0, 90.0, 4, 3.0, 1).unwrap();
+        let total: f64 = skewed.iter().map(|(_, sz)| sz).sum();
+        assert!((total - 10.0).abs() < 1e-9);
+        assert!(skewed.windows(2).all(|w| w[0].1 < w[1].1));
+        for (_, sz) in &skewed {
+            assert!(((sz * 10.0).round() - sz * 10.0).abs() < 1e-9);
+        }
+
+        assert!(build_scale_ladder(0.01, 100.0, 90.0, 5, 1.0, 2).is_err());
+    }
+
+    #[test]
+    fn scale_yes_keeps_the_risk_threshold() {
+        let cfg = config::LoadedConfig {
+            private_key: None,
+            wallet_address: None,
+            testnet: false,
+            account: None,
+            dry_run: true,
+            vault: None,
+        };
+        let limits = order_config::RiskLimits {
+            confirm_above_usd: Some(1_000.0),
+            ..Default::default()
+        };
+        let intent = |size| OrderIntent {
+            coin: "ETH",
+            is_buy: true,
+            size,
+            price: 3000.0,
+            reduce_only: false,
+            is_spot: false,
+        };
+
+        let (confirmed, warning) = confirm_scale(&cfg, &limits, intent(1.0), 5, true).unwrap();
+        assert!(confirmed);
+        assert!(warning.unwrap().contains("confirmation threshold"));
+
+        let (_, warning) = confirm_scale(&cfg, &limits, intent(0.1), 5, true).unwrap();
+        assert_eq!(warning, None);
+    }
+
+    #[test]
+    fn twap_progress_dedupes_and_filters_by_id() {
+        let data = serde_json::json!({
+            "twapSliceFills": [
+                { "twapId": 7, "fill": { "coin": "ETH", "px": "3000", "sz": "1", "tid": 1 } },
+                { "twapId": 7, "fill": { "coin": "ETH", "px": "3010", "sz": "1", "tid": 2 } },
+                { "twapId": 8, "fill": { "coin": "BTC", "px": "60000", "sz": "1", "tid": 3 } }
+            ]
+        });
+        let mut progress = TwapProgress::default();
+        assert!(progress.apply(&data, 7));
+        assert!(!progress.apply(&data, 7));
+        assert_eq!(progress.slices, 2);
+        assert_eq!(progress.filled_sz, 2.0);
+        assert_eq!(progress.avg_px(), Some(3005.0));
+        assert_eq!(progress.coin.as_deref(), Some("ETH"));
+    }
+
+    #[test]
+    fn chase_progress_tracks_fills_across_modifies() {
+        let update = |oid: u64, orig: &str, sz: &str, status: &str| {
+            serde_json::json!({
+                "order": { "coin": "ETH", "oid": oid, "origSz": orig, "sz": sz },
+                "status": status
+            })
+        };
+        let mut progress = ChaseProgress {
+            oid: Some(1),
+            ..Default::default()
+        };
+        let data =
+            serde_json::json!([update(1, "2", "1.5", "open"), update(9, "5", "0", "filled")]);
+        assert_eq!(progress.apply_updates(&data), None);
+        assert_eq!(progress.filled(), 0.5);
+
+        // The replacement rests for the remaining size; a late update for the
+        // old order still counts.
+        progress.oid = Some(2);
+        progress.fills.insert(2, 0.0);
+        let data = serde_json::json!([
+            update(2, "1.5", "1", "open"),
+            update(1, "2", "1.5", "canceled")
+        ]);
+        assert_eq!(progress.apply_updates(&data), None);
+        assert_eq!(progress.filled(), 1.0);
+
+        let data = serde_json::json!([update(2, "1.5", "1", "marginCanceled")]);
+        assert_eq!(
+            progress.apply_updates(&data).as_deref(),
+            Some("marginCanceled")
+        );
+    }
+
+    #[test]
+    fn fill_rows_show_fee_and_liquidity() {
+        let fill: Fill = serde_json::from_value(serde_json::json!({
+            "coin": "ETH", "px": "3000.5", "sz": "0.2", "side": "A", "time": 1714608000000i64,
+            "startPosition": "0.2", "dir": "Close Long", "closedPnl": "0", "hash": "0x00",
+            "oid": 1, "crossed": false, "fee": "0.12", "tid": 5, "feeToken": "USDC"
+        }))
+        .unwrap();
+        let row = format_fill_row(&fill);
+        assert_eq!(row[1..6], ["ETH", "Sell", "3000.5", "0.2", "0.12 USDC"]);
+        assert_eq!(row[6], "-");
+        assert_eq!(row[7], "Maker");
+
+        let liquidated = Fill {
+            crossed: true,
+            liquidation: Some(serde_json::json!({ "method": "market" })),
+            ..fill
+        };
+        assert_eq!(format_fill_row(&liquidated)[7], "Taker (liquidation)");
+    }
+
+    #[test]
+    fn order_status_names_the_reason() {
+        assert_eq!(format_order_status("filled"), "Filled");
+        assert_eq!(format_order_status("canceled"), "Cancelled");
+        assert_eq!(
+            format_order_status("marginCanceled"),
+            "Cancelled (insufficient margin)"
+        );
+        assert_eq!(
+            format_order_status("scheduledCancel"),
+            "Cancelled (dead man's switch)"
+        );
+        assert_eq!(
+            format_order_status("badAloPxRejected"),
+            "Rejected (post-only would have crossed)"
+        );
+        assert_eq!(
+            format_order_status("someNewReasonRejected"),
+            "Rejected (some new reason)"
+        );
+        assert_eq!(order_status_kind("scheduledCancel"), "canceled");
+        assert_eq!(order_status_kind("tickRejected"), "rejected");
+        assert_eq!(order_status_kind("triggered"), "triggered");
+    }
+
+    #[test]
+    fn status_filter_selects_kinds() {
+        assert_eq!(parse_status_filter("rejected").unwrap(), "rejected");
+        assert_eq!(order_status_kind("rejected"), "rejected");
+        assert_eq!(parse_status_filter("Cancelled").unwrap(), "canceled");
+        assert_eq!(parse_status_filter("open").unwrap(), "open");
+        assert!(parse_status_filter("pending").is_err());
+        assert_eq!(
+            order_status_kind("minTradeNtlRejected"),
+            parse_status_filter("rejected").unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn time_pages_resume_at_last_timestamp() {
+        // Entries (time, id) served 3 per page from the requested start.
+        let all = [(1, 'a'), (2, 'b'), (3, 'c'), (3, 'd'), (4, 'e')];
+        let fetched = fetch_time_pages(
+            1,
+            3,
+            |e: &(i64, char)| e.0,
+            |start| async move {
+                Ok(all
+                    .iter()
+                    .copied()
+                    .filter(|e| e.0 >= start)
+                    .take(3)
+                    .collect())
+            },
+        )
+        .await
+        .unwrap();
+        let ids: String = fetched.iter().map(|e| e.1).collect();
+        assert_eq!(ids, "abcde");
+    }
+
+    #[test]
+    fn funding_totals_net_per_coin() {
+        let payment = |coin: &str, usdc: &str| UserFunding {
+            time: 0,
+            hash: String::new(),
+            delta: hl_api::FundingDelta {
+                coin: coin.to_string(),
+                usdc: usdc.to_string(),
+                szi: "1".to_string(),
+                funding_rate: "0.0000125".to_string(),
+            },
+        };
+        let totals = funding_totals(&[
+            payment("ETH", "1.5"),
+            payment("BTC", "-2"),
+            payment("ETH", "-0.5"),
+        ]);
+        assert_eq!(totals.len(), 2);
+        assert_eq!((totals[0].coin.as_str(), totals[0].usdc), ("BTC", -2.0));
+        assert_eq!(
+            (totals[1].coin.as_str(), totals[1].payments, totals[1].usdc),
+            ("ETH", 2, 1.0)
+        );
+    }
+
+    #[test]
+    fn parses_modify_specs() {
+        let (oid, a) = parse_modify_spec("123:3000").unwrap();
+        assert_eq!(oid, 123);
+        assert_eq!(a.price, Some(3000.0));
+        assert_eq!(a.size, None);
+
+        let (_, a) = parse_modify_spec("123:3000:0.5").unwrap();
+        assert_eq!(a.size, Some(0.5));
+
+        assert!(parse_modify_spec("123").is_err());
+        assert!(parse_modify_spec("abc:3000").is_err());
+        assert!(parse_modify_spec("1:2:3:4").is_err());
+    }
+}
+
+#[cfg(test)]
+mod server_format_tests {
+    use super::*;
+
+    #[test]
+    fn format_uptime_matches_js_helpers() {
+        assert_eq!(format_uptime(30_000), "30s");
+        assert_eq!(format_uptime(90_000), "1m 30s");
+        assert_eq!(format_uptime(3_660_000), "1h 1m");
+        assert_eq!(format_uptime(90_000_000), "1d 1h");
+        assert_eq!(format_uptime(0), "0s");
+        assert_eq!(format_uptime(60_000), "1m 0s");
+        assert_eq!(format_uptime(3_600_000), "1h 0m");
+        assert_eq!(format_uptime(86_400_000), "1d 0h");
+        assert_eq!(format_uptime(172_800_000 + 3_600_000 * 5), "2d 5h");
+    }
+
+    #[test]
+    fn format_age_matches_js_helpers() {
+        assert_eq!(format_age(None), "unknown");
+        assert_eq!(format_age(Some(500)), "500ms");
+        assert_eq!(format_age(Some(30_000)), "30s");
+        assert_eq!(format_age(Some(90_000)), "1m 30s");
+        assert_eq!(format_age(Some(1_000)), "1s");
+        assert_eq!(format_age(Some(60_000)), "1m 0s");
+        assert_eq!(format_age(Some(0)), "0ms");
+        assert_eq!(format_age(Some(999)), "999ms");
+    }
+}
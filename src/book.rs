@@ -0,0 +1,182 @@
+use anyhow::{Result, anyhow};
+use serde::Serialize;
+
+use crate::hl_api::L2Book;
+
+/// Expected execution of a market order against the current book.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FillEstimate {
+    pub avg_px: f64,
+    /// Price of the deepest level the order reaches.
+    pub worst_px: f64,
+    pub levels: usize,
+}
+
+impl FillEstimate {
+    /// Distance of the average fill from `reference_px`, as a fraction.
+    pub fn slippage_vs(&self, reference_px: f64) -> f64 {
+        (self.avg_px - reference_px).abs() / reference_px
+    }
+
+    /// IOC limit price for the order: the worst level reached, moved by
+    /// `slippage` (a fraction) so book changes before the order lands still
+    /// fill.
+    pub fn limit_px(&self, is_buy: bool, slippage: f64) -> f64 {
+        if is_buy {
+            self.worst_px * (1.0 + slippage)
+        } else {
+            self.worst_px * (1.0 - slippage)
+        }
+    }
+}
+
+/// Walk the side of `book` a market order of `size` would take: asks for a
+/// buy, bids for a sell.
+pub fn estimate_fill(book: &L2Book, is_buy: bool, size: f64) -> Result<FillEstimate> {
+    let side = book.levels.get(if is_buy { 1 } else { 0 }).ok_or_else(|| {
+        anyhow!(
+            "Order book for {} has no {} side",
+            book.coin,
+            side_name(is_buy)
+        )
+    })?;
+
+    let mut remaining = size;
+    let mut notional = 0.0;
+    for (i, level) in side.iter().enumerate() {
+        let px: f64 = level
+            .px
+            .parse()
+            .map_err(|_| anyhow!("Invalid book price: {}", level.px))?;
+        let sz: f64 = level
+            .sz
+            .parse()
+            .map_err(|_| anyhow!("Invalid book size: {}", level.sz))?;
+        let take = remaining.min(sz);
+        notional += take * px;
+        remaining -= take;
+        if remaining <= size * 1e-12 {
+            return Ok(FillEstimate {
+                avg_px: notional / size,
+                worst_px: px,
+                levels: i + 1,
+            });
+        }
+    }
+
+    Err(anyhow!(
+        "Not enough {} liquidity in the visible {} book: {} of {size} available",
+        side_name(is_buy),
+        book.coin,
+        size - remaining
+    ))
+}
+
+/// Where a chasing maker order should rest.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChaseTarget {
+    /// Join the best bid (buys) or best ask (sells) at this price.
+    Price(f64),
+    /// The touch has moved past the price cap.
+    CapReached(f64),
+}
+
+/// Price for a post-only order that follows the top of `book` on its own
+/// side, as long as it stays within `cap` (a ceiling for buys, a floor for
+/// sells).
+pub fn chase_target(book: &L2Book, is_buy: bool, cap: Option<f64>) -> Result<ChaseTarget> {
+    let level = book
+        .levels
+        .get(if is_buy { 0 } else { 1 })
+        .and_then(|side| side.first())
+        .ok_or_else(|| {
+            anyhow!(
+                "Order book for {} has no {} side",
+                book.coin,
+                side_name(!is_buy)
+            )
+        })?;
+    let px: f64 = level
+        .px
+        .parse()
+        .map_err(|_| anyhow!("Invalid book price: {}", level.px))?;
+    Ok(match cap {
+        Some(cap) if (is_buy && px > cap) || (!is_buy && px < cap) => ChaseTarget::CapReached(px),
+        _ => ChaseTarget::Price(px),
+    })
+}
+
+fn side_name(is_buy: bool) -> &'static str {
+    if is_buy { "ask" } else { "bid" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hl_api::BookLevel;
+
+    fn book() -> L2Book {
+        let level = |px: &str, sz: &str| BookLevel {
+            px: px.to_string(),
+            sz: sz.to_string(),
+            n: 1,
+        };
+        L2Book {
+            coin: "ETH".to_string(),
+            levels: vec![
+                vec![level("99", "1"), level("98", "2")],
+                vec![level("101", "1"), level("102", "1"), level("104", "2")],
+            ],
+            time: 0,
+        }
+    }
+
+    #[test]
+    fn walks_asks_for_buys() {
+        let est = estimate_fill(&book(), true, 3.0).unwrap();
+        assert_eq!(est.levels, 3);
+        assert_eq!(est.worst_px, 104.0);
+        assert!((est.avg_px - 307.0 / 3.0).abs() < 1e-9);
+        assert!((est.slippage_vs(100.0) - 7.0 / 300.0).abs() < 1e-9);
+        assert!((est.limit_px(true, 0.01) - 105.04).abs() < 1e-9);
+    }
+
+    #[test]
+    fn walks_bids_for_sells() {
+        let est = estimate_fill(&book(), false, 0.5).unwrap();
+        assert_eq!(est.levels, 1);
+        assert_eq!(est.avg_px, 99.0);
+        assert!((est.limit_px(false, 0.01) - 98.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn chase_joins_own_side_within_cap() {
+        assert_eq!(
+            chase_target(&book(), true, None).unwrap(),
+            ChaseTarget::Price(99.0)
+        );
+        assert_eq!(
+            chase_target(&book(), false, Some(100.0)).unwrap(),
+            ChaseTarget::Price(101.0)
+        );
+        assert_eq!(
+            chase_target(&book(), true, Some(99.0)).unwrap(),
+            ChaseTarget::Price(99.0)
+        );
+        assert_eq!(
+            chase_target(&book(), true, Some(98.5)).unwrap(),
+            ChaseTarget::CapReached(99.0)
+        );
+        assert_eq!(
+            chase_target(&book(), false, Some(102.0)).unwrap(),
+            ChaseTarget::CapReached(101.0)
+        );
+    }
+
+    #[test]
+    fn errors_when_book_too_thin() {
+        let err = estimate_fill(&book(), false, 5.0).unwrap_err();
+        assert!(err.to_string().contains("3 of 5 available"));
+    }
+}
@@ -1,4 +1,4 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 
 use anyhow::{Result, anyhow};
 use ethers::{
@@ -20,12 +20,64 @@ pub enum AccountType {
     ApiWallet,
 }
 
+/// Vault or sub-account traded on behalf of with `--vault`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Vault {
+    pub address: Address,
+    /// Local account alias it was given by, if any
+    pub alias: Option<String>,
+}
+
+impl Vault {
+    /// Resolve an address, or the alias of a stored account (e.g. a read-only
+    /// account added for the sub-account).
+    pub fn resolve(value: &str) -> Result<Self> {
+        if value.starts_with("0x") {
+            return Ok(Self {
+                address: validation::validate_address(value)?,
+                alias: None,
+            });
+        }
+        let account = db::get_account_by_alias(value)?.ok_or_else(|| {
+            anyhow!("Unknown vault: {value}. Pass an address or the alias of a stored account")
+        })?;
+        let address = account
+            .user_address
+            .parse::<Address>()
+            .map_err(|_| anyhow!("Invalid address in db for account \"{}\"", account.alias))?;
+        Ok(Self {
+            address,
+            alias: Some(account.alias),
+        })
+    }
+}
+
+impl fmt::Display for Vault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.alias {
+            Some(alias) => write!(f, "{alias} ({:#x})", self.address),
+            None => write!(f, "{:#x}", self.address),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct LoadedConfig {
     pub private_key: Option<String>,
     pub wallet_address: Option<Address>,
     pub testnet: bool,
     pub account: Option<AccountSummary>,
+    /// Stop trading commands before signing and print the action instead.
+    pub dry_run: bool,
+    /// Account traded and queried instead of the wallet's own.
+    pub vault: Option<Vault>,
+}
+
+impl LoadedConfig {
+    /// Address passed with signed actions: the vault, if trading one.
+    pub fn vault_address(&self) -> Option<Address> {
+        self.vault.as_ref().map(|v| v.address)
+    }
 }
 
 pub fn load_config(testnet: bool) -> Result<LoadedConfig> {
@@ -58,6 +110,8 @@ pub fn load_config(testnet: bool) -> Result<LoadedConfig> {
                 alias: acc.alias,
                 account_type,
             }),
+            dry_run: false,
+            vault: None,
         });
     }
 
@@ -85,6 +139,8 @@ pub fn load_config(testnet: bool) -> Result<LoadedConfig> {
         wallet_address,
         testnet,
         account: None,
+        dry_run: false,
+        vault: None,
     })
 }
 
@@ -110,4 +166,35 @@ mod tests {
             },
         );
     }
+
+    #[test]
+    fn resolves_vault_by_address_or_alias() {
+        let tmp = tempfile::tempdir().unwrap();
+        crate::test_support::with_env_vars(
+            &[("HYPERLIQUID_CLI_DIR", Some(tmp.path().as_os_str()))],
+            || {
+                let address = "0x1234567890123456789012345678901234567890";
+                let vault = Vault::resolve(address).unwrap();
+                assert_eq!(vault.alias, None);
+                assert_eq!(vault.to_string(), address);
+
+                db::create_account(db::CreateAccountInput {
+                    alias: "fund-a".to_string(),
+                    user_address: address.to_string(),
+                    account_type: "readonly".to_string(),
+                    source: None,
+                    api_wallet_private_key: None,
+                    api_wallet_public_key: None,
+                    set_as_default: false,
+                })
+                .unwrap();
+                let vault = Vault::resolve("fund-a").unwrap();
+                assert_eq!(vault.address, address.parse::<Address>().unwrap());
+                assert_eq!(vault.to_string(), format!("fund-a ({address})"));
+
+                assert!(Vault::resolve("nope").is_err());
+                assert!(Vault::resolve("0x1234").is_err());
+            },
+        );
+    }
 }
@@ -26,6 +26,22 @@ pub struct Account {
     pub updated_at: i64,
 }
 
+/// Client order ID (cloid) submitted from this machine and the exchange order
+/// ID it resolved to, if any.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ClientOrder {
+    pub cloid: String,
+    #[serde(rename = "userAddress")]
+    pub user_address: String,
+    pub coin: String,
+    pub oid: Option<u64>,
+    pub status: String, // "pending" | "resting" | "filled" | "rejected" | "cancelled"
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+}
+
 #[derive(Clone, Debug)]
 pub struct CreateAccountInput {
     pub alias: String,
@@ -96,6 +112,23 @@ fn run_migrations(conn: &Connection) -> Result<()> {
       "#,
     )?;
 
+    apply_migration(
+        "002_create_client_orders",
+        r#"
+        CREATE TABLE IF NOT EXISTS client_orders (
+          cloid TEXT PRIMARY KEY,
+          user_address TEXT NOT NULL,
+          coin TEXT NOT NULL,
+          oid INTEGER,
+          status TEXT NOT NULL,
+          created_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now')),
+          updated_at INTEGER NOT NULL DEFAULT (strftime('%s', 'now'))
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_client_orders_oid ON client_orders(oid);
+      "#,
+    )?;
+
     Ok(())
 }
 
@@ -305,3 +338,88 @@ pub fn get_account_count() -> Result<i64> {
     let count: i64 = conn.query_row("SELECT COUNT(*) FROM accounts", [], |r| r.get(0))?;
     Ok(count)
 }
+
+fn row_to_client_order(row: &Row<'_>) -> rusqlite::Result<ClientOrder> {
+    Ok(ClientOrder {
+        cloid: row.get("cloid")?,
+        user_address: row.get("user_address")?,
+        coin: row.get("coin")?,
+        oid: row.get::<_, Option<i64>>("oid")?.map(|oid| oid as u64),
+        status: row.get("status")?,
+        created_at: row.get("created_at")?,
+        updated_at: row.get("updated_at")?,
+    })
+}
+
+/// Record a cloid as pending before the order is submitted, so a timed-out
+/// request can still be looked up afterwards.
+pub fn record_client_order(cloid: &str, user_address: &str, coin: &str) -> Result<()> {
+    let conn = connect()?;
+    conn.execute(
+        r#"
+        INSERT INTO client_orders (cloid, user_address, coin, status)
+        VALUES (?, ?, ?, 'pending')
+        ON CONFLICT(cloid) DO UPDATE SET updated_at = strftime('%s', 'now')
+      "#,
+        params![cloid, user_address, coin],
+    )?;
+    Ok(())
+}
+
+/// Update a client order's status. A rejection never replaces the status of
+/// an order that already reached the exchange: resending its cloid is
+/// rejected as a duplicate.
+pub fn update_client_order(cloid: &str, oid: Option<u64>, status: &str) -> Result<()> {
+    let conn = connect()?;
+    conn.execute(
+        r#"
+        UPDATE client_orders
+        SET oid = COALESCE(?1, oid), status = ?2, updated_at = strftime('%s', 'now')
+        WHERE cloid = ?3
+          AND NOT (?2 = 'rejected' AND status IN ('resting', 'filled', 'cancelled'))
+      "#,
+        params![oid.map(|oid| oid as i64), status, cloid],
+    )?;
+    Ok(())
+}
+
+pub fn get_client_order(cloid: &str) -> Result<Option<ClientOrder>> {
+    let conn = connect()?;
+    let order = conn
+        .query_row(
+            "SELECT * FROM client_orders WHERE cloid = ?",
+            params![cloid],
+            row_to_client_order,
+        )
+        .optional()?;
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_cloid_to_oid_mapping() {
+        crate::test_support::with_temp_hl_dir(|_| {
+            let cloid = "0x1234567890abcdef1234567890abcdef";
+            assert!(get_client_order(cloid).unwrap().is_none());
+
+            record_client_order(cloid, "0xabc", "ETH").unwrap();
+            let pending = get_client_order(cloid).unwrap().unwrap();
+            assert_eq!(pending.status, "pending");
+            assert_eq!(pending.oid, None);
+
+            update_client_order(cloid, Some(42), "resting").unwrap();
+            update_client_order(cloid, None, "filled").unwrap();
+            let filled = get_client_order(cloid).unwrap().unwrap();
+            assert_eq!(filled.oid, Some(42));
+            assert_eq!(filled.status, "filled");
+            assert_eq!(filled.coin, "ETH");
+
+            // A rejected resend of the same cloid keeps the landed order
+            update_client_order(cloid, None, "rejected").unwrap();
+            assert_eq!(get_client_order(cloid).unwrap().unwrap().status, "filled");
+        });
+    }
+}
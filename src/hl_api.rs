@@ -60,8 +60,17 @@ impl HlApi {
     }
 
     pub async fn all_mids(&self) -> Result<HashMap<String, String>> {
-        self.post_info(serde_json::json!({ "type": "allMids" }))
-            .await
+        self.all_mids_for_dex(None).await
+    }
+
+    /// Mid prices for one perp dex. Spot mids are only included for the main
+    /// dex.
+    pub async fn all_mids_for_dex(&self, dex: Option<&str>) -> Result<HashMap<String, String>> {
+        let mut body = serde_json::json!({ "type": "allMids" });
+        if let Some(dex) = dex {
+            body["dex"] = serde_json::Value::String(dex.to_string());
+        }
+        self.post_info(body).await
     }
 
     pub async fn meta(&self) -> Result<PerpMeta> {
@@ -135,14 +144,44 @@ impl HlApi {
         .await
     }
 
-    pub async fn clearinghouse_state(&self, user: Address) -> Result<ClearinghouseState> {
+    /// Open orders on one builder-deployed perp dex.
+    pub async fn open_orders_for_dex(&self, user: Address, dex: &str) -> Result<Vec<OpenOrder>> {
         self.post_info(serde_json::json!({
-            "type": "clearinghouseState",
-            "user": addr(user)
+            "type": "openOrders",
+            "user": addr(user),
+            "dex": dex
+        }))
+        .await
+    }
+
+    pub async fn frontend_open_orders(&self, user: Address) -> Result<Vec<FrontendOpenOrder>> {
+        self.post_info(serde_json::json!({
+            "type": "frontendOpenOrders",
+            "user": addr(user),
+            "dex": "ALL_DEXS"
         }))
         .await
     }
 
+    pub async fn clearinghouse_state(&self, user: Address) -> Result<ClearinghouseState> {
+        self.clearinghouse_state_for_dex(user, None).await
+    }
+
+    pub async fn clearinghouse_state_for_dex(
+        &self,
+        user: Address,
+        dex: Option<&str>,
+    ) -> Result<ClearinghouseState> {
+        let mut body = serde_json::json!({
+            "type": "clearinghouseState",
+            "user": addr(user)
+        });
+        if let Some(dex) = dex {
+            body["dex"] = serde_json::Value::String(dex.to_string());
+        }
+        self.post_info(body).await
+    }
+
     pub async fn spot_clearinghouse_state(&self, user: Address) -> Result<SpotClearinghouseState> {
         self.post_info(serde_json::json!({
             "type": "spotClearinghouseState",
@@ -168,6 +207,103 @@ impl HlApi {
         .await
     }
 
+    /// The most recent orders (up to 2000) in any state, with their outcome.
+    pub async fn historical_orders(&self, user: Address) -> Result<Vec<HistoricalOrder>> {
+        self.post_info(serde_json::json!({
+            "type": "historicalOrders",
+            "user": addr(user)
+        }))
+        .await
+    }
+
+    pub async fn order_status(
+        &self,
+        user: Address,
+        order: &OrderRef,
+    ) -> Result<OrderStatusResponse> {
+        let oid = match order {
+            OrderRef::Oid(oid) => serde_json::Value::from(*oid),
+            OrderRef::Cloid(cloid) => serde_json::Value::from(cloid.as_str()),
+        };
+        self.post_info(serde_json::json!({
+            "type": "orderStatus",
+            "user": addr(user),
+            "oid": oid
+        }))
+        .await
+    }
+
+    /// The most recent fills (up to 2000).
+    pub async fn user_fills(&self, user: Address) -> Result<Vec<Fill>> {
+        self.post_info(serde_json::json!({
+            "type": "userFills",
+            "user": addr(user)
+        }))
+        .await
+    }
+
+    /// Fills from `start_time` (ms), up to 2000 per call.
+    pub async fn user_fills_by_time(
+        &self,
+        user: Address,
+        start_time: i64,
+        end_time: Option<i64>,
+    ) -> Result<Vec<Fill>> {
+        let mut body = serde_json::json!({
+            "type": "userFillsByTime",
+            "user": addr(user),
+            "startTime": start_time
+        });
+        if let Some(end_time) = end_time {
+            body["endTime"] = end_time.into();
+        }
+        self.post_info(body).await
+    }
+
+    /// Funding payments from `start_time` (ms), up to 500 per call.
+    pub async fn user_funding(
+        &self,
+        user: Address,
+        start_time: i64,
+        end_time: Option<i64>,
+    ) -> Result<Vec<UserFunding>> {
+        let mut body = serde_json::json!({
+            "type": "userFunding",
+            "user": addr(user),
+            "startTime": start_time
+        });
+        if let Some(end_time) = end_time {
+            body["endTime"] = end_time.into();
+        }
+        self.post_info(body).await
+    }
+
+    /// Funding rates for a perp from `start_time` (ms), up to 500 per call.
+    pub async fn funding_history(
+        &self,
+        coin: &str,
+        start_time: i64,
+        end_time: Option<i64>,
+    ) -> Result<Vec<FundingRate>> {
+        let mut body = serde_json::json!({
+            "type": "fundingHistory",
+            "coin": coin,
+            "startTime": start_time
+        });
+        if let Some(end_time) = end_time {
+            body["endTime"] = end_time.into();
+        }
+        self.post_info(body).await
+    }
+
+    pub async fn user_fees(&self, user: Address) -> Result<UserFees> {
+        self.post_info(serde_json::json!({
+            "type": "userFees",
+            "user": addr(user)
+        }))
+        .await
+    }
+
     pub async fn user_role(&self, user: Address) -> Result<UserRoleResponse> {
         self.post_info(serde_json::json!({
             "type": "userRole",
@@ -324,6 +460,122 @@ pub struct OpenOrder {
     pub timestamp: u64,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FrontendOpenOrder {
+    pub coin: String,
+    pub limit_px: String,
+    pub oid: u64,
+    pub side: String,
+    pub sz: String,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub orig_sz: Option<String>,
+    #[serde(default)]
+    pub order_type: String,
+    #[serde(default)]
+    pub is_trigger: bool,
+    #[serde(default)]
+    pub trigger_condition: String,
+    #[serde(default)]
+    pub trigger_px: String,
+    #[serde(default)]
+    pub reduce_only: bool,
+    #[serde(default)]
+    pub is_position_tpsl: bool,
+    #[serde(default)]
+    pub tif: Option<String>,
+    #[serde(default)]
+    pub cloid: Option<String>,
+}
+
+/// An order by exchange order ID or client order ID (`0x` + 32 hex).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderRef {
+    Oid(u64),
+    Cloid(String),
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalOrder {
+    pub order: FrontendOpenOrder,
+    /// e.g. "open", "filled", "canceled", "triggered", "marginCanceled",
+    /// "badAloPxRejected"
+    pub status: String,
+    pub status_timestamp: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OrderStatusResponse {
+    /// "order", or "unknownOid" when the exchange has no such order
+    pub status: String,
+    #[serde(default)]
+    pub order: Option<HistoricalOrder>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fill {
+    pub coin: String,
+    pub px: String,
+    pub sz: String,
+    /// `B` (buy) or `A` (sell)
+    pub side: String,
+    pub time: i64,
+    #[serde(default)]
+    pub start_position: String,
+    /// e.g. "Open Long", "Close Short"
+    #[serde(default)]
+    pub dir: String,
+    #[serde(default)]
+    pub closed_pnl: String,
+    #[serde(default)]
+    pub hash: String,
+    pub oid: u64,
+    /// True if the fill took liquidity
+    #[serde(default)]
+    pub crossed: bool,
+    #[serde(default)]
+    pub fee: String,
+    pub tid: u64,
+    #[serde(default)]
+    pub fee_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub liquidation: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFunding {
+    pub time: i64,
+    #[serde(default)]
+    pub hash: String,
+    pub delta: FundingDelta,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingDelta {
+    pub coin: String,
+    /// Payment in USDC: positive when received, negative when paid
+    pub usdc: String,
+    /// Position size the payment was for
+    pub szi: String,
+    pub funding_rate: String,
+}
+
+/// One hourly funding rate of a perp.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundingRate {
+    pub coin: String,
+    pub funding_rate: String,
+    #[serde(default)]
+    pub premium: String,
+    pub time: i64,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ClearinghouseState {
@@ -411,6 +663,18 @@ pub struct ActiveAssetData {
     pub mark_px: String,
 }
 
+/// Effective fee rates for a user, as decimal fractions (e.g. "0.00045").
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserFees {
+    /// Taker rate
+    pub user_cross_rate: String,
+    /// Maker rate
+    pub user_add_rate: String,
+    /// Spot taker rate
+    pub user_spot_cross_rate: String,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(tag = "role", rename_all = "camelCase")]
 pub enum UserRoleResponse {
@@ -0,0 +1,172 @@
+use std::{sync::Arc, time::SystemTime};
+
+use anyhow::{Context, Result, anyhow};
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::{Address, H256, Signature},
+    utils::keccak256,
+};
+use hyperliquid::{
+    Exchange,
+    types::{API, Chain, agent::l1, exchange::response::Response},
+};
+use serde::Serialize;
+
+/// L1 exchange actions that the `hyperliquid` crate has no signing method for,
+/// or only signs without a vault address.
+///
+/// Field names and order must match the exchange's msgpack encoding, since the
+/// signature covers the serialized action.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum L1Action {
+    /// Cancel a running TWAP order (`a` = asset index, `t` = TWAP id).
+    TwapCancel { a: u32, t: u64 },
+    /// Cancel all open orders at `time` (ms); without a time, clears the
+    /// scheduled cancel.
+    ScheduleCancel {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        time: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    UpdateLeverage {
+        asset: u32,
+        is_cross: bool,
+        leverage: u32,
+    },
+    /// Add (`is_buy`) or remove isolated margin; `ntli` is in micro-USDC.
+    #[serde(rename_all = "camelCase")]
+    UpdateIsolatedMargin { asset: u32, is_buy: bool, ntli: i64 },
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedRequest<'a> {
+    action: &'a L1Action,
+    nonce: u64,
+    signature: Signature,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vault_address: Option<Address>,
+}
+
+impl L1Action {
+    /// Hash signed by the agent payload: msgpack(action) ++ nonce ++ vault flag/address.
+    pub fn connection_id(&self, vault_address: Option<Address>, nonce: u64) -> Result<H256> {
+        let mut encoded = rmp_serde::to_vec_named(self).context("Failed to encode action")?;
+        encoded.extend(nonce.to_be_bytes());
+        match vault_address {
+            Some(address) => {
+                encoded.push(1);
+                encoded.extend(address.to_fixed_bytes());
+            }
+            None => encoded.push(0),
+        }
+        Ok(keccak256(encoded).into())
+    }
+
+    /// Sign the action with `wallet` and post it to the exchange endpoint.
+    pub async fn send(
+        &self,
+        exchange: &Exchange,
+        wallet: Arc<LocalWallet>,
+        vault_address: Option<Address>,
+    ) -> Result<Response> {
+        let source = match exchange.chain {
+            Chain::Arbitrum => "a",
+            Chain::ArbitrumTestnet => "b",
+            other => return Err(anyhow!("Unsupported chain: {other}")),
+        };
+        let nonce = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)?
+            .as_millis() as u64;
+        let connection_id = self.connection_id(vault_address, nonce)?;
+        let signature = wallet
+            .sign_typed_data(&l1::Agent {
+                source: source.to_string(),
+                connection_id,
+            })
+            .await
+            .context("Failed to sign action")?;
+
+        let request = SignedRequest {
+            action: self,
+            nonce,
+            signature,
+            vault_address,
+        };
+        Ok(exchange.client.post(&API::Exchange, &request).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn twap_cancel_serializes_with_short_keys() {
+        let action = L1Action::TwapCancel { a: 3, t: 42 };
+        assert_eq!(
+            serde_json::to_value(&action).unwrap(),
+            serde_json::json!({ "type": "twapCancel", "a": 3, "t": 42 })
+        );
+    }
+
+    #[test]
+    fn schedule_cancel_omits_time_when_clearing() {
+        assert_eq!(
+            serde_json::to_value(L1Action::ScheduleCancel { time: Some(5) }).unwrap(),
+            serde_json::json!({ "type": "scheduleCancel", "time": 5 })
+        );
+        assert_eq!(
+            serde_json::to_value(L1Action::ScheduleCancel { time: None }).unwrap(),
+            serde_json::json!({ "type": "scheduleCancel" })
+        );
+    }
+
+    #[test]
+    fn leverage_actions_match_crate_encoding() {
+        use hyperliquid::types::exchange::request::Action;
+
+        let ours = L1Action::UpdateLeverage {
+            asset: 1,
+            is_cross: false,
+            leverage: 5,
+        };
+        let theirs = Action::UpdateLeverage {
+            asset: 1,
+            is_cross: false,
+            leverage: 5,
+        };
+        assert_eq!(
+            rmp_serde::to_vec_named(&ours).unwrap(),
+            rmp_serde::to_vec_named(&theirs).unwrap()
+        );
+
+        let ours = L1Action::UpdateIsolatedMargin {
+            asset: 1,
+            is_buy: true,
+            ntli: 1_000_000,
+        };
+        let theirs = Action::UpdateIsolatedMargin {
+            asset: 1,
+            is_buy: true,
+            ntli: 1_000_000,
+        };
+        assert_eq!(
+            rmp_serde::to_vec_named(&ours).unwrap(),
+            rmp_serde::to_vec_named(&theirs).unwrap()
+        );
+    }
+
+    #[test]
+    fn connection_id_depends_on_nonce_and_vault() {
+        let action = L1Action::TwapCancel { a: 3, t: 42 };
+        let base = action.connection_id(None, 1).unwrap();
+        assert_eq!(base, action.connection_id(None, 1).unwrap());
+        assert_ne!(base, action.connection_id(None, 2).unwrap());
+        assert_ne!(
+            base,
+            action.connection_id(Some(Address::zero()), 1).unwrap()
+        );
+    }
+}
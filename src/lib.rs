@@ -1,12 +1,17 @@
 pub mod asset_index;
+pub mod book;
 pub mod config;
 pub mod db;
 pub mod hl_api;
+pub mod l1_action;
+pub mod order_batch;
 pub mod order_config;
 pub mod output;
 pub mod paths;
 pub mod prompt;
+pub mod rounding;
 pub mod server;
+pub mod trail;
 pub mod validation;
 pub mod watch;
 pub mod ws;
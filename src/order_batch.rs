@@ -0,0 +1,245 @@
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Deserializer};
+
+/// Input formats accepted by `order batch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchFormat {
+    /// A JSON array of entries.
+    Json,
+    /// One JSON entry per line.
+    Ndjson,
+    /// A header row followed by one entry per line.
+    Csv,
+}
+
+impl BatchFormat {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "ndjson" | "jsonl" => Ok(Self::Ndjson),
+            "csv" => Ok(Self::Csv),
+            _ => Err(anyhow!("Format must be one of: json, ndjson, csv")),
+        }
+    }
+
+    /// Guess the format from a file extension, falling back to the content:
+    /// `[` starts a JSON array, `{` an NDJSON stream, anything else is CSV.
+    pub fn detect(path: Option<&str>, input: &str) -> Self {
+        let ext = path
+            .and_then(|p| p.rsplit_once('.'))
+            .map(|(_, ext)| ext.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("json") => return Self::Json,
+            Some("ndjson") | Some("jsonl") => return Self::Ndjson,
+            Some("csv") => return Self::Csv,
+            _ => {}
+        }
+        match input.trim_start().chars().next() {
+            Some('[') => Self::Json,
+            Some('{') => Self::Ndjson,
+            _ => Self::Csv,
+        }
+    }
+}
+
+/// One order or cancel from a batch file.
+///
+/// Values are kept as strings so they go through the same validation as the
+/// equivalent command-line arguments; JSON numbers are accepted too.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct BatchEntry {
+    /// limit, market, stop, tp or cancel
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub coin: String,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub side: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub size: Option<String>,
+    /// Limit price (limit orders, or the execution price of a trigger)
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub price: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub trigger_price: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub tif: Option<String>,
+    #[serde(default)]
+    pub reduce_only: bool,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub slippage: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub oid: Option<String>,
+    #[serde(default, deserialize_with = "string_or_number")]
+    pub cloid: Option<String>,
+}
+
+/// A parsed entry with its line number (entry number for JSON arrays).
+#[derive(Debug)]
+pub struct BatchLine {
+    pub line: usize,
+    pub entry: Result<BatchEntry>,
+}
+
+fn string_or_number<'de, D: Deserializer<'de>>(de: D) -> Result<Option<String>, D::Error> {
+    Ok(match Option::<serde_json::Value>::deserialize(de)? {
+        None | Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::String(s)) => Some(s),
+        Some(other) => Some(other.to_string()),
+    })
+}
+
+/// Split batch input into entries. Blank lines and lines starting with `#`
+/// are skipped in NDJSON and CSV. A malformed entry is reported on its line
+/// rather than failing the whole batch; only a malformed JSON array or CSV
+/// header is fatal.
+pub fn parse_batch(input: &str, format: BatchFormat) -> Result<Vec<BatchLine>> {
+    match format {
+        BatchFormat::Json => {
+            let values: Vec<serde_json::Value> =
+                serde_json::from_str(input).map_err(|e| anyhow!("Invalid JSON batch: {e}"))?;
+            Ok(values
+                .into_iter()
+                .enumerate()
+                .map(|(i, v)| BatchLine {
+                    line: i + 1,
+                    entry: serde_json::from_value(v).map_err(|e| anyhow!("{e}")),
+                })
+                .collect())
+        }
+        BatchFormat::Ndjson => Ok(content_lines(input)
+            .map(|(line, text)| BatchLine {
+                line,
+                entry: serde_json::from_str(text).map_err(|e| anyhow!("{e}")),
+            })
+            .collect()),
+        BatchFormat::Csv => parse_csv(input),
+    }
+}
+
+fn content_lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l.trim()))
+        .filter(|(_, l)| !l.is_empty() && !l.starts_with('#'))
+}
+
+/// Minimal CSV: comma-separated, no quoting. Column names match the JSON keys
+/// in snake_case or camelCase (`trigger_price` or `triggerPrice`).
+fn parse_csv(input: &str) -> Result<Vec<BatchLine>> {
+    let mut lines = content_lines(input);
+    let (_, header) = lines.next().ok_or_else(|| anyhow!("CSV batch is empty"))?;
+    let columns: Vec<String> = header
+        .split(',')
+        .map(|c| snake_to_camel(c.trim()))
+        .collect();
+
+    Ok(lines
+        .map(|(line, text)| {
+            let cells: Vec<&str> = text.split(',').map(str::trim).collect();
+            let entry = if cells.len() != columns.len() {
+                Err(anyhow!(
+                    "Expected {} columns, found {}",
+                    columns.len(),
+                    cells.len()
+                ))
+            } else {
+                let object: serde_json::Map<String, serde_json::Value> = columns
+                    .iter()
+                    .zip(cells)
+                    .filter(|(_, cell)| !cell.is_empty())
+                    .map(|(col, cell)| {
+                        let value = if col == "reduceOnly" {
+                            serde_json::Value::Bool(matches!(
+                                cell.to_ascii_lowercase().as_str(),
+                                "true" | "yes" | "1"
+                            ))
+                        } else {
+                            serde_json::Value::String(cell.to_string())
+                        };
+                        (col.clone(), value)
+                    })
+                    .collect();
+                serde_json::from_value(serde_json::Value::Object(object))
+                    .map_err(|e| anyhow!("{e}"))
+            };
+            BatchLine { line, entry }
+        })
+        .collect())
+}
+
+fn snake_to_camel(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut upper = false;
+    for c in value.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            out.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit_entry() -> BatchEntry {
+        BatchEntry {
+            kind: "limit".to_string(),
+            coin: "ETH".to_string(),
+            side: Some("buy".to_string()),
+            size: Some("0.5".to_string()),
+            price: Some("3000".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn detects_format_from_extension_then_content() {
+        assert_eq!(BatchFormat::detect(Some("a.csv"), "[]"), BatchFormat::Csv);
+        assert_eq!(BatchFormat::detect(None, "  [{}]"), BatchFormat::Json);
+        assert_eq!(
+            BatchFormat::detect(Some("-"), "{}\n{}"),
+            BatchFormat::Ndjson
+        );
+        assert_eq!(BatchFormat::detect(None, "type,coin"), BatchFormat::Csv);
+    }
+
+    #[test]
+    fn parses_json_and_ndjson_with_numbers() {
+        let json = r#"[{"type":"limit","coin":"ETH","side":"buy","size":0.5,"price":3000}]"#;
+        let lines = parse_batch(json, BatchFormat::Json).unwrap();
+        assert_eq!(lines[0].entry.as_ref().unwrap(), &limit_entry());
+
+        let ndjson =
+            "# orders\n{\"type\":\"cancel\",\"coin\":\"ETH\",\"oid\":42}\n\n{\"type\":\"bogus\"}\n";
+        let lines = parse_batch(ndjson, BatchFormat::Ndjson).unwrap();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].line, 2);
+        assert_eq!(lines[0].entry.as_ref().unwrap().oid.as_deref(), Some("42"));
+        assert_eq!(lines[1].line, 4);
+        assert!(lines[1].entry.is_err());
+    }
+
+    #[test]
+    fn parses_csv_rows_by_header() {
+        let csv = "type,coin,side,size,price,trigger_price,reduce_only\n\
+                   limit,ETH,buy,0.5,3000,,\n\
+                   stop,ETH,sell,0.5,,2800,true\n\
+                   limit,ETH\n";
+        let lines = parse_batch(csv, BatchFormat::Csv).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].entry.as_ref().unwrap(), &limit_entry());
+        let stop = lines[1].entry.as_ref().unwrap();
+        assert_eq!(stop.trigger_price.as_deref(), Some("2800"));
+        assert!(stop.reduce_only);
+        assert_eq!(lines[2].line, 4);
+        assert!(lines[2].entry.is_err());
+    }
+}
@@ -1,18 +1,403 @@
-use std::{fs, path::PathBuf};
+use std::{collections::BTreeMap, collections::HashMap, fs, path::PathBuf};
 
-use anyhow::{Context, Result};
+use anyhow::{Context, Result, anyhow};
 use serde::{Deserialize, Serialize};
 
-use crate::paths;
+use crate::{hl_api::ClearinghouseState, paths};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Schema version written to `order-config.json`.
+///
+/// Version 1 files hold only a top-level `slippage`; they are migrated on load.
+pub const CONFIG_VERSION: u32 = 2;
+
+/// Market slippage percentage when none is configured.
+pub const DEFAULT_SLIPPAGE: f64 = 1.0;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct OrderConfig {
-    pub slippage: f64,
+    pub version: u32,
+    /// Order defaults for every coin
+    #[serde(default)]
+    pub defaults: OrderDefaults,
+    /// Per-coin defaults, overriding `defaults` field by field
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub coins: BTreeMap<String, OrderDefaults>,
+    /// Limits applied to every account
+    #[serde(default)]
+    pub risk: RiskLimits,
+    /// Per-account limits by alias, overriding `risk` field by field
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub accounts: BTreeMap<String, RiskLimits>,
 }
 
 impl Default for OrderConfig {
     fn default() -> Self {
-        Self { slippage: 1.0 }
+        Self {
+            version: CONFIG_VERSION,
+            defaults: OrderDefaults::default(),
+            coins: BTreeMap::new(),
+            risk: RiskLimits::default(),
+            accounts: BTreeMap::new(),
+        }
+    }
+}
+
+impl OrderConfig {
+    /// The limits in force for `account` (an alias).
+    pub fn risk_limits(&self, account: Option<&str>) -> RiskLimits {
+        match account.and_then(|alias| self.accounts.get(alias)) {
+            Some(overrides) => self.risk.overridden_by(overrides),
+            None => self.risk.clone(),
+        }
+    }
+
+    /// The defaults in force for `coin`; coin names match case-insensitively.
+    pub fn defaults_for(&self, coin: Option<&str>) -> OrderDefaults {
+        let overrides = coin.and_then(|coin| {
+            self.coins
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(coin))
+                .map(|(_, defaults)| defaults)
+        });
+        match overrides {
+            Some(overrides) => self.defaults.overridden_by(overrides),
+            None => self.defaults.clone(),
+        }
+    }
+
+    /// Default market slippage percentage for `coin`.
+    pub fn slippage(&self, coin: Option<&str>) -> f64 {
+        self.defaults_for(coin).slippage.unwrap_or(DEFAULT_SLIPPAGE)
+    }
+
+    /// The per-coin entry for `coin`, reusing an existing key that differs
+    /// only in case.
+    pub fn coin_defaults_mut(&mut self, coin: &str) -> &mut OrderDefaults {
+        let key = self
+            .coins
+            .keys()
+            .find(|name| name.eq_ignore_ascii_case(coin))
+            .cloned()
+            .unwrap_or_else(|| coin.to_string());
+        self.coins.entry(key).or_default()
+    }
+
+    /// Drop per-coin and per-account entries left with nothing set.
+    pub fn prune(&mut self) {
+        self.coins.retain(|_, d| *d != OrderDefaults::default());
+        self.accounts.retain(|_, l| *l != RiskLimits::default());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MarginMode {
+    Cross,
+    Isolated,
+}
+
+impl MarginMode {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "cross" => Ok(Self::Cross),
+            "isolated" => Ok(Self::Isolated),
+            _ => Err(anyhow!("Margin mode must be \"cross\" or \"isolated\"")),
+        }
+    }
+
+    pub fn is_cross(self) -> bool {
+        self == Self::Cross
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Cross => "cross",
+            Self::Isolated => "isolated",
+        }
+    }
+}
+
+/// Defaults for order flags left off the command line. Unset fields fall back
+/// to the global defaults, then to the built-in behavior.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderDefaults {
+    /// Market slippage percentage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slippage: Option<f64>,
+    /// Time-in-force for limit orders: Gtc, Ioc or Alo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tif: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reduce_only: Option<bool>,
+    /// Leverage to set before placing an order, if different
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leverage: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub margin_mode: Option<MarginMode>,
+}
+
+/// Keys accepted by `OrderDefaults::unset`.
+pub const DEFAULT_KEYS: &[&str] = &["slippage", "tif", "reduce-only", "leverage", "margin-mode"];
+
+impl OrderDefaults {
+    /// `self` with every default set in `overrides` replaced.
+    pub fn overridden_by(&self, overrides: &OrderDefaults) -> OrderDefaults {
+        OrderDefaults {
+            slippage: overrides.slippage.or(self.slippage),
+            tif: overrides.tif.clone().or_else(|| self.tif.clone()),
+            reduce_only: overrides.reduce_only.or(self.reduce_only),
+            leverage: overrides.leverage.or(self.leverage),
+            margin_mode: overrides.margin_mode.or(self.margin_mode),
+        }
+    }
+
+    /// Clear the default named by `key` (one of `DEFAULT_KEYS`). Returns
+    /// false for other keys.
+    pub fn unset(&mut self, key: &str) -> bool {
+        match key {
+            "slippage" => self.slippage = None,
+            "tif" => self.tif = None,
+            "reduce-only" => self.reduce_only = None,
+            "leverage" => self.leverage = None,
+            "margin-mode" => self.margin_mode = None,
+            _ => return false,
+        }
+        true
+    }
+}
+
+/// Keys accepted by `RiskLimits::unset`.
+pub const RISK_KEYS: &[&str] = &[
+    "max-order-usd",
+    "max-position-usd",
+    "max-leverage",
+    "allow-coins",
+    "deny-coins",
+    "confirm-above-usd",
+];
+
+/// Client-side trading limits, checked before an order is signed. Unset limits
+/// are not enforced. Reduce-only orders are always allowed so positions can
+/// be closed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskLimits {
+    /// Largest notional of a single order, in USD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_order_usd: Option<f64>,
+    /// Largest perp position notional per coin after an order, in USD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_position_usd: Option<f64>,
+    /// Largest account leverage (perp notional / account value) after an
+    /// order, and the highest leverage `set-leverage` accepts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_leverage: Option<u32>,
+    /// If not empty, only these coins may be traded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow_coins: Vec<String>,
+    /// Coins that may not be traded
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny_coins: Vec<String>,
+    /// Ask for confirmation before orders above this notional, in USD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_above_usd: Option<f64>,
+}
+
+/// An order as seen by the risk checks.
+#[derive(Debug, Clone, Copy)]
+pub struct OrderIntent<'a> {
+    pub coin: &'a str,
+    pub is_buy: bool,
+    pub size: f64,
+    /// Limit price, or the expected fill price for market and TWAP orders
+    pub price: f64,
+    pub reduce_only: bool,
+    pub is_spot: bool,
+}
+
+impl OrderIntent<'_> {
+    pub fn notional(&self) -> f64 {
+        self.size * self.price
+    }
+}
+
+/// Open perp positions and account value, for position and leverage limits.
+#[derive(Debug, Clone, Default)]
+pub struct Exposure {
+    pub account_value: f64,
+    /// Signed size and notional by coin
+    pub positions: HashMap<String, (f64, f64)>,
+}
+
+impl Exposure {
+    pub fn from_state(state: &ClearinghouseState) -> Self {
+        let mut exposure = Self {
+            account_value: state.margin_summary.account_value.parse().unwrap_or(0.0),
+            positions: HashMap::new(),
+        };
+        exposure.add_positions(state);
+        exposure
+    }
+
+    /// Add the positions of another clearinghouse (a builder dex) without
+    /// changing the account value.
+    pub fn add_positions(&mut self, state: &ClearinghouseState) {
+        self.positions.extend(state.asset_positions.iter().map(|p| {
+            let szi = p.position.szi.parse().unwrap_or(0.0);
+            let value: f64 = p.position.position_value.parse().unwrap_or(0.0);
+            (p.position.coin.clone(), (szi, value.abs()))
+        }));
+    }
+
+    fn total_notional(&self) -> f64 {
+        self.positions.values().map(|(_, value)| value).sum()
+    }
+}
+
+impl RiskLimits {
+    /// `self` with every limit set in `overrides` replaced.
+    pub fn overridden_by(&self, overrides: &RiskLimits) -> RiskLimits {
+        let coins = |own: &Vec<String>, other: &Vec<String>| {
+            if other.is_empty() {
+                own.clone()
+            } else {
+                other.clone()
+            }
+        };
+        RiskLimits {
+            max_order_usd: overrides.max_order_usd.or(self.max_order_usd),
+            max_position_usd: overrides.max_position_usd.or(self.max_position_usd),
+            max_leverage: overrides.max_leverage.or(self.max_leverage),
+            allow_coins: coins(&self.allow_coins, &overrides.allow_coins),
+            deny_coins: coins(&self.deny_coins, &overrides.deny_coins),
+            confirm_above_usd: overrides.confirm_above_usd.or(self.confirm_above_usd),
+        }
+    }
+
+    /// Clear the limit named by `key` (one of `RISK_KEYS`). Returns false for
+    /// other keys.
+    pub fn unset(&mut self, key: &str) -> bool {
+        match key {
+            "max-order-usd" => self.max_order_usd = None,
+            "max-position-usd" => self.max_position_usd = None,
+            "max-leverage" => self.max_leverage = None,
+            "allow-coins" => self.allow_coins.clear(),
+            "deny-coins" => self.deny_coins.clear(),
+            "confirm-above-usd" => self.confirm_above_usd = None,
+            _ => return false,
+        }
+        true
+    }
+
+    /// Whether checking orders needs the account's positions.
+    pub fn needs_exposure(&self) -> bool {
+        self.max_position_usd.is_some() || self.max_leverage.is_some()
+    }
+
+    pub fn check_coin(&self, coin: &str) -> Result<()> {
+        let listed = |coins: &[String]| coins.iter().any(|c| c.eq_ignore_ascii_case(coin));
+        if listed(&self.deny_coins) {
+            return Err(anyhow!("{coin} is on the deny list (denyCoins)"));
+        }
+        if !self.allow_coins.is_empty() && !listed(&self.allow_coins) {
+            return Err(anyhow!(
+                "{coin} is not on the allow list (allowCoins: {})",
+                self.allow_coins.join(", ")
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn check_leverage_setting(&self, leverage: u32) -> Result<()> {
+        match self.max_leverage {
+            Some(max) if leverage > max => Err(anyhow!(
+                "Leverage {leverage}x exceeds the {max}x limit (maxLeverage)"
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check one order, then add it to `exposure` so later orders in the same
+    /// submission see it.
+    pub fn check_order(&self, order: &OrderIntent, exposure: Option<&mut Exposure>) -> Result<()> {
+        if order.reduce_only {
+            return Ok(());
+        }
+        self.check_coin(order.coin)?;
+
+        let notional = order.notional();
+        if let Some(max) = self.max_order_usd
+            && notional > max
+        {
+            return Err(anyhow!(
+                "Order notional ${notional:.2} exceeds the ${max:.2} limit (maxOrderUsd)"
+            ));
+        }
+
+        let Some(exposure) = exposure.filter(|_| !order.is_spot) else {
+            return Ok(());
+        };
+        let (szi, _) = exposure
+            .positions
+            .get(order.coin)
+            .copied()
+            .unwrap_or_default();
+        let szi = szi
+            + if order.is_buy {
+                order.size
+            } else {
+                -order.size
+            };
+        let position = szi.abs() * order.price;
+        if let Some(max) = self.max_position_usd
+            && position > max
+        {
+            return Err(anyhow!(
+                "{} position would be ${position:.2}, above the ${max:.2} limit (maxPositionUsd)",
+                order.coin
+            ));
+        }
+
+        let mut after = exposure.clone();
+        after
+            .positions
+            .insert(order.coin.to_string(), (szi, position));
+        if let Some(max) = self.max_leverage {
+            let leverage = if after.account_value > 0.0 {
+                after.total_notional() / after.account_value
+            } else {
+                f64::INFINITY
+            };
+            if leverage > max as f64 {
+                return Err(anyhow!(
+                    "Account leverage would be {leverage:.2}x, above the {max}x limit (maxLeverage)"
+                ));
+            }
+        }
+        *exposure = after;
+        Ok(())
+    }
+
+    /// Check orders submitted together, in order.
+    pub fn check_orders(
+        &self,
+        orders: &[OrderIntent],
+        mut exposure: Option<Exposure>,
+    ) -> Result<()> {
+        orders
+            .iter()
+            .try_for_each(|order| self.check_order(order, exposure.as_mut()))
+    }
+
+    /// A prompt if `notional` is above the confirmation threshold.
+    pub fn confirmation(&self, notional: f64) -> Option<String> {
+        self.confirm_above_usd
+            .filter(|threshold| notional > *threshold)
+            .map(|threshold| {
+                format!("Order notional ${notional:.2} is above the ${threshold:.2} confirmation threshold (confirmAboveUsd)")
+            })
     }
 }
 
@@ -20,28 +405,50 @@ fn config_path() -> Result<PathBuf> {
     paths::order_config_path()
 }
 
-pub fn load_order_config() -> OrderConfig {
-    let path = match config_path() {
-        Ok(p) => p,
-        Err(_) => return OrderConfig::default(),
-    };
+/// Parse `order-config.json`, migrating older schema versions.
+pub fn parse_order_config(content: &str) -> Result<OrderConfig> {
+    let mut value: serde_json::Value = serde_json::from_str(content)?;
+    let object = value
+        .as_object_mut()
+        .ok_or_else(|| anyhow!("expected a JSON object"))?;
+    let version = object
+        .get("version")
+        .map(|v| {
+            v.as_u64()
+                .ok_or_else(|| anyhow!("version must be a number"))
+        })
+        .transpose()?
+        .unwrap_or(1);
+    if version > CONFIG_VERSION as u64 {
+        return Err(anyhow!(
+            "schema version {version} is newer than this rhl supports ({CONFIG_VERSION}); upgrade rhl"
+        ));
+    }
+    if version < 2 {
+        let slippage = object.remove("slippage");
+        if let Some(slippage) = slippage.filter(|s| s.as_f64() != Some(DEFAULT_SLIPPAGE)) {
+            object.insert(
+                "defaults".to_string(),
+                serde_json::json!({ "slippage": slippage }),
+            );
+        }
+    }
+    object.insert("version".to_string(), CONFIG_VERSION.into());
+    Ok(serde_json::from_value(value)?)
+}
 
+/// Load the order config; a missing file gives the defaults.
+pub fn load_order_config() -> Result<OrderConfig> {
+    let path = config_path()?;
     let content = match fs::read_to_string(&path) {
         Ok(c) => c,
-        Err(_) => return OrderConfig::default(),
-    };
-
-    let parsed: OrderConfig = match serde_json::from_str(&content) {
-        Ok(p) => p,
-        Err(_) => return OrderConfig::default(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(OrderConfig::default()),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
     };
-
-    OrderConfig {
-        slippage: parsed.slippage,
-    }
+    parse_order_config(&content).with_context(|| format!("Invalid {}", path.display()))
 }
 
-pub fn update_order_config(slippage: Option<f64>) -> Result<OrderConfig> {
+pub fn update_order_config(update: impl FnOnce(&mut OrderConfig)) -> Result<OrderConfig> {
     let path = config_path()?;
     let dir = path
         .parent()
@@ -50,13 +457,147 @@ pub fn update_order_config(slippage: Option<f64>) -> Result<OrderConfig> {
 
     fs::create_dir_all(&dir).with_context(|| format!("Failed to create dir: {}", dir.display()))?;
 
-    let mut current = load_order_config();
-    if let Some(s) = slippage {
-        current.slippage = s;
-    }
+    let mut current = load_order_config()?;
+    update(&mut current);
+    current.prune();
 
     fs::write(&path, serde_json::to_string_pretty(&current)?)
         .with_context(|| format!("Failed to write {}", path.display()))?;
 
     Ok(current)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eth_buy(size: f64) -> OrderIntent<'static> {
+        OrderIntent {
+            coin: "ETH",
+            is_buy: true,
+            size,
+            price: 3000.0,
+            reduce_only: false,
+            is_spot: false,
+        }
+    }
+
+    fn exposure() -> Exposure {
+        Exposure {
+            account_value: 10_000.0,
+            positions: HashMap::from([
+                ("ETH".to_string(), (-1.0, 3000.0)),
+                ("BTC".to_string(), (0.2, 12_000.0)),
+            ]),
+        }
+    }
+
+    #[test]
+    fn migrates_version_1_config() {
+        let cfg = parse_order_config(r#"{"slippage": 0.5}"#).unwrap();
+        assert_eq!(cfg.version, CONFIG_VERSION);
+        assert_eq!(cfg.defaults.slippage, Some(0.5));
+        assert_eq!(cfg.risk, RiskLimits::default());
+
+        let cfg = parse_order_config(r#"{"slippage": 1.0}"#).unwrap();
+        assert_eq!(cfg, OrderConfig::default());
+
+        assert!(parse_order_config(r#"{"version": 99}"#).is_err());
+    }
+
+    #[test]
+    fn coin_defaults_override_global_ones() {
+        let mut cfg = OrderConfig::default();
+        cfg.defaults.tif = Some("Alo".to_string());
+        cfg.defaults.leverage = Some(3);
+        cfg.coin_defaults_mut("BTC").slippage = Some(0.2);
+        cfg.coin_defaults_mut("btc").leverage = Some(10);
+        assert_eq!(cfg.coins.len(), 1);
+
+        let btc = cfg.defaults_for(Some("Btc"));
+        assert_eq!(btc.tif.as_deref(), Some("Alo"));
+        assert_eq!(btc.leverage, Some(10));
+        assert_eq!(cfg.slippage(Some("BTC")), 0.2);
+        assert_eq!(cfg.slippage(Some("ETH")), DEFAULT_SLIPPAGE);
+
+        assert!(cfg.coin_defaults_mut("BTC").unset("slippage"));
+        assert!(cfg.coin_defaults_mut("BTC").unset("leverage"));
+        assert!(!cfg.defaults.unset("max-order-usd"));
+        cfg.prune();
+        assert!(cfg.coins.is_empty());
+    }
+
+    #[test]
+    fn account_limits_override_global_ones() {
+        let mut cfg = OrderConfig::default();
+        cfg.risk.max_order_usd = Some(1000.0);
+        cfg.risk.deny_coins = vec!["DOGE".to_string()];
+        cfg.accounts.insert(
+            "scalp".to_string(),
+            RiskLimits {
+                max_order_usd: Some(50_000.0),
+                ..Default::default()
+            },
+        );
+        let limits = cfg.risk_limits(Some("scalp"));
+        assert_eq!(limits.max_order_usd, Some(50_000.0));
+        assert_eq!(limits.deny_coins, vec!["DOGE".to_string()]);
+        assert_eq!(cfg.risk_limits(None).max_order_usd, Some(1000.0));
+    }
+
+    #[test]
+    fn rejects_orders_over_limits_naming_the_limit() {
+        let limits = RiskLimits {
+            max_order_usd: Some(10_000.0),
+            allow_coins: vec!["eth".to_string(), "BTC".to_string()],
+            ..Default::default()
+        };
+        assert!(limits.check_order(&eth_buy(3.0), None).is_ok());
+        let err = limits.check_order(&eth_buy(4.0), None).unwrap_err();
+        assert!(err.to_string().contains("maxOrderUsd"));
+        let sol = OrderIntent {
+            coin: "SOL",
+            ..eth_buy(1.0)
+        };
+        assert!(limits.check_order(&sol, None).is_err());
+        let close = OrderIntent {
+            reduce_only: true,
+            ..sol
+        };
+        assert!(limits.check_order(&close, None).is_ok());
+    }
+
+    #[test]
+    fn position_and_leverage_limits_include_current_positions() {
+        let position = RiskLimits {
+            max_position_usd: Some(6000.0),
+            ..Default::default()
+        };
+        // Short 1 ETH: buying 3 leaves a 2 ETH long
+        assert!(
+            position
+                .check_order(&eth_buy(3.0), Some(&mut exposure()))
+                .is_ok()
+        );
+        let err = position
+            .check_order(&eth_buy(3.5), Some(&mut exposure()))
+            .unwrap_err();
+        assert!(err.to_string().contains("maxPositionUsd"));
+
+        let leverage = RiskLimits {
+            max_leverage: Some(2),
+            ..Default::default()
+        };
+        // 12k BTC + 6k ETH on 10k equity
+        assert!(
+            leverage
+                .check_orders(&[eth_buy(1.0), eth_buy(2.0)], Some(exposure()))
+                .is_ok()
+        );
+        let err = leverage
+            .check_orders(&[eth_buy(2.0), eth_buy(2.0)], Some(exposure()))
+            .unwrap_err();
+        assert!(err.to_string().contains("maxLeverage"));
+        assert!(leverage.check_leverage_setting(3).is_err());
+    }
+}
@@ -205,7 +205,7 @@ fn strip_ansi_codes(s: &str) -> Cow<'_, str> {
     while let Some(ch) = chars.next() {
         if ch == '\x1b' && chars.peek() == Some(&'[') {
             chars.next(); // '['
-            while let Some(c) = chars.next() {
+            for c in chars.by_ref() {
                 if c == 'm' {
                     break;
                 }
@@ -0,0 +1,198 @@
+use anyhow::{Result, anyhow};
+
+use crate::{
+    asset_index,
+    hl_api::{PerpMeta, SpotMeta},
+};
+
+const MAX_PRICE_SIG_FIGS: i32 = 5;
+const MAX_PERP_DECIMALS: u32 = 6;
+const MAX_SPOT_DECIMALS: u32 = 8;
+
+/// Tick and lot rules for one asset.
+///
+/// Prices may have at most 5 significant figures (integer prices are always
+/// allowed) and at most `6 - szDecimals` decimals for perps, `8 - szDecimals`
+/// for spot. Sizes are limited to `szDecimals` decimals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetPrecision {
+    pub sz_decimals: u32,
+    pub is_spot: bool,
+}
+
+impl AssetPrecision {
+    pub fn resolve(all_perp_metas: &[PerpMeta], spot_meta: &SpotMeta, coin: &str) -> Result<Self> {
+        let is_perp = all_perp_metas
+            .iter()
+            .any(|dex| dex.universe.iter().any(|a| a.name == coin));
+        Ok(Self {
+            sz_decimals: asset_index::size_decimals(all_perp_metas, spot_meta, coin)?,
+            is_spot: !is_perp,
+        })
+    }
+
+    pub fn max_price_decimals(&self) -> u32 {
+        let max = if self.is_spot {
+            MAX_SPOT_DECIMALS
+        } else {
+            MAX_PERP_DECIMALS
+        };
+        max.saturating_sub(self.sz_decimals)
+    }
+
+    fn price_decimals(&self, px: f64) -> u32 {
+        if px <= 0.0 {
+            return self.max_price_decimals();
+        }
+        let int_digits = px.log10().floor() as i32 + 1;
+        let sig_decimals = (MAX_PRICE_SIG_FIGS - int_digits).max(0) as u32;
+        sig_decimals.min(self.max_price_decimals())
+    }
+
+    /// Round to the nearest valid price.
+    pub fn round_price(&self, px: f64) -> f64 {
+        round_to(px, self.price_decimals(px))
+    }
+
+    /// Round to the nearest valid size.
+    pub fn round_size(&self, sz: f64) -> f64 {
+        round_to(sz, self.sz_decimals)
+    }
+
+    /// Round down to a valid size, for sizes that must not exceed a budget.
+    pub fn floor_size(&self, sz: f64) -> f64 {
+        let factor = 10f64.powi(self.sz_decimals as i32);
+        let scaled = sz * factor;
+        // A value already on a step may sit just below it in binary.
+        let steps = if approx_eq(scaled, scaled.round()) {
+            scaled.round()
+        } else {
+            scaled.floor()
+        };
+        steps / factor
+    }
+
+    /// Round and format a price for the wire (no trailing zeros).
+    pub fn format_price(&self, px: f64) -> String {
+        let rounded = self.round_price(px);
+        format_decimal(rounded, self.price_decimals(rounded))
+    }
+
+    /// Round and format a size for the wire (no trailing zeros).
+    pub fn format_size(&self, sz: f64) -> String {
+        format_decimal(self.round_size(sz), self.sz_decimals)
+    }
+
+    /// Round a user-supplied price. In strict mode, error instead of rounding.
+    pub fn check_price(&self, px: f64, name: &str, strict: bool) -> Result<f64> {
+        let rounded = self.round_price(px);
+        if rounded <= 0.0 {
+            return Err(anyhow!("{name} {px} rounds to zero"));
+        }
+        if strict && !approx_eq(rounded, px) {
+            return Err(anyhow!(
+                "{name} {px} is not a valid tick: max {MAX_PRICE_SIG_FIGS} significant figures and {} decimals (nearest: {})",
+                self.max_price_decimals(),
+                self.format_price(px)
+            ));
+        }
+        Ok(rounded)
+    }
+
+    /// Round a user-supplied size. In strict mode, error instead of rounding.
+    pub fn check_size(&self, sz: f64, strict: bool) -> Result<f64> {
+        let rounded = self.round_size(sz);
+        if rounded <= 0.0 {
+            return Err(anyhow!(
+                "Size {sz} rounds to zero at {} size decimals",
+                self.sz_decimals
+            ));
+        }
+        if strict && !approx_eq(rounded, sz) {
+            return Err(anyhow!(
+                "Size {sz} has more than {} decimals (nearest: {})",
+                self.sz_decimals,
+                self.format_size(sz)
+            ));
+        }
+        Ok(rounded)
+    }
+}
+
+fn round_to(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+}
+
+/// Format with at most `decimals` decimals, trimming trailing zeros.
+pub fn format_decimal(value: f64, decimals: u32) -> String {
+    let s = format!("{value:.*}", decimals as usize);
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERP_BTC: AssetPrecision = AssetPrecision {
+        sz_decimals: 5,
+        is_spot: false,
+    };
+    const PERP_ETH: AssetPrecision = AssetPrecision {
+        sz_decimals: 4,
+        is_spot: false,
+    };
+    const SPOT_PURR: AssetPrecision = AssetPrecision {
+        sz_decimals: 0,
+        is_spot: true,
+    };
+
+    #[test]
+    fn rounds_prices_to_sig_figs_and_decimals() {
+        assert_eq!(PERP_ETH.format_price(3000.123), "3000.1");
+        assert_eq!(PERP_ETH.format_price(3000.0), "3000");
+        assert_eq!(PERP_BTC.format_price(67123.45), "67123");
+        assert_eq!(PERP_BTC.format_price(123456.7), "123457");
+        // BTC allows only one decimal regardless of significant figures
+        assert_eq!(PERP_BTC.format_price(1.23456), "1.2");
+        assert_eq!(PERP_ETH.format_price(0.0123456), "0.01");
+        assert_eq!(SPOT_PURR.format_price(0.000123456), "0.00012346");
+    }
+
+    #[test]
+    fn rounds_sizes_to_sz_decimals() {
+        assert_eq!(PERP_ETH.format_size(1.23456), "1.2346");
+        assert_eq!(PERP_ETH.format_size(2.0), "2");
+        assert_eq!(SPOT_PURR.format_size(10.4), "10");
+    }
+
+    #[test]
+    fn floors_sizes_to_sz_decimals() {
+        assert_eq!(PERP_ETH.floor_size(1.23459), 1.2345);
+        assert_eq!(PERP_ETH.floor_size(0.1 + 0.2), 0.3);
+        assert_eq!(SPOT_PURR.floor_size(10.9), 10.0);
+    }
+
+    #[test]
+    fn strict_mode_rejects_off_tick_values() {
+        assert_eq!(PERP_ETH.check_price(3000.1, "Price", true).unwrap(), 3000.1);
+        assert_eq!(
+            PERP_ETH.check_price(3000.12, "Price", false).unwrap(),
+            3000.1
+        );
+        let err = PERP_ETH.check_price(3000.12, "Price", true).unwrap_err();
+        assert!(err.to_string().contains("nearest: 3000.1"));
+
+        assert_eq!(PERP_ETH.check_size(0.1234, true).unwrap(), 0.1234);
+        assert!(PERP_ETH.check_size(0.12345, true).is_err());
+        assert!(PERP_ETH.check_size(0.00001, false).is_err());
+    }
+}
@@ -10,7 +10,7 @@ use tokio::{
 use crate::paths;
 use crate::{
     hl_api::{AllDexsAssetCtxsEvent, PerpMeta, SpotAssetCtx, SpotMeta},
-    server::types::ServerStatus,
+    server::types::{AddTrailParams, ArmDeadManParams, DeadManStatus, ServerStatus, TrailStatus},
 };
 
 #[derive(Debug, Clone)]
@@ -127,6 +127,55 @@ impl ServerClient {
         Ok(())
     }
 
+    pub async fn arm_dead_man(&mut self, params: &ArmDeadManParams) -> Result<DeadManStatus> {
+        let resp = self
+            .request("armDeadMan", Some(serde_json::to_value(params)?))
+            .await?;
+        let value = resp.result.ok_or_else(|| anyhow!("Missing result"))?;
+        serde_json::from_value(value).context("Invalid armDeadMan result")
+    }
+
+    pub async fn disarm_dead_man(&mut self) -> Result<()> {
+        let _ = self.request("disarmDeadMan", None).await?;
+        Ok(())
+    }
+
+    /// Keep the dead-man's switch alive. Returns whether one is armed.
+    pub async fn heartbeat(&mut self) -> Result<bool> {
+        let resp = self.request("heartbeat", None).await?;
+        let value = resp.result.ok_or_else(|| anyhow!("Missing result"))?;
+        Ok(value
+            .get("armed")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false))
+    }
+
+    pub async fn add_trail(&mut self, params: &AddTrailParams) -> Result<TrailStatus> {
+        let resp = self
+            .request("addTrail", Some(serde_json::to_value(params)?))
+            .await?;
+        let value = resp.result.ok_or_else(|| anyhow!("Missing result"))?;
+        serde_json::from_value(value).context("Invalid addTrail result")
+    }
+
+    pub async fn list_trails(&mut self) -> Result<Vec<TrailStatus>> {
+        let resp = self.request("listTrails", None).await?;
+        let value = resp.result.ok_or_else(|| anyhow!("Missing result"))?;
+        serde_json::from_value(value).context("Invalid listTrails result")
+    }
+
+    /// Remove a trailing stop, or every trailing stop without an `id`.
+    /// Returns how many were removed.
+    pub async fn remove_trail(&mut self, id: Option<u64>) -> Result<usize> {
+        let params = match id {
+            Some(id) => serde_json::json!({ "id": id }),
+            None => serde_json::json!({ "all": true }),
+        };
+        let resp = self.request("removeTrail", Some(params)).await?;
+        let value = resp.result.ok_or_else(|| anyhow!("Missing result"))?;
+        Ok(value.get("removed").and_then(|v| v.as_u64()).unwrap_or(0) as usize)
+    }
+
     pub async fn get_prices(
         &mut self,
         coin: Option<&str>,
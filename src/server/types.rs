@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::trail::{TrailDistance, TrailingStop};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheStatus {
     #[serde(rename = "hasMids")]
@@ -33,4 +35,93 @@ pub struct ServerStatus {
     pub started_at: i64,
     pub uptime: i64,
     pub cache: CacheStatus,
+    #[serde(rename = "deadMan", default, skip_serializing_if = "Option::is_none")]
+    pub dead_man: Option<DeadManStatus>,
+    /// Number of trailing stops still following the price.
+    #[serde(rename = "activeTrails", default)]
+    pub active_trails: usize,
+}
+
+/// A dead-man's switch the server keeps re-arming while heartbeats arrive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadManStatus {
+    #[serde(rename = "timeoutMs")]
+    pub timeout_ms: i64,
+    #[serde(rename = "heartbeatFile")]
+    pub heartbeat_file: Option<String>,
+    #[serde(rename = "lastHeartbeat")]
+    pub last_heartbeat: i64,
+    /// When open orders will be cancelled unless the switch is re-armed.
+    #[serde(rename = "cancelAt")]
+    pub cancel_at: i64,
+    #[serde(rename = "lastError")]
+    pub last_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArmDeadManParams {
+    /// Account the server must sign for; guards against a server started
+    /// with a different default account.
+    pub user: String,
+    #[serde(rename = "timeoutMs")]
+    pub timeout_ms: i64,
+    /// Cancel time already scheduled by the client.
+    #[serde(rename = "cancelAt")]
+    pub cancel_at: i64,
+    #[serde(rename = "heartbeatFile")]
+    pub heartbeat_file: Option<String>,
+    /// Vault or sub-account the switch covers, traded with `--vault`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<String>,
+}
+
+/// A trailing stop for the server to follow; see [`TrailingStop`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddTrailParams {
+    /// Account the server must sign for.
+    pub user: String,
+    /// Vault or sub-account holding the position, traded with `--vault`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<String>,
+    pub coin: String,
+    pub asset: u32,
+    pub sz_decimals: u32,
+    pub is_long: bool,
+    /// Position size to close when the stop fires.
+    pub size: f64,
+    pub distance: TrailDistance,
+    /// Slippage of the closing order, as a fraction.
+    pub slippage: f64,
+    /// Price the trail starts from.
+    pub start_px: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TrailState {
+    Active,
+    /// The stop was crossed and the closing order is being sent.
+    Firing,
+    Fired,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrailStatus {
+    pub id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vault: Option<String>,
+    pub coin: String,
+    pub size: f64,
+    pub stop: TrailingStop,
+    pub stop_px: f64,
+    pub last_px: Option<f64>,
+    pub created_at: i64,
+    pub state: TrailState,
+    /// Outcome of the closing order, or the last error
+    pub result: Option<String>,
 }
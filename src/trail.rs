@@ -0,0 +1,180 @@
+use std::fmt;
+
+use anyhow::{Result, anyhow};
+use hyperliquid::types::exchange::request::{Limit, OrderRequest, OrderType, Tif};
+use serde::{Deserialize, Serialize};
+
+use crate::rounding::AssetPrecision;
+
+/// How far a trailing stop stays behind the best price seen.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "camelCase")]
+pub enum TrailDistance {
+    /// Percent of the best price, e.g. 1.5 for 1.5%
+    Percent(f64),
+    /// Fixed price amount
+    Absolute(f64),
+}
+
+impl TrailDistance {
+    /// Parse `1.5%` as a percentage or `50` as a price amount.
+    pub fn parse(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let (num, is_pct) = match value.strip_suffix('%') {
+            Some(num) => (num.trim(), true),
+            None => (value, false),
+        };
+        let num: f64 = num
+            .parse()
+            .ok()
+            .filter(|n: &f64| n.is_finite() && *n > 0.0)
+            .ok_or_else(|| anyhow!("Distance must be a positive amount or percentage like 1.5%"))?;
+        if is_pct {
+            if num >= 100.0 {
+                return Err(anyhow!("Distance percentage must be below 100%"));
+            }
+            Ok(Self::Percent(num))
+        } else {
+            Ok(Self::Absolute(num))
+        }
+    }
+
+    /// Price offset from `px`.
+    pub fn offset(&self, px: f64) -> f64 {
+        match self {
+            Self::Percent(pct) => px * pct / 100.0,
+            Self::Absolute(amount) => *amount,
+        }
+    }
+}
+
+impl fmt::Display for TrailDistance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Percent(pct) => write!(f, "{pct}%"),
+            Self::Absolute(amount) => write!(f, "{amount}"),
+        }
+    }
+}
+
+/// A stop that ratchets behind the best price since it started: below the
+/// high for a long position, above the low for a short one. It never moves
+/// back.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrailingStop {
+    /// Side of the position being protected; the stop closes it.
+    pub is_long: bool,
+    pub distance: TrailDistance,
+    /// Highest price seen for a long, lowest for a short
+    pub extreme_px: f64,
+}
+
+impl TrailingStop {
+    pub fn new(is_long: bool, distance: TrailDistance, px: f64) -> Self {
+        Self {
+            is_long,
+            distance,
+            extreme_px: px,
+        }
+    }
+
+    pub fn stop_px(&self) -> f64 {
+        let offset = self.distance.offset(self.extreme_px);
+        if self.is_long {
+            self.extreme_px - offset
+        } else {
+            self.extreme_px + offset
+        }
+    }
+
+    /// Follow a new price. Returns true once the price crosses the stop.
+    pub fn update(&mut self, px: f64) -> bool {
+        if self.is_long {
+            self.extreme_px = self.extreme_px.max(px);
+            px <= self.stop_px()
+        } else {
+            self.extreme_px = self.extreme_px.min(px);
+            px >= self.stop_px()
+        }
+    }
+
+    /// Reduce-only IOC order closing `size` of the position, priced
+    /// `slippage` (a fraction) through `mid_price`.
+    pub fn close_order(
+        &self,
+        asset: u32,
+        size: f64,
+        mid_price: f64,
+        slippage: f64,
+        precision: &AssetPrecision,
+    ) -> OrderRequest {
+        let is_buy = !self.is_long;
+        let limit_px = if is_buy {
+            mid_price * (1.0 + slippage)
+        } else {
+            mid_price * (1.0 - slippage)
+        };
+        OrderRequest {
+            asset,
+            is_buy,
+            limit_px: precision.format_price(limit_px),
+            sz: precision.format_size(size),
+            reduce_only: true,
+            order_type: OrderType::Limit(Limit { tif: Tif::Ioc }),
+            cloid: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_percent_and_absolute_distances() {
+        assert_eq!(
+            TrailDistance::parse("1.5%").unwrap(),
+            TrailDistance::Percent(1.5)
+        );
+        assert_eq!(
+            TrailDistance::parse("50").unwrap(),
+            TrailDistance::Absolute(50.0)
+        );
+        assert!(TrailDistance::parse("0%").is_err());
+        assert!(TrailDistance::parse("100%").is_err());
+        assert!(TrailDistance::parse("-5").is_err());
+        assert_eq!(TrailDistance::Percent(1.5).to_string(), "1.5%");
+    }
+
+    #[test]
+    fn long_stop_ratchets_up_and_fires_on_drop() {
+        let mut stop = TrailingStop::new(true, TrailDistance::Percent(10.0), 100.0);
+        assert_eq!(stop.stop_px(), 90.0);
+        assert!(!stop.update(120.0));
+        assert_eq!(stop.stop_px(), 108.0);
+        // Pulling back does not lower the stop
+        assert!(!stop.update(110.0));
+        assert_eq!(stop.extreme_px, 120.0);
+        assert!(stop.update(108.0));
+    }
+
+    #[test]
+    fn short_stop_ratchets_down_and_fires_on_rise() {
+        let mut stop = TrailingStop::new(false, TrailDistance::Absolute(5.0), 100.0);
+        assert!(!stop.update(90.0));
+        assert_eq!(stop.stop_px(), 95.0);
+        assert!(!stop.update(94.0));
+        assert!(stop.update(95.5));
+
+        let precision = AssetPrecision {
+            sz_decimals: 2,
+            is_spot: false,
+        };
+        let order = stop.close_order(3, 1.234, 95.5, 0.01, &precision);
+        assert!(order.is_buy);
+        assert!(order.reduce_only);
+        assert_eq!(order.sz, "1.23");
+        assert_eq!(order.limit_px, "96.455");
+    }
+}
@@ -1,8 +1,9 @@
-use std::cmp::Ordering;
+use std::{cmp::Ordering, time::Duration};
 
 use anyhow::{Result, anyhow};
 use ethers::types::Address;
 use hyperliquid::types::exchange::request::Tif;
+use uuid::Uuid;
 
 pub fn validate_address(value: &str) -> Result<Address> {
     if !value.starts_with("0x") || value.len() != 42 {
@@ -100,6 +101,67 @@ pub fn validate_tif(value: &str) -> Result<Tif> {
     }
 }
 
+pub fn validate_cloid(value: &str) -> Result<Uuid> {
+    let hex = value
+        .strip_prefix("0x")
+        .filter(|h| h.len() == 32 && h.chars().all(|c| c.is_ascii_hexdigit()))
+        .ok_or_else(|| {
+            anyhow!("Invalid client order ID: {value} (expected 0x followed by 32 hex characters)")
+        })?;
+    Uuid::parse_str(hex).map_err(|_| anyhow!("Invalid client order ID: {value}"))
+}
+
+pub fn format_cloid(cloid: &Uuid) -> String {
+    format!("0x{}", cloid.simple())
+}
+
+/// Parse a duration like `500ms`, `30s`, `5m`, `2h` or `7d`. A bare number is
+/// taken as seconds.
+pub fn validate_duration(value: &str, name: &str) -> Result<Duration> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(value.len());
+    let (num, unit) = value.split_at(split);
+    let num: f64 = num
+        .parse()
+        .ok()
+        .filter(|n: &f64| n.is_finite() && *n > 0.0)
+        .ok_or_else(|| anyhow!("{name} must be a positive duration like 30s, 5m or 2h"))?;
+    let unit_ms = match unit {
+        "ms" => 1.0,
+        "" | "s" => 1_000.0,
+        "m" => 60_000.0,
+        "h" => 3_600_000.0,
+        "d" => 86_400_000.0,
+        _ => {
+            return Err(anyhow!(
+                "{name} has an unknown unit \"{unit}\" (use ms, s, m, h or d)"
+            ));
+        }
+    };
+    Ok(Duration::from_millis((num * unit_ms).round() as u64))
+}
+
+/// Parse a point in time: a duration ago (`7d`, `12h`), a date
+/// (`2024-05-01`, midnight UTC) or an RFC 3339 timestamp. Returns Unix ms.
+pub fn validate_past_time(value: &str, name: &str, now_ms: i64) -> Result<i64> {
+    let value = value.trim();
+    if let Ok(t) = chrono::DateTime::parse_from_rfc3339(value) {
+        return Ok(t.timestamp_millis());
+    }
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date
+            .and_time(chrono::NaiveTime::MIN)
+            .and_utc()
+            .timestamp_millis());
+    }
+    let ago = validate_duration(value, name).map_err(|_| {
+        anyhow!("{name} must be a duration ago like 7d or 12h, a date like 2024-05-01, or an RFC 3339 time")
+    })?;
+    Ok(now_ms - ago.as_millis() as i64)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -156,6 +218,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn validates_positive_number() {
         assert_eq!(validate_positive_number("42", "value").unwrap(), 42.0);
         assert_eq!(validate_positive_number("3.14", "price").unwrap(), 3.14);
@@ -177,6 +240,24 @@ mod tests {
         assert!(validate_positive_u64("abc", "count").is_err());
     }
 
+    #[test]
+    fn validates_past_time() {
+        let now = 1_714_608_000_000; // 2024-05-02T00:00:00Z
+        assert_eq!(
+            validate_past_time("7d", "since", now).unwrap(),
+            now - 7 * 86_400_000
+        );
+        assert_eq!(
+            validate_past_time("2024-05-01", "since", now).unwrap(),
+            now - 86_400_000
+        );
+        assert_eq!(
+            validate_past_time("2024-05-01T12:00:00Z", "until", now).unwrap(),
+            now - 43_200_000
+        );
+        assert!(validate_past_time("yesterday", "since", now).is_err());
+    }
+
     #[test]
     fn validates_side() {
         assert_eq!(validate_side("buy").unwrap(), "buy");
@@ -207,4 +288,41 @@ mod tests {
         assert!(validate_tif("fok").is_err());
         assert!(validate_tif("").is_err());
     }
+
+    #[test]
+    fn validates_cloid() {
+        let cloid = "0x1234567890abcdef1234567890abcdef";
+        assert_eq!(format_cloid(&validate_cloid(cloid).unwrap()), cloid);
+        assert_eq!(
+            format_cloid(&validate_cloid("0x1234567890ABCDEF1234567890ABCDEF").unwrap()),
+            cloid
+        );
+        assert!(validate_cloid("1234567890abcdef1234567890abcdef").is_err());
+        assert!(validate_cloid("0x1234").is_err());
+        assert!(validate_cloid("0x1234567890abcdef1234567890abcdeg").is_err());
+    }
+
+    #[test]
+    fn validates_duration() {
+        assert_eq!(
+            validate_duration("500ms", "timeout").unwrap(),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            validate_duration("30", "timeout").unwrap(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            validate_duration("1.5m", "timeout").unwrap(),
+            Duration::from_secs(90)
+        );
+        assert_eq!(
+            validate_duration("7d", "since").unwrap(),
+            Duration::from_secs(7 * 86_400)
+        );
+
+        assert!(validate_duration("0s", "timeout").is_err());
+        assert!(validate_duration("5w", "timeout").is_err());
+        assert!(validate_duration("abc", "timeout").is_err());
+    }
 }
@@ -1,4 +1,4 @@
-use chrono::Local;
+use chrono::{DateTime, Local};
 
 pub const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
 pub const HIDE_CURSOR: &str = "\x1b[?25l";
@@ -20,6 +20,17 @@ pub fn format_timestamp() -> String {
     Local::now().format("%H:%M:%S").to_string()
 }
 
+/// Local date and time for a millisecond Unix timestamp.
+pub fn format_datetime_ms(ms: i64) -> String {
+    match DateTime::from_timestamp_millis(ms) {
+        Some(t) => t
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        None => ms.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -30,6 +30,14 @@ pub fn sub_order_updates(user: Address) -> serde_json::Value {
     serde_json::json!({ "type": "orderUpdates", "user": addr(user) })
 }
 
+pub fn sub_user_fills(user: Address) -> serde_json::Value {
+    serde_json::json!({ "type": "userFills", "user": addr(user) })
+}
+
+pub fn sub_user_twap_slice_fills(user: Address) -> serde_json::Value {
+    serde_json::json!({ "type": "userTwapSliceFills", "user": addr(user) })
+}
+
 pub fn sub_active_asset_data(user: Address, coin: &str) -> serde_json::Value {
     serde_json::json!({ "type": "activeAssetData", "user": addr(user), "coin": coin })
 }
@@ -96,13 +104,13 @@ impl WsClient {
         }
     }
 
-    pub fn channel<'a>(msg: &'a serde_json::Value) -> Result<&'a str> {
+    pub fn channel(msg: &serde_json::Value) -> Result<&str> {
         msg.get("channel")
             .and_then(|c| c.as_str())
             .ok_or_else(|| anyhow!("Missing channel in WebSocket message"))
     }
 
-    pub fn data<'a>(msg: &'a serde_json::Value) -> Result<&'a serde_json::Value> {
+    pub fn data(msg: &serde_json::Value) -> Result<&serde_json::Value> {
         msg.get("data")
             .ok_or_else(|| anyhow!("Missing data in WebSocket message"))
     }